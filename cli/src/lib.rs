@@ -0,0 +1,315 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_app::App;
+use clap::{Parser, ValueEnum};
+use crossflow::{
+    CrossflowExecutorApp, Diagram, DiagramElementMetadata, DiagramError, Outcome, RequestExt,
+    RunCommandsOnWorldExt, RustCodegenOptions, bevy_time::TimePlugin,
+};
+use std::{
+    fs::File,
+    io::{Read, stdin},
+    path::{Path, PathBuf},
+};
+
+pub use crossflow::DiagramElementRegistry;
+pub use std::error::Error;
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "crossflow",
+    about = "Validate and run workflow diagrams from the command line."
+)]
+pub struct Args {
+    #[clap(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Parser, Debug)]
+pub enum Commands {
+    /// Checks diagrams against the registry and reports every error that is found.
+    Validate(ValidateArgs),
+
+    /// Runs a diagram with a request and prints the response.
+    Run(RunArgs),
+
+    /// Prints the metadata of every element in the registry.
+    PrintRegistry,
+
+    /// Prints a JSON schema.
+    DumpSchema(DumpSchemaArgs),
+
+    /// Renders a diagram as a graph that can be embedded in documentation.
+    Export(ExportArgs),
+
+    /// Generates Rust code that builds the workflow of a diagram natively.
+    ///
+    /// Sections, scripts, switches, spreads, collects, gates, trims, delays,
+    /// timeouts and buffer access are not supported yet.
+    GenerateRust(GenerateRustArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ValidateArgs {
+    #[arg(required = true, help = "paths to the diagrams to validate")]
+    diagrams: Vec<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct RunArgs {
+    #[arg(help = "path to the diagram to run")]
+    diagram: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "path to a json file containing the request, or - to read it from stdin [default: -]"
+    )]
+    request: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DumpSchemaArgs {
+    #[arg(value_enum, default_value_t = SchemaKind::Diagram)]
+    schema: SchemaKind,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SchemaKind {
+    /// The schema of diagram files.
+    Diagram,
+    /// The schema of the output of print-registry.
+    Registry,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    #[arg(help = "path to the diagram to export")]
+    diagram: PathBuf,
+
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Dot)]
+    format: ExportFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// Graphviz DOT.
+    Dot,
+    /// Mermaid flowchart.
+    Mermaid,
+}
+
+#[derive(Parser, Debug)]
+pub struct GenerateRustArgs {
+    #[arg(help = "path to the diagram to generate code for")]
+    diagram: PathBuf,
+
+    #[arg(
+        long,
+        help = "the generated functions will be named build_<NAME> and spawn_<NAME> [default: diagram]"
+    )]
+    name: Option<String>,
+
+    #[arg(
+        long,
+        help = "rust type of the workflow requests, if it cannot be inferred"
+    )]
+    request: Option<String>,
+
+    #[arg(
+        long,
+        help = "rust type of the workflow responses, if it cannot be inferred"
+    )]
+    response: Option<String>,
+
+    #[arg(
+        long,
+        help = "name of the crate that will include the generated code, to refer to its types with crate::"
+    )]
+    crate_name: Option<String>,
+}
+
+/// This struct describes how the executor app should be set up for the `run`
+/// command. You can use this to add arbitrary systems and plugins to the app.
+pub struct ExecutorSetup {
+    /// The app, initialized with everything the executor needs.
+    pub app: App,
+    /// The registry with all necessary node builders, section builders, and
+    /// messages registered.
+    pub registry: DiagramElementRegistry,
+}
+
+impl ExecutorSetup {
+    /// Use a minimal setup.
+    pub fn minimal(registry: DiagramElementRegistry) -> Self {
+        Self {
+            app: App::new(),
+            registry,
+        }
+    }
+}
+
+pub fn run(registry: DiagramElementRegistry) -> Result<(), Box<dyn Error>> {
+    run_with_args(Args::parse(), registry)
+}
+
+pub fn run_with_args(args: Args, registry: DiagramElementRegistry) -> Result<(), Box<dyn Error>> {
+    run_custom_setup(Some(args), move || ExecutorSetup::minimal(registry))
+}
+
+/// Run the command line tool with a custom setup. Unlike the simpler run
+/// functions, this gives you the opportunity to create a custom [`App`],
+/// adding whatever systems and plugins to it that you would like.
+///
+/// # Arguments
+/// * `args` - Custom arguments for the command line tool.
+///   If `args` is set to [`None`], we will use the environment variable arguments.
+/// * `setup` - A closure used to set up the app and the registry.
+pub fn run_custom_setup(
+    args: Option<Args>,
+    setup: impl FnOnce() -> ExecutorSetup,
+) -> Result<(), Box<dyn Error>> {
+    let args = args.unwrap_or_else(Args::parse);
+    // Logs go to stderr so that they do not get mixed into the responses that
+    // are printed to stdout.
+    let _ = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .try_init();
+
+    match args.command {
+        Commands::Validate(args) => validate(args, &setup().registry),
+        Commands::Run(args) => run_diagram(args, setup()),
+        Commands::PrintRegistry => print_registry(&setup().registry),
+        Commands::DumpSchema(args) => dump_schema(args),
+        Commands::Export(args) => export(args),
+        Commands::GenerateRust(args) => generate_rust(args, &setup().registry),
+    }
+}
+
+pub fn validate(
+    args: ValidateArgs,
+    registry: &DiagramElementRegistry,
+) -> Result<(), Box<dyn Error>> {
+    let mut error_count = 0;
+    for path in &args.diagrams {
+        let errors = validate_diagram(path, registry)?;
+        for error in &errors {
+            println!("{}: {}", path.display(), error.to_string().trim_start());
+        }
+        error_count += errors.len();
+    }
+
+    if error_count > 0 {
+        return Err(format!("found {error_count} error(s) in the diagrams").into());
+    }
+
+    Ok(())
+}
+
+fn validate_diagram(
+    path: &Path,
+    registry: &DiagramElementRegistry,
+) -> Result<Vec<DiagramError>, Box<dyn Error>> {
+    let diagram = load_diagram(path)?;
+    Ok(diagram.validate_json(registry))
+}
+
+pub fn run_diagram(args: RunArgs, setup: ExecutorSetup) -> Result<(), Box<dyn Error>> {
+    let ExecutorSetup { mut app, registry } = setup;
+    app.add_plugins(CrossflowExecutorApp::default());
+    // Delay and timeout operations need a clock.
+    if !app.is_plugin_added::<TimePlugin>() {
+        app.add_plugins(TimePlugin);
+    }
+    let diagram = load_diagram(&args.diagram)?;
+
+    let request: serde_json::Value = match args.request {
+        Some(path) if path.as_os_str() != "-" => serde_json::from_reader(File::open(path)?)?,
+        _ => {
+            let mut request = String::new();
+            stdin().read_to_string(&mut request)?;
+            serde_json::from_str(&request)?
+        }
+    };
+
+    let mut outcome =
+        app.world_mut()
+            .command(|cmds| -> Result<Outcome<serde_json::Value>, DiagramError> {
+                let workflow = diagram.spawn_io_workflow(cmds, &registry)?;
+                Ok(cmds.request(request, workflow).outcome())
+            })?;
+
+    while outcome.is_pending() {
+        app.update();
+    }
+
+    match outcome.try_recv() {
+        Some(Ok(response)) => {
+            println!("{response}");
+            Ok(())
+        }
+        Some(Err(err)) => Err(err.to_string().into()),
+        None => Err("the workflow finished without a response".into()),
+    }
+}
+
+pub fn print_registry(registry: &DiagramElementRegistry) -> Result<(), Box<dyn Error>> {
+    println!("{}", serde_json::to_string_pretty(&registry.metadata())?);
+    Ok(())
+}
+
+pub fn dump_schema(args: DumpSchemaArgs) -> Result<(), Box<dyn Error>> {
+    let schema = match args.schema {
+        SchemaKind::Diagram => schemars::schema_for!(Diagram),
+        SchemaKind::Registry => schemars::schema_for!(DiagramElementMetadata),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+pub fn export(args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    let diagram = load_diagram(&args.diagram)?;
+    let graph = match args.format {
+        ExportFormat::Dot => diagram.to_dot(),
+        ExportFormat::Mermaid => diagram.to_mermaid(),
+    };
+    print!("{graph}");
+    Ok(())
+}
+
+pub fn generate_rust(
+    args: GenerateRustArgs,
+    registry: &DiagramElementRegistry,
+) -> Result<(), Box<dyn Error>> {
+    let diagram = load_diagram(&args.diagram)?;
+    let options = RustCodegenOptions {
+        name: args.name,
+        request: args.request,
+        response: args.response,
+        crate_name: args.crate_name,
+    };
+    print!("{}", diagram.generate_rust(registry, &options)?);
+    Ok(())
+}
+
+fn load_diagram(path: &Path) -> Result<Diagram, Box<dyn Error>> {
+    let file =
+        File::open(path).map_err(|err| format!("unable to open {}: {err}", path.display()))?;
+    Diagram::from_reader(file)
+        .map_err(|err| format!("unable to parse {}: {err}", path.display()).into())
+}
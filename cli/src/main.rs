@@ -0,0 +1,25 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crossflow_cli::{DiagramElementRegistry, Error};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Only the elements that are built into crossflow are available. Executors
+    // with their own node builders should call crossflow_cli::run from their
+    // own binary.
+    crossflow_cli::run(DiagramElementRegistry::new())
+}
@@ -0,0 +1,123 @@
+use assert_cmd::{Command, cargo};
+
+fn crossflow() -> Command {
+    Command::new(cargo::cargo_bin!("crossflow"))
+}
+
+#[test]
+fn validate_valid_diagram() {
+    crossflow()
+        .args(["validate", "tests/diagrams/passthrough.json"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn validate_reports_every_error() {
+    let output = crossflow()
+        .args(["validate", "tests/diagrams/broken.json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("[missing_builder]"), "{output}");
+    assert!(output.contains("[another_missing_builder]"), "{output}");
+}
+
+#[test]
+fn run_with_request_from_stdin() {
+    crossflow()
+        .args(["run", "tests/diagrams/passthrough.json"])
+        .write_stdin(r#"{"value": 4}"#)
+        .assert()
+        .success()
+        .stdout("{\"value\":4}\n");
+}
+
+#[test]
+fn print_registry() {
+    let output = crossflow()
+        .arg("print-registry")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let registry: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(registry.get("messages").is_some());
+}
+
+#[test]
+fn dump_schema() {
+    let output = crossflow()
+        .args(["dump-schema", "diagram"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(schema["title"], "Diagram");
+}
+
+#[test]
+fn export_diagram() {
+    let output = crossflow()
+        .args(["export", "tests/diagrams/passthrough.json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(output).unwrap().starts_with("digraph"));
+
+    let output = crossflow()
+        .args([
+            "export",
+            "tests/diagrams/passthrough.json",
+            "--format",
+            "mermaid",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(
+        String::from_utf8(output)
+            .unwrap()
+            .starts_with("flowchart TD")
+    );
+}
+
+#[test]
+fn generate_rust() {
+    let output = crossflow()
+        .args([
+            "generate-rust",
+            "tests/diagrams/passthrough.json",
+            "--request",
+            "::crossflow::JsonMessage",
+            "--response",
+            "::crossflow::JsonMessage",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let code = String::from_utf8(output).unwrap();
+    assert!(code.contains("pub fn build_diagram("), "{code}");
+    assert!(
+        code.contains("builder.connect(scope.start, scope.terminate);"),
+        "{code}"
+    );
+
+    crossflow()
+        .args(["generate-rust", "tests/diagrams/passthrough.json"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,568 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use bevy_ecs::prelude::{Entity, World};
+use crossflow::{
+    Gate, JsonBuffer, JsonBufferInspection, JsonBufferWorldAccess, RequestId, WorkflowOperationsExt,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{
+    debug::find_operations,
+    executor::ExecutorState,
+    runs::{ActiveRun, parse_run_id, run_id},
+};
+
+/// The contents of a buffer in a run, listed per session of the buffer's scope.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferContents {
+    /// Name of the buffer operation in the diagram.
+    pub operation: String,
+    pub message_type: String,
+    pub sessions: Vec<BufferSessionContents>,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferSessionContents {
+    pub session: String,
+    pub gate_open: bool,
+    /// The messages in the buffer, from oldest to newest.
+    pub messages: Vec<serde_json::Value>,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushBufferRequest {
+    pub message: serde_json::Value,
+    /// Which session of the buffer to push into. This can be left out if the
+    /// buffer has only one active session in the run.
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// A request to inspect or modify the buffers of a run.
+pub struct BufferRequest {
+    run: Entity,
+    action: BufferAction,
+    reply: tokio::sync::oneshot::Sender<BufferResult>,
+}
+
+pub(super) enum BufferAction {
+    List {
+        operation: Option<String>,
+    },
+    Push {
+        operation: String,
+        session: Option<String>,
+        message: serde_json::Value,
+    },
+    /// Remove every message from the buffer in all sessions of the run.
+    Clear {
+        operation: String,
+    },
+}
+
+type BufferResult = Result<Vec<BufferContents>, BufferInspectionError>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum BufferInspectionError {
+    #[error("no active run with id [{0}]")]
+    RunNotFound(String),
+    #[error("no buffer named [{0}] can be inspected in this run")]
+    BufferNotFound(String),
+    #[error("unknown session [{0}] for this buffer")]
+    SessionNotFound(String),
+    #[error("buffer [{0}] has {1} active sessions, so a session must be specified")]
+    AmbiguousSession(String, usize),
+    #[error("{0}")]
+    Rejected(String),
+    #[error("the executor is unavailable")]
+    Unavailable,
+}
+
+impl IntoResponse for BufferInspectionError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::RunNotFound(_) | Self::BufferNotFound(_) | Self::SessionNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            Self::AmbiguousSession(..) | Self::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Unavailable => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+impl ExecutorState {
+    pub(super) async fn request_buffers(&self, run: Entity, action: BufferAction) -> BufferResult {
+        let (reply, reply_rx) = tokio::sync::oneshot::channel();
+        self.buffer_chan
+            .send(BufferRequest { run, action, reply })
+            .await
+            .map_err(|err| {
+                error!("{err}");
+                BufferInspectionError::Unavailable
+            })?;
+        reply_rx.await.map_err(|err| {
+            error!("{err}");
+            BufferInspectionError::Unavailable
+        })?
+    }
+}
+
+fn parse_run(run: &str) -> Result<Entity, BufferInspectionError> {
+    parse_run_id(run).ok_or_else(|| BufferInspectionError::RunNotFound(run.to_owned()))
+}
+
+/// List the contents of every buffer in a run.
+pub async fn get_buffers(
+    state: State<ExecutorState>,
+    Path(run): Path<String>,
+) -> Result<Json<Vec<BufferContents>>, BufferInspectionError> {
+    let buffers = state
+        .request_buffers(parse_run(&run)?, BufferAction::List { operation: None })
+        .await?;
+    Ok(Json(buffers))
+}
+
+/// Get the contents of one buffer in a run.
+pub async fn get_buffer(
+    state: State<ExecutorState>,
+    Path((run, operation)): Path<(String, String)>,
+) -> Result<Json<BufferContents>, BufferInspectionError> {
+    let buffers = state
+        .request_buffers(
+            parse_run(&run)?,
+            BufferAction::List {
+                operation: Some(operation.clone()),
+            },
+        )
+        .await?;
+    buffers
+        .into_iter()
+        .next()
+        .map(Json)
+        .ok_or(BufferInspectionError::BufferNotFound(operation))
+}
+
+/// Push a message into a buffer of a run and get the new buffer contents.
+pub async fn post_buffer(
+    state: State<ExecutorState>,
+    Path((run, operation)): Path<(String, String)>,
+    Json(body): Json<PushBufferRequest>,
+) -> Result<Json<Vec<BufferContents>>, BufferInspectionError> {
+    let buffers = state
+        .request_buffers(
+            parse_run(&run)?,
+            BufferAction::Push {
+                operation,
+                session: body.session,
+                message: body.message,
+            },
+        )
+        .await?;
+    Ok(Json(buffers))
+}
+
+/// Remove all messages from a buffer of a run and get the new buffer contents.
+pub async fn delete_buffer(
+    state: State<ExecutorState>,
+    Path((run, operation)): Path<(String, String)>,
+) -> Result<Json<Vec<BufferContents>>, BufferInspectionError> {
+    let buffers = state
+        .request_buffers(parse_run(&run)?, BufferAction::Clear { operation })
+        .await?;
+    Ok(Json(buffers))
+}
+
+/// Messages that the client can send after an interaction session has started
+/// in order to inspect or modify the buffers of the run.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum InteractionBufferCommand {
+    /// Get the contents of one buffer, or all buffers if no operation is given.
+    InspectBuffers {
+        #[serde(default)]
+        operation: Option<String>,
+    },
+    PushBuffer {
+        operation: String,
+        message: serde_json::Value,
+        #[serde(default)]
+        session: Option<String>,
+    },
+    ClearBuffer {
+        operation: String,
+    },
+}
+
+impl From<InteractionBufferCommand> for BufferAction {
+    fn from(command: InteractionBufferCommand) -> Self {
+        match command {
+            InteractionBufferCommand::InspectBuffers { operation } => Self::List { operation },
+            InteractionBufferCommand::PushBuffer {
+                operation,
+                message,
+                session,
+            } => Self::Push {
+                operation,
+                session,
+                message,
+            },
+            InteractionBufferCommand::ClearBuffer { operation } => Self::Clear { operation },
+        }
+    }
+}
+
+/// Receiver for requests to inspect or modify the buffers of runs.
+#[derive(bevy_ecs::prelude::Resource)]
+struct BufferRequestReceiver(tokio::sync::mpsc::Receiver<BufferRequest>);
+
+pub(super) fn setup(app: &mut bevy_app::SubApp) -> tokio::sync::mpsc::Sender<BufferRequest> {
+    let (buffer_tx, buffer_rx) = tokio::sync::mpsc::channel(10);
+    app.insert_resource(BufferRequestReceiver(buffer_rx));
+    app.add_systems(bevy_app::Update, handle_buffer_requests);
+    buffer_tx
+}
+
+fn handle_buffer_requests(world: &mut World) {
+    world.resource_scope::<BufferRequestReceiver, _>(|world, mut receiver| {
+        while let Ok(request) = receiver.0.try_recv() {
+            let result = apply_buffer_action(world, request.run, request.action);
+            let _ = request.reply.send(result);
+        }
+    });
+}
+
+fn apply_buffer_action(world: &mut World, run: Entity, action: BufferAction) -> BufferResult {
+    let workflow = world
+        .get::<ActiveRun>(run)
+        .ok_or_else(|| BufferInspectionError::RunNotFound(run_id(run)))?
+        .workflow;
+
+    match action {
+        BufferAction::List { operation: None } => {
+            let mut buffers: Vec<_> = world
+                .workflow_operations(workflow)
+                .into_iter()
+                .filter_map(|(entity, id)| {
+                    world
+                        .inspect_json_buffer(entity)
+                        .map(|buffer| (id.to_string(), buffer))
+                })
+                .collect();
+            buffers.sort_by(|(a, _), (b, _)| a.cmp(b));
+            buffers
+                .into_iter()
+                .map(|(name, buffer)| buffer_contents(world, name, &buffer, run))
+                .collect()
+        }
+        BufferAction::List {
+            operation: Some(operation),
+        } => {
+            let buffer = find_buffer(world, workflow, &operation)?;
+            Ok(vec![buffer_contents(world, operation, &buffer, run)?])
+        }
+        BufferAction::Push {
+            operation,
+            session,
+            message,
+        } => {
+            let buffer = find_buffer(world, workflow, &operation)?;
+            let mut sessions = world.json_buffer_sessions_within(&buffer, run);
+            let session = match session {
+                Some(session) => parse_run_id(&session)
+                    .filter(|s| sessions.contains(s))
+                    .ok_or(BufferInspectionError::SessionNotFound(session))?,
+                None => {
+                    if sessions.len() != 1 {
+                        return Err(BufferInspectionError::AmbiguousSession(
+                            operation,
+                            sessions.len(),
+                        ));
+                    }
+                    sessions.remove(0)
+                }
+            };
+
+            modify_buffer(world, &buffer, session, |mut access| {
+                access.push_json(message).map(|_| ())
+            })?;
+            Ok(vec![buffer_contents(world, operation, &buffer, run)?])
+        }
+        BufferAction::Clear { operation } => {
+            let buffer = find_buffer(world, workflow, &operation)?;
+            for session in world.json_buffer_sessions_within(&buffer, run) {
+                modify_buffer(world, &buffer, session, |mut access| {
+                    access.drain(..).for_each(drop);
+                    Ok(())
+                })?;
+            }
+            Ok(vec![buffer_contents(world, operation, &buffer, run)?])
+        }
+    }
+}
+
+fn find_buffer(
+    world: &mut World,
+    workflow: Entity,
+    name: &str,
+) -> Result<JsonBuffer, BufferInspectionError> {
+    find_operations(world, workflow, name)
+        .ok()
+        .and_then(|operations| {
+            operations
+                .into_iter()
+                .find_map(|op| world.inspect_json_buffer(op))
+        })
+        .ok_or_else(|| BufferInspectionError::BufferNotFound(name.to_owned()))
+}
+
+fn modify_buffer(
+    world: &mut World,
+    buffer: &JsonBuffer,
+    session: Entity,
+    f: impl FnOnce(crossflow::JsonBufferMut) -> Result<(), serde_json::Error>,
+) -> Result<(), BufferInspectionError> {
+    let rejected = |err: &dyn std::fmt::Display| BufferInspectionError::Rejected(err.to_string());
+    let key = world
+        .inspection_key(buffer, session)
+        .map_err(|err| rejected(&err))?;
+    let req = RequestId {
+        session,
+        source: buffer.id(),
+        seq: 0,
+    };
+    world
+        .json_buffer_mut(req, &key, f)
+        .map_err(|err| rejected(&err))?
+        .map_err(|err| rejected(&err))
+}
+
+fn buffer_contents(
+    world: &mut World,
+    operation: String,
+    buffer: &JsonBuffer,
+    run: Entity,
+) -> Result<BufferContents, BufferInspectionError> {
+    let rejected = |err: &dyn std::fmt::Display| BufferInspectionError::Rejected(err.to_string());
+    let mut sessions = Vec::new();
+    for session in world.json_buffer_sessions_within(buffer, run) {
+        let key = world
+            .inspection_key(buffer, session)
+            .map_err(|err| rejected(&err))?;
+        let view = world
+            .json_buffer_view_untraced(&key)
+            .map_err(|err| rejected(&err))?;
+        let messages = view
+            .iter()
+            .map(|message| message.serialize().map_err(|err| rejected(&err)))
+            .collect::<Result<_, _>>()?;
+        sessions.push(BufferSessionContents {
+            session: run_id(session),
+            gate_open: matches!(view.gate(), Gate::Open),
+            messages,
+        });
+    }
+
+    Ok(BufferContents {
+        operation,
+        message_type: crossflow::AnyBuffer::from(*buffer)
+            .message_type_name()
+            .to_owned(),
+        sessions,
+    })
+}
+
+#[cfg(feature = "router")]
+#[cfg(test)]
+mod tests {
+    use axum::extract::ws;
+    use crossflow::Diagram;
+    use futures_util::SinkExt;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::{
+        debug::InteractionDebugCommand,
+        executor::{
+            InteractionSessionEnd, InteractionSessionMessage, InteractionStartRequest,
+            PostRunRequest,
+            tests::{WsTestFixture, next_interaction_message, send_debug_command, setup_ws_test},
+            ws_interaction,
+        },
+    };
+
+    async fn send_buffer_command(
+        tx: &mut futures_channel::mpsc::Sender<Result<ws::Message, axum::Error>>,
+        command: InteractionBufferCommand,
+    ) {
+        tx.send(Ok(ws::Message::Text(
+            serde_json::to_string(&command).unwrap().into(),
+        )))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_inspect_buffers() {
+        let WsTestFixture {
+            executor_state,
+            cleanup_test,
+        } = setup_ws_test();
+
+        // The input gets copied into a buffer while add7 is held at a
+        // breakpoint, which keeps the run alive for inspection.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fork",
+            "ops": {
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["pending", "add7"],
+                },
+                "pending": {
+                    "type": "buffer",
+                    "settings": { "retention": "keep_all" },
+                },
+                "add7": {
+                    "type": "node",
+                    "builder": "add7",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let request_body = InteractionStartRequest {
+            run: PostRunRequest {
+                diagram,
+                request: serde_json::Value::from(5),
+            },
+            breakpoints: vec!["add7".to_string()],
+            pause: false,
+        };
+
+        let (socket_write, mut test_rx) = futures_channel::mpsc::channel(1024);
+        let (mut test_tx, socket_read) = futures_channel::mpsc::channel(1024);
+
+        tokio::spawn(ws_interaction(
+            socket_write,
+            socket_read,
+            State(executor_state.clone()),
+        ));
+
+        test_tx
+            .send(Ok(ws::Message::Text(
+                serde_json::to_string(&request_body).unwrap().into(),
+            )))
+            .await
+            .unwrap();
+
+        let run = match next_interaction_message(&mut test_rx).await {
+            InteractionSessionMessage::Started { run } => run,
+            _ => panic!("expected the run to start"),
+        };
+        let msg = next_interaction_message(&mut test_rx).await;
+        assert!(matches!(msg, InteractionSessionMessage::Paused { .. }));
+
+        // Inspect over REST
+        let Json(buffers) = get_buffers(State(executor_state.clone()), Path(run.clone()))
+            .await
+            .unwrap();
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(buffers[0].operation, "pending");
+        assert_eq!(buffers[0].sessions.len(), 1);
+        assert!(buffers[0].sessions[0].gate_open);
+        assert_eq!(buffers[0].sessions[0].messages, [json!(5)]);
+
+        let Json(buffers) = post_buffer(
+            State(executor_state.clone()),
+            Path((run.clone(), "pending".to_string())),
+            Json(PushBufferRequest {
+                message: json!(10),
+                session: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(buffers[0].sessions[0].messages, [json!(5), json!(10)]);
+
+        let err = get_buffer(
+            State(executor_state.clone()),
+            Path((run.clone(), "add7".to_string())),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, BufferInspectionError::BufferNotFound(_)));
+
+        // Inspect and clear over the websocket
+        send_buffer_command(
+            &mut test_tx,
+            InteractionBufferCommand::InspectBuffers {
+                operation: Some("pending".to_string()),
+            },
+        )
+        .await;
+        match next_interaction_message(&mut test_rx).await {
+            InteractionSessionMessage::Buffers { buffers } => {
+                assert_eq!(buffers[0].sessions[0].messages, [json!(5), json!(10)]);
+            }
+            _ => panic!("expected buffer contents"),
+        }
+
+        send_buffer_command(
+            &mut test_tx,
+            InteractionBufferCommand::ClearBuffer {
+                operation: "pending".to_string(),
+            },
+        )
+        .await;
+        match next_interaction_message(&mut test_rx).await {
+            InteractionSessionMessage::Buffers { buffers } => {
+                assert!(buffers[0].sessions[0].messages.is_empty());
+            }
+            _ => panic!("expected buffer contents"),
+        }
+
+        send_debug_command(
+            &mut test_tx,
+            InteractionDebugCommand::ClearBreakpoint {
+                operation: "add7".to_string(),
+            },
+        )
+        .await;
+        send_debug_command(&mut test_tx, InteractionDebugCommand::Unpause).await;
+        let resp = loop {
+            if let InteractionSessionMessage::Finish(end) =
+                next_interaction_message(&mut test_rx).await
+            {
+                break end;
+            }
+        };
+        assert!(matches!(
+            resp,
+            InteractionSessionEnd::Ok(value) if value == 12
+        ));
+
+        // The run is gone once it has finished
+        let err = get_buffers(State(executor_state), Path(run))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BufferInspectionError::RunNotFound(_)));
+
+        cleanup_test();
+    }
+}
@@ -0,0 +1,389 @@
+use bevy_ecs::{
+    prelude::{Entity, World},
+    system::Command,
+};
+use crossflow::{DebugStep, WorkflowOperationsExt};
+#[cfg(feature = "router")]
+use crossflow::{PauseCause, SessionChange, TracedEvent, TracedEventKind};
+use serde::Deserialize;
+#[cfg(feature = "router")]
+use std::collections::HashMap;
+#[cfg(feature = "router")]
+use tracing::error;
+
+#[cfg(feature = "router")]
+use super::{
+    executor::{ExecutorState, InteractionCommand, InteractionSessionMessage},
+    websocket::WebsocketSinkExt,
+};
+
+/// A request to change the debugging state of a running session.
+pub struct DebugRequest {
+    pub(super) session: Entity,
+    pub(super) workflow: Entity,
+    pub(super) action: DebugAction,
+    pub(super) reply: Option<tokio::sync::oneshot::Sender<DebugResult>>,
+}
+
+#[cfg_attr(not(feature = "router"), allow(dead_code))]
+pub(super) enum DebugAction {
+    Command(InteractionDebugCommand),
+    /// The client is no longer debugging, so stop debugging the session and
+    /// clear these breakpoints.
+    Finish(Vec<Entity>),
+}
+
+/// The entities that a debug command applied to, or the reason it failed.
+pub(super) type DebugResult = Result<Vec<Entity>, String>;
+
+/// Messages that the client can send after an interaction session has started
+/// in order to debug the run. Operations are referred to by the same names
+/// that are used in [`InteractionSessionFeedback`](super::executor::InteractionSessionFeedback).
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum InteractionDebugCommand {
+    SetBreakpoint {
+        operation: String,
+    },
+    ClearBreakpoint {
+        operation: String,
+    },
+    Pause,
+    /// Resume a paused session. A session that is paused at a breakpoint will
+    /// pause there again unless it is stepped past the breakpoint first.
+    Unpause,
+    /// Let a paused session take one step forward. If an operation is given,
+    /// only that operation will be allowed to take its next message.
+    Step {
+        #[serde(default)]
+        operation: Option<String>,
+    },
+}
+
+/// Keeps track of the debugging state of one interaction session.
+#[cfg(feature = "router")]
+pub(super) struct InteractionDebugger {
+    pub(super) state: ExecutorState,
+    pub(super) session: Entity,
+    pub(super) workflow: Entity,
+    /// The names of the operations that breakpoints have been set on.
+    pub(super) breakpoints: HashMap<Entity, String>,
+    pub(super) finished: bool,
+}
+
+#[cfg(feature = "router")]
+impl InteractionDebugger {
+    async fn request(&self, action: DebugAction) -> DebugResult {
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.state
+            .debug_chan
+            .send(DebugRequest {
+                session: self.session,
+                workflow: self.workflow,
+                action,
+                reply: Some(reply_tx),
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+        reply_rx.await.map_err(|err| err.to_string())?
+    }
+
+    pub(super) async fn apply<W>(&mut self, command: InteractionCommand, write: &mut W)
+    where
+        W: WebsocketSinkExt<InteractionSessionMessage>,
+    {
+        match command {
+            InteractionCommand::Debug(command) => {
+                let result = self.request(DebugAction::Command(command.clone())).await;
+                self.record(command, result, write).await;
+            }
+            InteractionCommand::Buffer(command) => {
+                let msg = match self
+                    .state
+                    .request_buffers(self.session, command.into())
+                    .await
+                {
+                    Ok(buffers) => InteractionSessionMessage::Buffers { buffers },
+                    Err(err) => InteractionSessionMessage::DebugError {
+                        message: err.to_string(),
+                    },
+                };
+                write.send_json(&msg).await;
+            }
+        }
+    }
+
+    /// Remember which breakpoints were set by a command, or tell the client
+    /// why the command failed.
+    pub(super) async fn record<W>(
+        &mut self,
+        command: InteractionDebugCommand,
+        result: DebugResult,
+        write: &mut W,
+    ) where
+        W: WebsocketSinkExt<InteractionSessionMessage>,
+    {
+        let entities = match result {
+            Ok(entities) => entities,
+            Err(message) => {
+                write
+                    .send_json(&InteractionSessionMessage::DebugError { message })
+                    .await;
+                return;
+            }
+        };
+
+        match command {
+            InteractionDebugCommand::SetBreakpoint { operation } => {
+                for entity in entities {
+                    self.breakpoints.insert(entity, operation.clone());
+                }
+            }
+            InteractionDebugCommand::ClearBreakpoint { .. } => {
+                for entity in entities {
+                    self.breakpoints.remove(&entity);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Stop debugging so that the run can continue on its own.
+    pub(super) async fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.finished = true;
+        let breakpoints = self.breakpoints.keys().copied().collect();
+        if let Err(err) = self.request(DebugAction::Finish(breakpoints)).await {
+            error!("Failed to stop debugging: {err}");
+        }
+    }
+}
+
+#[cfg(feature = "router")]
+pub(super) fn pause_message(
+    feedback: &TracedEvent,
+    breakpoints: &HashMap<Entity, String>,
+) -> Option<InteractionSessionMessage> {
+    let TracedEventKind::SessionEvent(event) = &feedback.event else {
+        return None;
+    };
+
+    match &event.change {
+        SessionChange::Paused(cause) => Some(InteractionSessionMessage::Paused {
+            breakpoint: match cause {
+                PauseCause::UserRequest => None,
+                PauseCause::Breakpoint(operation) => breakpoints.get(operation).cloned(),
+            },
+        }),
+        SessionChange::Unpaused => Some(InteractionSessionMessage::Unpaused),
+        _ => None,
+    }
+}
+
+/// Receiver for requests to change the debugging state of sessions.
+#[derive(bevy_ecs::prelude::Resource)]
+struct DebugRequestReceiver(tokio::sync::mpsc::Receiver<DebugRequest>);
+
+pub(super) fn setup(app: &mut bevy_app::SubApp) -> tokio::sync::mpsc::Sender<DebugRequest> {
+    let (debug_tx, debug_rx) = tokio::sync::mpsc::channel(10);
+    app.insert_resource(DebugRequestReceiver(debug_rx));
+    app.add_systems(bevy_app::Update, handle_debug_requests);
+    debug_tx
+}
+
+/// Applies requests from interaction sessions to the [`crossflow::Debug`] resource.
+fn handle_debug_requests(world: &mut World) {
+    world.resource_scope::<DebugRequestReceiver, _>(|world, mut receiver| {
+        while let Ok(request) = receiver.0.try_recv() {
+            let result = match request.action {
+                DebugAction::Command(command) => {
+                    apply_debug_command(world, request.session, request.workflow, command)
+                }
+                DebugAction::Finish(breakpoints) => {
+                    let mut debug = world.get_resource_or_init::<crossflow::Debug>();
+                    debug.stop_debugging_for(request.session);
+                    for breakpoint in &breakpoints {
+                        debug.breakpoints.remove(breakpoint);
+                    }
+                    Ok(breakpoints)
+                }
+            };
+
+            if let Some(reply) = request.reply {
+                let _ = reply.send(result);
+            }
+        }
+    });
+}
+
+pub(super) fn apply_debug_command(
+    world: &mut World,
+    session: Entity,
+    workflow: Entity,
+    command: InteractionDebugCommand,
+) -> DebugResult {
+    match command {
+        InteractionDebugCommand::SetBreakpoint { operation } => {
+            let operations = find_operations(world, workflow, &operation)?;
+            let mut debug = world.get_resource_or_init::<crossflow::Debug>();
+            debug.breakpoints.extend(operations.iter().copied());
+            debug.start_debugging_for(session, false);
+            Ok(operations)
+        }
+        InteractionDebugCommand::ClearBreakpoint { operation } => {
+            let operations = find_operations(world, workflow, &operation)?;
+            let mut debug = world.get_resource_or_init::<crossflow::Debug>();
+            for op in &operations {
+                debug.breakpoints.remove(op);
+            }
+            Ok(operations)
+        }
+        InteractionDebugCommand::Pause => {
+            world
+                .get_resource_or_init::<crossflow::Debug>()
+                .start_debugging_for(session, true);
+            Ok(Vec::new())
+        }
+        InteractionDebugCommand::Unpause => {
+            world
+                .get_resource_or_init::<crossflow::Debug>()
+                .unpause(session);
+            Ok(Vec::new())
+        }
+        InteractionDebugCommand::Step { operation } => {
+            let operation = match operation {
+                Some(operation) => find_operations(world, workflow, &operation)?
+                    .first()
+                    .copied(),
+                None => None,
+            };
+            DebugStep { session, operation }.apply(world);
+            Ok(operation.into_iter().collect())
+        }
+    }
+}
+
+/// Find the entities of a workflow's operations that have the given name.
+pub(super) fn find_operations(world: &mut World, workflow: Entity, name: &str) -> DebugResult {
+    let operations: Vec<Entity> = world
+        .workflow_operations(workflow)
+        .into_iter()
+        .filter(|(_, id)| id.to_string() == name)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    if operations.is_empty() {
+        return Err(format!("no operation named [{name}] in the workflow"));
+    }
+
+    Ok(operations)
+}
+
+#[cfg(feature = "router")]
+#[cfg(test)]
+mod tests {
+    use axum::extract::{State, ws};
+    use futures_util::SinkExt;
+
+    use super::*;
+    use crate::api::executor::{
+        InteractionSessionEnd, InteractionSessionMessage, InteractionStartRequest, PostRunRequest,
+        tests::{
+            WsTestFixture, new_add7_diagram, next_interaction_message, send_debug_command,
+            setup_ws_test,
+        },
+        ws_interaction,
+    };
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_ws_debug_breakpoint() {
+        let WsTestFixture {
+            executor_state,
+            cleanup_test,
+        } = setup_ws_test();
+
+        let request_body = InteractionStartRequest {
+            run: PostRunRequest {
+                diagram: new_add7_diagram(),
+                request: serde_json::Value::from(5),
+            },
+            breakpoints: vec!["add7".to_string()],
+            pause: false,
+        };
+
+        let (socket_write, mut test_rx) = futures_channel::mpsc::channel(1024);
+        let (mut test_tx, socket_read) = futures_channel::mpsc::channel(1024);
+
+        tokio::spawn(ws_interaction(
+            socket_write,
+            socket_read,
+            State(executor_state),
+        ));
+
+        test_tx
+            .send(Ok(ws::Message::Text(
+                serde_json::to_string(&request_body).unwrap().into(),
+            )))
+            .await
+            .unwrap();
+
+        let msg = next_interaction_message(&mut test_rx).await;
+        assert!(matches!(msg, InteractionSessionMessage::Started { .. }));
+
+        let msg = next_interaction_message(&mut test_rx).await;
+        assert!(
+            matches!(
+                &msg,
+                InteractionSessionMessage::Paused { breakpoint: Some(breakpoint) }
+                    if breakpoint == "add7"
+            ),
+            "expected to pause at add7",
+        );
+
+        // Unknown operations are reported without ending the session
+        send_debug_command(
+            &mut test_tx,
+            InteractionDebugCommand::SetBreakpoint {
+                operation: "does_not_exist".to_string(),
+            },
+        )
+        .await;
+        let msg = next_interaction_message(&mut test_rx).await;
+        assert!(matches!(msg, InteractionSessionMessage::DebugError { .. }));
+
+        // Step past the breakpoint, then let the rest of the run continue
+        send_debug_command(
+            &mut test_tx,
+            InteractionDebugCommand::Step {
+                operation: Some("add7".to_string()),
+            },
+        )
+        .await;
+        send_debug_command(&mut test_tx, InteractionDebugCommand::Unpause).await;
+
+        let mut unpaused = false;
+        let resp = loop {
+            match next_interaction_message(&mut test_rx).await {
+                InteractionSessionMessage::Unpaused => unpaused = true,
+                InteractionSessionMessage::Finish(end) => break end,
+                InteractionSessionMessage::Paused { breakpoint } => {
+                    panic!("unexpected pause {breakpoint:?}")
+                }
+                _ => {}
+            }
+        };
+        assert!(unpaused);
+        assert!(matches!(
+            resp,
+            InteractionSessionEnd::Ok(value) if value == 12
+        ));
+
+        cleanup_test();
+    }
+}
@@ -1,28 +1,37 @@
 use axum::{
     Json,
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
-    response::{self, Response},
+    response::{self, IntoResponse, Response},
 };
 #[cfg(feature = "router")]
-use axum::{Router, routing::post};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 #[cfg(feature = "router")]
 use axum::{
     extract::ws,
     routing::{self},
 };
-use bevy_ecs::{prelude::Entity, schedule::IntoScheduleConfigs};
+use bevy_ecs::{
+    prelude::{Entity, World},
+    schedule::IntoScheduleConfigs,
+};
 #[cfg(feature = "router")]
 use crossflow::TracedEventKind;
 use crossflow::{
-    Diagram, DiagramElementRegistry, DiagramError, DiagramErrorCode, DiagramOperation,
-    InferenceBoundaryConditions, MetadataAccess, Outcome, PortRef, RequestExt, TracedEvent, trace,
+    Diagram, DiagramElementRegistry, DiagramError, DiagramErrorCode, InferenceBoundaryConditions,
+    MetadataAccess, Outcome, PortRef, RequestExt, TraceRecord, TracedEvent, trace,
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "router")]
+use std::collections::HashMap;
 use std::{
     error::Error,
+    path::PathBuf,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 use tokio::sync::mpsc::error::TryRecvError;
 use tracing::error;
@@ -31,19 +40,58 @@ use tracing::warn;
 
 #[cfg(feature = "router")]
 use super::websocket::{WebsocketSinkExt, WebsocketStreamExt};
+use super::{
+    buffers::{self, BufferContents, BufferRequest, InteractionBufferCommand},
+    debug::{self, DebugRequest, DebugResult, InteractionDebugCommand, apply_debug_command},
+    runs::{self, ActiveRun, RunsRequest, await_outcome, finish_run, run_id},
+};
+#[cfg(feature = "router")]
+use super::{
+    buffers::{delete_buffer, get_buffer, get_buffers, post_buffer},
+    debug::{InteractionDebugger, pause_message},
+    runs::{delete_run, get_runs},
+};
 use crate::api::error_responses::WorkflowCancelledResponse;
+use crate::api::history::{RunHistory, RunHistoryError, RunRecord, RunResult, RunSummary};
 
 #[cfg(feature = "router")]
 type BroadcastRecvError = tokio::sync::broadcast::error::RecvError;
 
-type WorkflowResponseResult =
-    Result<(Outcome<serde_json::Value>, Entity), Box<dyn Error + Send + Sync>>;
+type WorkflowResponseResult = Result<StartedWorkflow, Box<dyn Error + Send + Sync>>;
 type WorkflowResponseSender = tokio::sync::oneshot::Sender<WorkflowResponseResult>;
 
+struct StartedWorkflow {
+    outcome: Outcome<serde_json::Value>,
+    /// The provider of the workflow service, which should be despawned once
+    /// the run is finished.
+    workflow: Entity,
+    /// The root session of the run.
+    #[cfg_attr(not(feature = "router"), allow(dead_code))]
+    session: Entity,
+    /// The outcome of each debug command that was requested in the [`Context`].
+    #[cfg_attr(not(feature = "router"), allow(dead_code))]
+    debug: Vec<(InteractionDebugCommand, DebugResult)>,
+    /// Triggered when the run is cancelled through [`delete_run`].
+    cancel: tokio::sync::oneshot::Receiver<()>,
+}
+
 type WorkflowFeedback = TracedEvent;
 
+/// Collects the trace of a run so it can be saved in the [`RunHistory`].
+#[derive(bevy_ecs::component::Component, Clone, Default)]
+struct TraceCapture(Arc<Mutex<Vec<TraceRecord>>>);
+
+/// What needs to be remembered while a run is in progress in order to add it
+/// to the [`RunHistory`] once it is finished.
+struct PendingRunRecord {
+    diagram: Diagram,
+    request: serde_json::Value,
+    started: SystemTime,
+    trace: TraceCapture,
+}
+
 #[derive(bevy_ecs::component::Component)]
-struct FeedbackSender(tokio::sync::broadcast::Sender<WorkflowFeedback>);
+pub(super) struct FeedbackSender(tokio::sync::broadcast::Sender<WorkflowFeedback>);
 
 pub struct Context {
     diagram: Diagram,
@@ -51,6 +99,9 @@ pub struct Context {
     registry: Arc<Mutex<DiagramElementRegistry>>,
     response_tx: WorkflowResponseSender,
     feedback_tx: Option<FeedbackSender>,
+    /// Debug commands to apply before the run has a chance to make progress.
+    debug: Vec<InteractionDebugCommand>,
+    trace: Option<TraceCapture>,
 }
 
 #[derive(Clone)]
@@ -58,9 +109,50 @@ pub struct ExecutorState {
     pub registry: Arc<Mutex<DiagramElementRegistry>>,
     pub send_chan: tokio::sync::mpsc::Sender<Context>,
     pub despawn_chan: tokio::sync::mpsc::Sender<Entity>,
+    pub debug_chan: tokio::sync::mpsc::Sender<DebugRequest>,
+    pub buffer_chan: tokio::sync::mpsc::Sender<BufferRequest>,
+    pub runs_chan: tokio::sync::mpsc::Sender<RunsRequest>,
+    pub history: Option<RunHistory>,
     pub response_timeout: Duration,
 }
 
+impl ExecutorState {
+    fn pending_run_record(
+        &self,
+        diagram: &Diagram,
+        request: &serde_json::Value,
+    ) -> Option<PendingRunRecord> {
+        self.history.as_ref()?;
+        Some(PendingRunRecord {
+            diagram: diagram.clone(),
+            request: request.clone(),
+            started: SystemTime::now(),
+            trace: TraceCapture::default(),
+        })
+    }
+
+    /// Save a finished run in the history, if the history is enabled.
+    async fn record_run(
+        &self,
+        pending: Option<PendingRunRecord>,
+        result: RunResult,
+    ) -> Option<RunRecord> {
+        let (pending, history) = (pending?, self.history.as_ref()?);
+        let trace = std::mem::take(&mut *pending.trace.0.lock().unwrap());
+        let record = RunRecord::new(
+            pending.diagram,
+            pending.request,
+            result,
+            pending.started,
+            trace,
+        );
+        if let Err(err) = history.save(&record).await {
+            error!("failed to save run [{}] to the history: {err}", record.id);
+        }
+        Some(record)
+    }
+}
+
 #[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 #[cfg_attr(test, derive(serde::Serialize))]
 #[derive(Deserialize)]
@@ -167,6 +259,53 @@ impl CompatibilityResult {
     }
 }
 
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationRequest {
+    pub diagram: Diagram,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResponse {
+    /// Every error that was found in the diagram. This is empty if the diagram
+    /// is valid.
+    pub errors: Vec<ValidationError>,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+    /// The port where the error was found, if it is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<PortRef>,
+    pub reason: String,
+    /// True if the message type of a port could not be inferred.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub type_inference: bool,
+}
+
+impl From<DiagramError> for ValidationError {
+    fn from(error: DiagramError) -> Self {
+        let type_inference = matches!(
+            &error.code,
+            DiagramErrorCode::CannotInferType(_) | DiagramErrorCode::MessageTypeInferenceFailure(_)
+        );
+        let port = match &error.code {
+            DiagramErrorCode::CannotInferType(port) => Some(port.clone()),
+            _ => error.context.port().cloned(),
+        };
+        Self {
+            port,
+            reason: error.to_string(),
+            type_inference,
+        }
+    }
+}
+
 #[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -180,11 +319,48 @@ fn is_false(value: &bool) -> bool {
     !*value
 }
 
-/// Sends a request to the executor system and wait for the response.
-pub async fn post_run(
-    state: State<ExecutorState>,
-    Json(body): Json<PostRunRequest>,
-) -> response::Result<Json<serde_json::Value>> {
+/// Why a run did not produce a response.
+enum RunFailure {
+    /// The diagram could not be turned into a workflow.
+    Rejected(String),
+    Cancelled(crossflow::Cancellation),
+    Internal,
+}
+
+impl IntoResponse for RunFailure {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Rejected(err) => (StatusCode::UNPROCESSABLE_ENTITY, err).into_response(),
+            Self::Cancelled(err) => WorkflowCancelledResponse(&err).into_response(),
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+/// The header of a [`post_run`] response that contains the id of the run.
+pub const RUN_ID_HEADER: &str = "x-crossflow-run";
+
+struct FinishedRun {
+    /// The root session of the run, if it was able to start.
+    run: Option<Entity>,
+    result: Result<serde_json::Value, RunFailure>,
+    /// Only available when the history is enabled.
+    record: Option<RunRecord>,
+}
+
+impl FinishedRun {
+    fn internal_failure() -> Self {
+        Self {
+            run: None,
+            result: Err(RunFailure::Internal),
+            record: None,
+        }
+    }
+}
+
+/// Run a diagram to completion, adding it to the history if that is enabled.
+async fn run_diagram(state: &ExecutorState, body: PostRunRequest) -> FinishedRun {
+    let pending = state.pending_run_record(&body.diagram, &body.request);
     let (response_tx, response_rx) = tokio::sync::oneshot::channel();
     if let Err(err) = state
         .send_chan
@@ -194,40 +370,118 @@ pub async fn post_run(
             request: body.request,
             response_tx,
             feedback_tx: None,
+            debug: Vec::new(),
+            trace: pending.as_ref().map(|pending| pending.trace.clone()),
         })
         .await
     {
         error!("{}", err);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        return FinishedRun::internal_failure();
     }
 
     let workflow_response = match response_rx.await {
         Ok(response) => response,
         Err(err) => {
             error!("{}", err);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+            return FinishedRun::internal_failure();
         }
     };
 
-    let response = (match workflow_response {
-        Ok((outcome, workflow)) => {
-            let result = outcome.await;
+    let mut run = None;
+    let (result, recorded) = match workflow_response {
+        Ok(StartedWorkflow {
+            outcome,
+            workflow,
+            session,
+            cancel,
+            ..
+        }) => {
+            run = Some(session);
+            let result = await_outcome(outcome, cancel).await;
+            finish_run(state, session).await;
             if let Err(err) = state.despawn_chan.send(workflow).await {
                 error!("Failed to request workflow despawn: {err}");
             }
 
             match result {
-                Ok(response) => Ok(response),
-                Err(err) => Err(WorkflowCancelledResponse(&err).into()),
+                Ok(response) => (Ok(response.clone()), RunResult::Ok(response)),
+                Err(err) => {
+                    let recorded = RunResult::Err(format!("workflow cancelled: {}", err.cause));
+                    (Err(RunFailure::Cancelled(err)), recorded)
+                }
             }
         }
-        Err(err) => Err(Response::builder()
-            .status(StatusCode::UNPROCESSABLE_ENTITY)
-            .body(err.to_string())
-            .map_or(StatusCode::INTERNAL_SERVER_ERROR.into(), |resp| resp.into())),
-    } as response::Result<serde_json::Value>)?;
+        Err(err) => (
+            Err(RunFailure::Rejected(err.to_string())),
+            RunResult::Err(err.to_string()),
+        ),
+    };
 
-    Ok(Json(response))
+    let record = state.record_run(pending, recorded).await;
+    FinishedRun {
+        run,
+        result,
+        record,
+    }
+}
+
+/// Sends a request to the executor system and wait for the response.
+///
+/// The id of the run is given in the [`RUN_ID_HEADER`] of the response.
+pub async fn post_run(state: State<ExecutorState>, Json(body): Json<PostRunRequest>) -> Response {
+    let FinishedRun { run, result, .. } = run_diagram(&state, body).await;
+    let mut response = match result {
+        Ok(value) => Json(value).into_response(),
+        Err(err) => err.into_response(),
+    };
+
+    if let Some(run) = run
+        && let Ok(value) = run_id(run).parse()
+    {
+        response.headers_mut().insert(RUN_ID_HEADER, value);
+    }
+    response
+}
+
+/// List the runs in the history, newest first.
+pub async fn get_history(
+    state: State<ExecutorState>,
+) -> Result<Json<Vec<RunSummary>>, RunHistoryError> {
+    let history = state.history.as_ref().ok_or(RunHistoryError::Disabled)?;
+    Ok(Json(history.list().await?))
+}
+
+/// Get everything that was saved about one run.
+pub async fn get_history_run(
+    state: State<ExecutorState>,
+    Path(id): Path<String>,
+) -> Result<Json<RunRecord>, RunHistoryError> {
+    let history = state.history.as_ref().ok_or(RunHistoryError::Disabled)?;
+    Ok(Json(history.load(&id).await?))
+}
+
+/// Run the diagram of a past run again with the same request. The response is
+/// the record of the new run.
+pub async fn post_history_rerun(
+    state: State<ExecutorState>,
+    Path(id): Path<String>,
+) -> response::Result<Json<RunRecord>> {
+    let history = state.history.as_ref().ok_or(RunHistoryError::Disabled)?;
+    let previous = history.load(&id).await?;
+    let FinishedRun { result, record, .. } = run_diagram(
+        &state,
+        PostRunRequest {
+            diagram: previous.diagram,
+            request: previous.request,
+        },
+    )
+    .await;
+
+    match (record, result) {
+        (Some(record), _) => Ok(Json(record)),
+        (None, Err(err)) => Err(err.into()),
+        (None, Ok(_)) => Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
 }
 
 pub async fn post_compatibility(
@@ -248,12 +502,32 @@ pub async fn post_compatibility(
     Ok(Json(CompatibilityResponse { results }))
 }
 
+pub async fn post_validate(
+    state: State<ExecutorState>,
+    Json(body): Json<ValidationRequest>,
+) -> response::Result<Json<ValidationResponse>> {
+    let registry = state.registry.lock().map_err(|err| {
+        error!("failed to lock registry for validation: {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(validate_diagram(&registry, &body.diagram)))
+}
+
+fn validate_diagram(registry: &DiagramElementRegistry, diagram: &Diagram) -> ValidationResponse {
+    let errors = diagram
+        .validate_json(registry)
+        .into_iter()
+        .map(ValidationError::from)
+        .collect();
+    ValidationResponse { errors }
+}
+
 fn check_compatibility_candidate(
     registry: &DiagramElementRegistry,
     candidate: CompatibilityCandidate,
 ) -> CompatibilityResult {
-    let stream_names = root_stream_names(&candidate.diagram);
-    let boundary = match InferenceBoundaryConditions::json_messages(registry, stream_names) {
+    let boundary = match InferenceBoundaryConditions::json_diagram(registry, &candidate.diagram) {
         Ok(boundary) => boundary,
         Err(err) => {
             return CompatibilityResult::terminal(
@@ -400,7 +674,7 @@ fn can_connect_message_types(
     registry: &DiagramElementRegistry,
     source_type: usize,
     target_type: usize,
-) -> Result<Option<String>, DiagramErrorCode> {
+) -> Result<Option<String>, Box<DiagramErrorCode>> {
     if source_type == target_type {
         return Ok(Some("Message types match exactly".to_string()));
     }
@@ -454,17 +728,6 @@ fn can_connect_message_types(
     Ok(None)
 }
 
-fn root_stream_names(diagram: &Diagram) -> Vec<String> {
-    diagram
-        .ops
-        .values()
-        .filter_map(|op| match op.as_ref() {
-            DiagramOperation::StreamOut(stream_out) => Some(stream_out.name.to_string()),
-            _ => None,
-        })
-        .collect()
-}
-
 #[cfg(test)]
 mod compatibility_tests {
     use super::*;
@@ -900,34 +1163,95 @@ pub enum InteractionSessionFeedback {
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum InteractionSessionMessage {
+    /// The run has started. The id can be used with the executor's `/runs`
+    /// endpoints.
+    Started {
+        run: String,
+    },
     Feedback(InteractionSessionFeedback),
+    /// The session has paused. If it was paused by a breakpoint, this will
+    /// contain the name of the operation that the breakpoint was set on.
+    Paused {
+        breakpoint: Option<String>,
+    },
+    Unpaused,
+    /// An [`InteractionCommand`] could not be applied.
+    DebugError {
+        message: String,
+    },
+    /// Reply to an [`InteractionBufferCommand`].
+    Buffers {
+        buffers: Vec<BufferContents>,
+    },
     Finish(InteractionSessionEnd),
 }
 
+/// The first message that the client sends to start an interaction session.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractionStartRequest {
+    #[serde(flatten)]
+    pub run: PostRunRequest,
+    /// Names of operations to set breakpoints on before the run begins.
+    #[serde(default)]
+    pub breakpoints: Vec<String>,
+    /// Pause the run before any operation receives a message.
+    #[serde(default)]
+    pub pause: bool,
+}
+
+/// Any message that the client can send after an interaction session has
+/// started.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum InteractionCommand {
+    Debug(InteractionDebugCommand),
+    Buffer(InteractionBufferCommand),
+}
+
 /// Start an interaction session.
 #[cfg(feature = "router")]
-async fn ws_interaction<W, R, Text>(mut write: W, mut read: R, state: State<ExecutorState>)
-where
+pub(super) async fn ws_interaction<W, R, Text>(
+    mut write: W,
+    mut read: R,
+    state: State<ExecutorState>,
+) where
     W: WebsocketSinkExt<InteractionSessionMessage>,
-    R: WebsocketStreamExt<PostRunRequest, Text>,
+    R: WebsocketStreamExt<InteractionStartRequest, Text>,
     Text: std::ops::Deref<Target = str>,
 {
-    let req: PostRunRequest = if let Some(req) = read.next_json().await {
+    let req: InteractionStartRequest = if let Some(req) = read.next_json().await {
         req
     } else {
         return;
     };
 
+    let mut debug: Vec<_> = req
+        .breakpoints
+        .into_iter()
+        .map(|operation| InteractionDebugCommand::SetBreakpoint { operation })
+        .collect();
+    if req.pause {
+        debug.push(InteractionDebugCommand::Pause);
+    }
+
+    let pending = state.pending_run_record(&req.run.diagram, &req.run.request);
     let (response_tx, response_rx) = tokio::sync::oneshot::channel();
     let (feedback_tx, mut feedback_rx) = tokio::sync::broadcast::channel(10);
     if let Err(err) = state
         .send_chan
         .send(Context {
             registry: state.registry.clone(),
-            diagram: req.diagram,
-            request: req.request,
+            diagram: req.run.diagram,
+            request: req.run.request,
             response_tx,
             feedback_tx: Some(FeedbackSender(feedback_tx)),
+            debug,
+            trace: pending.as_ref().map(|pending| pending.trace.clone()),
         })
         .await
     {
@@ -940,89 +1264,152 @@ where
         return;
     }
 
-    let response = async {
-        let response_result = response_rx.await;
+    let started = match response_rx.await {
+        Ok(Ok(started)) => started,
+        Ok(Err(err)) => {
+            state
+                .record_run(pending, RunResult::Err(err.to_string()))
+                .await;
+            write
+                .send_json(&InteractionSessionMessage::Finish(
+                    InteractionSessionEnd::Err(err.to_string()),
+                ))
+                .await;
+            return;
+        }
+        Err(err) => {
+            error!("{}", err);
+            write
+                .send_json(&InteractionSessionMessage::Finish(
+                    InteractionSessionEnd::err_from_status_code(StatusCode::INTERNAL_SERVER_ERROR),
+                ))
+                .await;
+            return;
+        }
+    };
 
-        let workflow_response = match response_result {
-            Ok(response) => response,
-            Err(err) => {
-                error!("{}", err);
-                return InteractionSessionEnd::err_from_status_code(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                );
-            }
-        };
+    let StartedWorkflow {
+        outcome,
+        workflow,
+        session,
+        debug,
+        cancel,
+    } = started;
+
+    write
+        .send_json(&InteractionSessionMessage::Started {
+            run: run_id(session),
+        })
+        .await;
+
+    let mut debugger = InteractionDebugger {
+        state: state.0.clone(),
+        session,
+        workflow,
+        breakpoints: HashMap::new(),
+        finished: false,
+    };
+    for (command, result) in debug {
+        debugger.record(command, result, &mut write).await;
+    }
 
-        match workflow_response {
-            Ok((outcome, workflow)) => {
-                let result = outcome.await;
-                if let Err(err) = state.despawn_chan.send(workflow).await {
-                    error!("Failed to request workflow despawn: {err}");
-                }
+    let response = async {
+        let result = await_outcome(outcome, cancel).await;
+        finish_run(&state, session).await;
+        if let Err(err) = state.despawn_chan.send(workflow).await {
+            error!("Failed to request workflow despawn: {err}");
+        }
 
-                // Brief yield so already-queued feedback reaches the socket
-                // before the finish message; drain_interaction_feedback handles
-                // the rest.
-                tokio::time::sleep(Duration::from_millis(100)).await;
+        // Brief yield so already-queued feedback reaches the socket
+        // before the finish message; drain_interaction_feedback handles
+        // the rest.
+        tokio::time::sleep(Duration::from_millis(100)).await;
 
-                match result {
-                    Ok(result) => InteractionSessionEnd::Ok(result),
-                    Err(err) => InteractionSessionEnd::Err(err.to_string()),
-                }
-            }
+        let recorded = match &result {
+            Ok(result) => RunResult::Ok(result.clone()),
+            Err(err) => RunResult::Err(format!("workflow cancelled: {}", err.cause)),
+        };
+        state.record_run(pending, recorded).await;
+
+        match result {
+            Ok(result) => InteractionSessionEnd::Ok(result),
             Err(err) => InteractionSessionEnd::Err(err.to_string()),
         }
     };
     tokio::pin!(response);
 
     let mut feedback_open = true;
+    let mut read_open = true;
     loop {
-        if feedback_open {
-            tokio::select! {
-                feedback = feedback_rx.recv() => {
-                    match feedback {
-                        Ok(feedback) => {
-                            send_interaction_feedback(&mut write, &feedback).await;
+        tokio::select! {
+            feedback = feedback_rx.recv(), if feedback_open => {
+                match feedback {
+                    Ok(feedback) => {
+                        send_interaction_feedback(&mut write, &feedback, &debugger.breakpoints)
+                            .await;
+                    }
+                    Err(e) => match e {
+                        BroadcastRecvError::Closed => {
+                            feedback_open = false;
                         }
-                        Err(e) => match e {
-                            BroadcastRecvError::Closed => {
-                                feedback_open = false;
-                            }
-                            BroadcastRecvError::Lagged(_) => {
-                                warn!("{}", e);
-                                feedback_open = false;
-                            }
-                        },
+                        BroadcastRecvError::Lagged(_) => {
+                            warn!("{}", e);
+                            feedback_open = false;
+                        }
+                    },
+                }
+            }
+            text = read.next_text(), if read_open => {
+                let Some(text) = text else {
+                    // The client can no longer send debug commands, so make
+                    // sure the run does not stay paused forever.
+                    read_open = false;
+                    debugger.finish().await;
+                    continue;
+                };
+
+                match serde_json::from_str::<InteractionCommand>(&text) {
+                    Ok(command) => debugger.apply(command, &mut write).await,
+                    Err(err) => {
+                        write
+                            .send_json(&InteractionSessionMessage::DebugError {
+                                message: err.to_string(),
+                            })
+                            .await;
                     }
                 }
-                result = &mut response => {
-                    drain_interaction_feedback(&mut write, &mut feedback_rx).await;
-                    write
-                        .send_json(&InteractionSessionMessage::Finish(result))
-                        .await;
-                    break;
+            }
+            result = &mut response => {
+                if feedback_open {
+                    drain_interaction_feedback(
+                        &mut write,
+                        &mut feedback_rx,
+                        &debugger.breakpoints,
+                    )
+                    .await;
                 }
+                write
+                    .send_json(&InteractionSessionMessage::Finish(result))
+                    .await;
+                break;
             }
-        } else {
-            let result = response.await;
-            write
-                .send_json(&InteractionSessionMessage::Finish(result))
-                .await;
-            break;
         }
     }
+
+    debugger.finish().await;
 }
 
 #[cfg(feature = "router")]
 async fn drain_interaction_feedback<W>(
     write: &mut W,
     feedback_rx: &mut tokio::sync::broadcast::Receiver<WorkflowFeedback>,
+    breakpoints: &HashMap<Entity, String>,
 ) where
     W: WebsocketSinkExt<InteractionSessionMessage>,
 {
     loop {
         match feedback_rx.try_recv() {
-            Ok(feedback) => send_interaction_feedback(write, &feedback).await,
+            Ok(feedback) => send_interaction_feedback(write, &feedback, breakpoints).await,
             Err(tokio::sync::broadcast::error::TryRecvError::Empty) => break,
             Err(tokio::sync::broadcast::error::TryRecvError::Closed) => break,
             Err(tokio::sync::broadcast::error::TryRecvError::Lagged(skipped)) => {
@@ -1033,10 +1420,17 @@ async fn drain_interaction_feedback<W>(
 }
 
 #[cfg(feature = "router")]
-async fn send_interaction_feedback<W>(write: &mut W, feedback: &TracedEvent)
-where
+async fn send_interaction_feedback<W>(
+    write: &mut W,
+    feedback: &TracedEvent,
+    breakpoints: &HashMap<Entity, String>,
+) where
     W: WebsocketSinkExt<InteractionSessionMessage>,
 {
+    if let Some(msg) = pause_message(feedback, breakpoints) {
+        write.send_json(&msg).await;
+    }
+
     for op_id in operation_finished_ids(feedback) {
         write
             .send_json(&InteractionSessionMessage::Feedback(
@@ -1108,7 +1502,7 @@ fn execute_requests(
     match rx.try_recv() {
         Ok(ctx) => {
             let registry = &*ctx.registry.lock().unwrap();
-            let maybe_outcome = match ctx.diagram.spawn_io_workflow(&mut cmds, registry) {
+            match ctx.diagram.spawn_io_workflow(&mut cmds, registry) {
                 Ok(workflow) => {
                     let series = cmds.request(ctx.request, workflow);
                     let session = series.session_id();
@@ -1116,13 +1510,50 @@ fn execute_requests(
                     if let Some(feedback_tx) = ctx.feedback_tx {
                         cmds.entity(session).insert(feedback_tx);
                     }
-                    Ok((outcome, workflow.provider()))
+                    if let Some(trace) = ctx.trace {
+                        cmds.entity(session).insert(trace);
+                    }
+                    let (cancel_tx, cancel) = tokio::sync::oneshot::channel();
+                    cmds.entity(session).insert(ActiveRun {
+                        workflow: workflow.provider(),
+                        started: SystemTime::now(),
+                        cancel: Some(cancel_tx),
+                    });
+
+                    // The debug commands are queued after the workflow is
+                    // built but before the request gets a chance to run, so
+                    // breakpoints cannot be missed.
+                    let workflow = workflow.provider();
+                    let response_tx = ctx.response_tx;
+                    let debug = ctx.debug;
+                    cmds.queue(move |world: &mut World| {
+                        let debug = debug
+                            .into_iter()
+                            .map(|command| {
+                                let result =
+                                    apply_debug_command(world, session, workflow, command.clone());
+                                (command, result)
+                            })
+                            .collect();
+
+                        // assuming that workflows are automatically cancelled when the promise is dropped.
+                        let started = StartedWorkflow {
+                            outcome,
+                            workflow,
+                            session,
+                            debug,
+                            cancel,
+                        };
+                        if response_tx.send(Ok(started)).is_err() {
+                            error!("failed to send response")
+                        }
+                    });
+                }
+                Err(err) => {
+                    if ctx.response_tx.send(Err(err.into())).is_err() {
+                        error!("failed to send response")
+                    }
                 }
-                Err(err) => Err(err.into()),
-            };
-            // assuming that workflows are automatically cancelled when the promise is dropped.
-            if let Err(_) = ctx.response_tx.send(maybe_outcome) {
-                error!("failed to send response")
             }
         }
         Err(err) => match err {
@@ -1146,6 +1577,23 @@ fn interaction_feedback(
     }
 }
 
+fn capture_trace(
+    trigger: bevy_ecs::prelude::Trigger<trace::TracedEvent>,
+    capture_query: bevy_ecs::system::Query<(Entity, &TraceCapture)>,
+    run_id: bevy_ecs::system::Res<trace::TraceRunId>,
+) {
+    let ev = trigger.event();
+    for (session, capture) in &capture_query {
+        if ev.event.is_for_session(session) {
+            capture
+                .0
+                .lock()
+                .unwrap()
+                .push(TraceRecord::new(ev, *run_id));
+        }
+    }
+}
+
 fn despawn_workflows(
     mut receiver: bevy_ecs::system::ResMut<WorkflowDespawnReceiver>,
     mut commands: bevy_ecs::system::Commands,
@@ -1162,12 +1610,16 @@ fn despawn_workflows(
 #[non_exhaustive]
 pub struct ExecutorOptions {
     pub response_timeout: Duration,
+    /// Save every run as a [`RunRecord`] in this directory. Runs are not kept
+    /// when this is [`None`].
+    pub history_dir: Option<PathBuf>,
 }
 
 impl Default for ExecutorOptions {
     fn default() -> Self {
         Self {
             response_timeout: Duration::from_secs(15),
+            history_dir: None,
         }
     }
 }
@@ -1185,12 +1637,17 @@ pub fn setup_bevy_app(
     app.insert_resource(WorkflowDespawnReceiver(despawn_rx));
     app.add_systems(bevy_app::Update, execute_requests);
     app.world_mut().add_observer(interaction_feedback);
+    app.world_mut().add_observer(capture_trace);
     app.add_systems(bevy_app::Update, despawn_workflows);
 
     ExecutorState {
         registry: Arc::new(Mutex::new(registry)),
         send_chan: request_tx,
         despawn_chan: despawn_tx,
+        debug_chan: debug::setup(app),
+        buffer_chan: buffers::setup(app),
+        runs_chan: runs::setup(app),
+        history: options.history_dir.clone().map(RunHistory::new),
         response_timeout: options.response_timeout,
     }
 }
@@ -1241,7 +1698,18 @@ pub(super) fn new_router(
 
     let router = Router::new()
         .route("/run", post(post_run))
-        .route("/compatibility", post(post_compatibility));
+        .route("/compatibility", post(post_compatibility))
+        .route("/validate", post(post_validate))
+        .route("/history", get(get_history))
+        .route("/history/{id}", get(get_history_run))
+        .route("/history/{id}/rerun", post(post_history_rerun))
+        .route("/runs", get(get_runs))
+        .route("/runs/{run}", routing::delete(delete_run))
+        .route("/runs/{run}/buffers", get(get_buffers))
+        .route(
+            "/runs/{run}/buffers/{operation}",
+            get(get_buffer).post(post_buffer).delete(delete_buffer),
+        );
 
     let router = router.route(
         "/interaction",
@@ -1257,13 +1725,12 @@ pub(super) fn new_router(
         ),
     );
 
-    let router = router.with_state(executor_state);
-    router
+    router.with_state(executor_state)
 }
 
 #[cfg(feature = "router")]
 #[cfg(test)]
-mod tests {
+pub(super) mod tests {
     use axum::extract::ws;
     use axum::{
         body,
@@ -1286,6 +1753,10 @@ mod tests {
     }
 
     async fn setup_test() -> TestFixture<impl FnOnce()> {
+        setup_test_with_options(ExecutorOptions::default()).await
+    }
+
+    async fn setup_test_with_options(options: ExecutorOptions) -> TestFixture<impl FnOnce()> {
         let mut registry = DiagramElementRegistry::new();
         registry.register_node_builder(NodeBuilderOptions::new("add7"), |builder, _config: ()| {
             builder.create_map_block(|req: i32| req + 7)
@@ -1302,13 +1773,13 @@ mod tests {
             app.add_systems(
                 bevy_app::Update,
                 move |mut app_exit: bevy_ecs::event::EventWriter<bevy_app::AppExit>| {
-                    if let Ok(_) = recv_stop.try_recv() {
+                    if recv_stop.try_recv().is_ok() {
                         app_exit.write_default();
                     }
                 },
             );
 
-            let router = new_router(&mut app, registry, ExecutorOptions::default());
+            let router = new_router(&mut app, registry, options);
             let _ = router_sender.send(router);
 
             app.run();
@@ -1325,7 +1796,7 @@ mod tests {
         }
     }
 
-    fn new_add7_diagram() -> Diagram {
+    pub(crate) fn new_add7_diagram() -> Diagram {
         Diagram::from_json(json!({
             "version": "0.1.0",
             "start": "add7",
@@ -1373,6 +1844,7 @@ mod tests {
                 .unwrap(),
             mime::APPLICATION_JSON
         );
+        assert!(response.headers().contains_key(RUN_ID_HEADER));
         let resp_bytes = body::to_bytes(response.into_body(), 1024 * 1024)
             .await
             .unwrap();
@@ -1383,6 +1855,112 @@ mod tests {
         cleanup_test();
     }
 
+    async fn send_json_request<T: serde::de::DeserializeOwned>(
+        router: &Router,
+        request: Request<axum::body::Body>,
+    ) -> (StatusCode, Option<T>) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let resp_bytes = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&resp_bytes).ok())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_run_history() {
+        let history_dir = tempfile::TempDir::new().unwrap();
+        let TestFixture {
+            router,
+            cleanup_test,
+        } = setup_test_with_options(ExecutorOptions {
+            history_dir: Some(history_dir.path().to_owned()),
+            ..Default::default()
+        })
+        .await;
+
+        let request_body = PostRunRequest {
+            diagram: new_add7_diagram(),
+            request: serde_json::Value::from(5),
+        };
+        let (status, resp) = send_json_request::<i32>(
+            &router,
+            Request::post("/run")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.to_string())
+                .body(serde_json::to_string(&request_body).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(resp, Some(12));
+
+        // Failed runs are kept too
+        let request_body = PostRunRequest {
+            diagram: new_add7_diagram(),
+            request: serde_json::Value::from("not a number"),
+        };
+        let (status, _) = send_json_request::<serde_json::Value>(
+            &router,
+            Request::post("/run")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.to_string())
+                .body(serde_json::to_string(&request_body).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, summaries) = send_json_request::<Vec<RunSummary>>(
+            &router,
+            Request::get("/history").body(Default::default()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let summaries = summaries.unwrap();
+        assert_eq!(summaries.len(), 2);
+        // Newest first
+        assert!(!summaries[0].ok);
+        assert!(summaries[1].ok);
+        assert_eq!(summaries[0].diagram_hash, summaries[1].diagram_hash);
+
+        let id = &summaries[1].id;
+        let (status, record) = send_json_request::<RunRecord>(
+            &router,
+            Request::get(format!("/history/{id}"))
+                .body(Default::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let record = record.unwrap();
+        assert_eq!(record.request, 5);
+        assert!(matches!(record.result, RunResult::Ok(value) if value == 12));
+
+        let (status, rerun) = send_json_request::<RunRecord>(
+            &router,
+            Request::post(format!("/history/{id}/rerun"))
+                .body(Default::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let rerun = rerun.unwrap();
+        assert_ne!(&rerun.id, id);
+        assert_eq!(rerun.diagram_hash, record.diagram_hash);
+        assert!(matches!(rerun.result, RunResult::Ok(value) if value == 12));
+
+        let (status, _) = send_json_request::<serde_json::Value>(
+            &router,
+            Request::get("/history/../secret")
+                .body(Default::default())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        cleanup_test();
+    }
+
     #[tokio::test]
     #[test_log::test]
     async fn test_post_compatibility() {
@@ -1480,12 +2058,116 @@ mod tests {
         cleanup_test();
     }
 
-    struct WsTestFixture<CleanupFn> {
-        executor_state: ExecutorState,
-        cleanup_test: CleanupFn,
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_post_validate() {
+        let TestFixture {
+            router,
+            cleanup_test,
+        } = setup_test().await;
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "add7",
+            "ops": {
+                "add7": {
+                    "type": "node",
+                    "builder": "add7",
+                    "next": "missing",
+                },
+                "missing": {
+                    "type": "node",
+                    "builder": "missing_builder",
+                    "next": { "builtin": "terminate" },
+                },
+                "unfinished": {
+                    "type": "buffer",
+                },
+            },
+        }))
+        .unwrap();
+
+        let validate = async |diagram: Diagram| -> ValidationResponse {
+            let response = router
+                .clone()
+                .oneshot(
+                    Request::post("/validate")
+                        .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.to_string())
+                        .body(serde_json::to_string(&ValidationRequest { diagram }).unwrap())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let resp_bytes = body::to_bytes(response.into_body(), 1024 * 1024)
+                .await
+                .unwrap();
+            serde_json::from_slice(&resp_bytes).unwrap()
+        };
+
+        let resp = validate(diagram).await;
+        let missing_port: PortRef = (&NextOperation::Name("missing".into())).into();
+        let unfinished_port: PortRef = (&NextOperation::Name("unfinished".into())).into();
+        assert!(
+            resp.errors
+                .iter()
+                .any(|err| err.port.as_ref() == Some(&missing_port) && !err.type_inference),
+            "{:?}",
+            resp.errors,
+        );
+        assert!(
+            resp.errors
+                .iter()
+                .any(|err| err.port.as_ref() == Some(&unfinished_port) && err.type_inference),
+            "{:?}",
+            resp.errors,
+        );
+
+        let resp = validate(new_add7_diagram()).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+
+        // Message types can be inferred for this diagram, but the node builders
+        // reject their configs, so it can only be caught by building it.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "first",
+            "ops": {
+                "first": {
+                    "type": "node",
+                    "builder": "add7",
+                    "config": "unexpected",
+                    "next": "second",
+                },
+                "second": {
+                    "type": "node",
+                    "builder": "add7",
+                    "config": 10,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+        let resp = validate(diagram).await;
+        for name in ["first", "second"] {
+            let port: PortRef = (&NextOperation::Name(name.into())).into();
+            assert!(
+                resp.errors
+                    .iter()
+                    .any(|err| err.port.as_ref() == Some(&port) && !err.type_inference),
+                "{:?}",
+                resp.errors,
+            );
+        }
+
+        cleanup_test();
+    }
+
+    pub(crate) struct WsTestFixture<CleanupFn> {
+        pub(crate) executor_state: ExecutorState,
+        pub(crate) cleanup_test: CleanupFn,
     }
 
-    fn setup_ws_test() -> WsTestFixture<impl FnOnce()> {
+    pub(crate) fn setup_ws_test() -> WsTestFixture<impl FnOnce()> {
         let (send_stop, mut recv_stop) = tokio::sync::oneshot::channel::<()>();
         let (state_sender, state_receiver) = std::sync::mpsc::channel();
 
@@ -1495,7 +2177,7 @@ mod tests {
             app.add_systems(
                 bevy_app::Update,
                 move |mut app_exit: bevy_ecs::event::EventWriter<bevy_app::AppExit>| {
-                    if let Ok(_) = recv_stop.try_recv() {
+                    if recv_stop.try_recv().is_ok() {
                         app_exit.write_default();
                     }
                 },
@@ -1541,9 +2223,13 @@ mod tests {
         let mut diagram = new_add7_diagram();
         diagram.default_trace = crossflow::TraceToggle::On;
 
-        let request_body = PostRunRequest {
-            diagram,
-            request: serde_json::Value::from(5),
+        let request_body = InteractionStartRequest {
+            run: PostRunRequest {
+                diagram,
+                request: serde_json::Value::from(5),
+            },
+            breakpoints: Vec::new(),
+            pause: false,
         };
 
         // Need to use "futures" channels rather than "tokio" channels as they implement `Sink` and
@@ -1564,6 +2250,14 @@ mod tests {
             .await
             .unwrap();
 
+        let msg = test_rx.next().await.unwrap();
+        let started_msg: InteractionSessionMessage =
+            serde_json::from_slice(msg.into_text().unwrap().as_bytes()).unwrap();
+        assert!(matches!(
+            started_msg,
+            InteractionSessionMessage::Started { .. }
+        ));
+
         // There should be 4 feedback messages: add7 starts, add7 finishes,
         // terminate starts, and terminate finishes.
         for _ in 0..4 {
@@ -1597,4 +2291,27 @@ mod tests {
 
         cleanup_test();
     }
+
+    pub(crate) async fn next_interaction_message(
+        rx: &mut futures_channel::mpsc::Receiver<ws::Message>,
+    ) -> InteractionSessionMessage {
+        use futures_util::StreamExt;
+
+        let msg = tokio::time::timeout(Duration::from_secs(5), rx.next())
+            .await
+            .expect("timed out waiting for an interaction message")
+            .unwrap();
+        serde_json::from_slice(msg.into_text().unwrap().as_bytes()).unwrap()
+    }
+
+    pub(crate) async fn send_debug_command(
+        tx: &mut futures_channel::mpsc::Sender<Result<ws::Message, axum::Error>>,
+        command: InteractionDebugCommand,
+    ) {
+        tx.send(Ok(ws::Message::Text(
+            serde_json::to_string(&command).unwrap().into(),
+        )))
+        .await
+        .unwrap();
+    }
 }
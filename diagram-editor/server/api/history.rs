@@ -0,0 +1,300 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use crossflow::{Diagram, TraceRecord};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Everything that is kept about one run of a diagram.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub id: String,
+    /// See [`diagram_hash`].
+    pub diagram_hash: String,
+    pub diagram: Diagram,
+    pub request: serde_json::Value,
+    pub result: RunResult,
+    /// Wall clock time when the run was requested, as milliseconds since the
+    /// UNIX epoch.
+    pub started_at_ms: u64,
+    pub duration_ms: u64,
+    /// Trace events of the run. This is only filled in for diagrams that have
+    /// tracing turned on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<TraceRecord>,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunResult {
+    Ok(serde_json::Value),
+    /// The diagram could not be built or the run was cancelled.
+    Err(String),
+}
+
+/// A short description of a [`RunRecord`], used when listing the history.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub id: String,
+    pub diagram_hash: String,
+    pub started_at_ms: u64,
+    pub duration_ms: u64,
+    pub ok: bool,
+}
+
+impl From<&RunRecord> for RunSummary {
+    fn from(record: &RunRecord) -> Self {
+        Self {
+            id: record.id.clone(),
+            diagram_hash: record.diagram_hash.clone(),
+            started_at_ms: record.started_at_ms,
+            duration_ms: record.duration_ms,
+            ok: matches!(record.result, RunResult::Ok(_)),
+        }
+    }
+}
+
+impl RunRecord {
+    /// Create a record for a run that was requested at `started`.
+    pub fn new(
+        diagram: Diagram,
+        request: serde_json::Value,
+        result: RunResult,
+        started: SystemTime,
+        trace: Vec<TraceRecord>,
+    ) -> Self {
+        let started_at_ms = started
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let duration_ms = started.elapsed().unwrap_or(Duration::ZERO).as_millis() as u64;
+
+        Self {
+            id: new_run_record_id(started_at_ms),
+            diagram_hash: diagram_hash(&diagram),
+            diagram,
+            request,
+            result,
+            started_at_ms,
+            duration_ms,
+            trace,
+        }
+    }
+}
+
+/// A stable fingerprint of a diagram, so runs of the same diagram can be
+/// grouped together. This is the 64-bit FNV-1a hash of the serialized diagram,
+/// written in hex. Object keys are sorted before hashing because the operations
+/// of a diagram are kept in a map that has no fixed order.
+pub fn diagram_hash(diagram: &Diagram) -> String {
+    let mut canonical = serde_json::to_value(diagram).unwrap_or_default();
+    canonical.sort_all_objects();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in serde_json::to_vec(&canonical).unwrap_or_default() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Ids sort in the order that the runs were started.
+fn new_run_record_id(started_at_ms: u64) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{started_at_ms:013}-{count:06}")
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RunHistoryError {
+    #[error("run history is not enabled on this server")]
+    Disabled,
+    #[error("no run with id [{0}] in the history")]
+    NotFound(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl IntoResponse for RunHistoryError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Disabled | Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Io(_) | Self::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Stores [`RunRecord`]s as JSON files in a directory, one file per run. The
+/// [`RunSummary`] of each run is kept in a small file next to the record so
+/// that listing the history does not need to read every trace.
+#[derive(Debug, Clone)]
+pub struct RunHistory {
+    dir: PathBuf,
+}
+
+impl RunHistory {
+    /// Keep the history in `dir`. The directory will be created when the
+    /// first run is saved.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub async fn save(&self, record: &RunRecord) -> Result<(), RunHistoryError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let data = serde_json::to_vec_pretty(record)?;
+        tokio::fs::write(self.path_for(&record.id), data).await?;
+        // The summary is written last so that a listed run can always be loaded.
+        let summary = serde_json::to_vec(&RunSummary::from(record))?;
+        tokio::fs::write(self.summary_path_for(&record.id), summary).await?;
+        Ok(())
+    }
+
+    pub async fn load(&self, id: &str) -> Result<RunRecord, RunHistoryError> {
+        // Ids only contain digits and dashes, so this also rejects anything
+        // that could escape the history directory.
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            return Err(RunHistoryError::NotFound(id.to_owned()));
+        }
+
+        let data = match tokio::fs::read(self.path_for(id)).await {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(RunHistoryError::NotFound(id.to_owned()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// List every run in the history, newest first. Summaries that cannot be
+    /// read are skipped.
+    pub async fn list(&self) -> Result<Vec<RunSummary>, RunHistoryError> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut summaries = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if !path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(SUMMARY_SUFFIX))
+            {
+                continue;
+            }
+
+            let Ok(data) = tokio::fs::read(&path).await else {
+                continue;
+            };
+            if let Ok(summary) = serde_json::from_slice::<RunSummary>(&data) {
+                summaries.push(summary);
+            }
+        }
+
+        summaries.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(summaries)
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    fn summary_path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}{SUMMARY_SUFFIX}"))
+    }
+}
+
+const SUMMARY_SUFFIX: &str = ".summary.json";
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn diagram_json() -> serde_json::Value {
+        json!({
+            "version": "0.1.0",
+            "start": "fork",
+            "ops": {
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["add", "mul", "sub", "div"],
+                },
+                "add": { "type": "node", "builder": "add", "next": "join" },
+                "mul": { "type": "node", "builder": "mul", "next": "join" },
+                "sub": { "type": "node", "builder": "sub", "next": "join" },
+                "div": { "type": "node", "builder": "div", "next": "join" },
+                "join": { "type": "node", "builder": "join", "next": { "builtin": "terminate" } },
+            },
+        })
+    }
+
+    #[test]
+    fn test_diagram_hash_is_stable() {
+        // Each deserialized diagram keeps its operations in a map with its own
+        // random ordering, so this would fail if the hash depended on it.
+        let hashes: Vec<_> = (0..8)
+            .map(|_| diagram_hash(&Diagram::from_json(diagram_json()).unwrap()))
+            .collect();
+        assert!(hashes.iter().all(|hash| *hash == hashes[0]));
+
+        let mut changed = diagram_json();
+        changed["ops"]["add"]["builder"] = json!("sub");
+        assert_ne!(
+            diagram_hash(&Diagram::from_json(changed).unwrap()),
+            hashes[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_reads_summaries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = RunHistory::new(dir.path());
+        let diagram = Diagram::from_json(diagram_json()).unwrap();
+
+        let first = RunRecord::new(
+            diagram.clone(),
+            json!(1),
+            RunResult::Ok(json!(2)),
+            SystemTime::now(),
+            Vec::new(),
+        );
+        let second = RunRecord::new(
+            diagram,
+            json!(1),
+            RunResult::Err("cancelled".to_owned()),
+            SystemTime::now(),
+            Vec::new(),
+        );
+        history.save(&first).await.unwrap();
+        history.save(&second).await.unwrap();
+
+        // Listing should not need to read the full records.
+        std::fs::write(history.path_for(&first.id), "not a record").unwrap();
+
+        let summaries = history.list().await.unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].id, second.id);
+        assert!(!summaries[0].ok);
+        assert_eq!(summaries[1].id, first.id);
+        assert!(summaries[1].ok);
+        assert_eq!(summaries[1].diagram_hash, first.diagram_hash);
+    }
+}
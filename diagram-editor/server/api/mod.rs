@@ -1,7 +1,12 @@
+pub mod buffers;
+pub mod debug;
 mod error_responses;
 pub mod executor;
+pub mod history;
+pub mod runs;
 #[cfg(feature = "router")]
 mod websocket;
+pub mod workspace;
 
 #[cfg(feature = "router")]
 use axum::{Router, routing::get};
@@ -19,6 +24,7 @@ use mime_guess::mime;
 #[non_exhaustive]
 pub struct ApiOptions {
     pub executor: executor::ExecutorOptions,
+    pub workspace: workspace::WorkspaceOptions,
 }
 
 #[derive(Clone)]
@@ -66,10 +72,13 @@ pub fn api_router(
     options: ApiOptions,
 ) -> Router {
     let registry_resp = RegistryResponse::new(&registry).expect("failed to serialize registry");
-    Router::new().route("/registry", get(registry_resp)).nest(
-        "/executor",
-        executor::new_router(app, registry, options.executor),
-    )
+    Router::new()
+        .route("/registry", get(registry_resp))
+        .nest(
+            "/executor",
+            executor::new_router(app, registry, options.executor),
+        )
+        .nest("/workspace", workspace::new_router(options.workspace))
 }
 
 #[cfg(feature = "router")]
@@ -0,0 +1,309 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response,
+};
+use bevy_ecs::prelude::{Entity, Has, World};
+use crossflow::Outcome;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use tracing::error;
+
+use super::executor::{ExecutorState, FeedbackSender};
+
+/// The error given to [`Outcome::cancel`] when a run is cancelled through
+/// [`delete_run`].
+#[derive(thiserror::Error, Debug)]
+#[error("the run was cancelled through the executor API")]
+struct RunCancelledByUser;
+
+/// Wait for the outcome of a run, cancelling it if `cancel` gets triggered
+/// first.
+pub(super) async fn await_outcome(
+    mut outcome: Outcome<serde_json::Value>,
+    cancel: tokio::sync::oneshot::Receiver<()>,
+) -> Result<serde_json::Value, crossflow::Cancellation> {
+    tokio::select! {
+        result = &mut outcome => return result,
+        Ok(()) = cancel => outcome.cancel(RunCancelledByUser),
+    }
+    outcome.await
+}
+
+/// Attached to the root session of each run while it is active.
+#[derive(bevy_ecs::component::Component)]
+pub(super) struct ActiveRun {
+    /// The provider of the workflow that the run is using.
+    pub(super) workflow: Entity,
+    pub(super) started: SystemTime,
+    /// Taken when a cancellation is requested.
+    pub(super) cancel: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+/// Runs are identified in the API by their root session. The id is a string
+/// because the bits of an [`Entity`] do not fit in a JSON number without losing
+/// precision.
+pub(super) fn run_id(session: Entity) -> String {
+    session.to_bits().to_string()
+}
+
+pub(super) fn parse_run_id(id: &str) -> Option<Entity> {
+    id.parse()
+        .ok()
+        .and_then(|bits| Entity::try_from_bits(bits).ok())
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStatus {
+    Running,
+    /// The run is paused by the debugger.
+    Paused,
+    /// A cancellation was requested but the run has not stopped yet.
+    Cancelling,
+}
+
+/// Describes a run that has not finished yet.
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunInfo {
+    pub run: String,
+    pub status: RunStatus,
+    /// Wall clock time when the run was started, as milliseconds since the
+    /// UNIX epoch.
+    pub started_at_ms: u64,
+    /// Whether the run was started by an interaction session.
+    pub interactive: bool,
+}
+
+/// A request to list or cancel active runs.
+pub enum RunsRequest {
+    List {
+        reply: tokio::sync::oneshot::Sender<Vec<RunInfo>>,
+    },
+    Cancel {
+        run: Entity,
+        /// Replies with false if the run is not active.
+        reply: tokio::sync::oneshot::Sender<bool>,
+    },
+    /// The run has finished, so stop listing it. Replies once the run can no
+    /// longer be found.
+    Finish {
+        run: Entity,
+        reply: tokio::sync::oneshot::Sender<()>,
+    },
+}
+
+/// List the runs that have not finished yet, oldest first.
+pub async fn get_runs(state: State<ExecutorState>) -> response::Result<Json<Vec<RunInfo>>> {
+    let (reply, reply_rx) = tokio::sync::oneshot::channel();
+    if let Err(err) = state.runs_chan.send(RunsRequest::List { reply }).await {
+        error!("{err}");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+    }
+
+    let runs = reply_rx.await.map_err(|err| {
+        error!("{err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(runs))
+}
+
+/// Cancel an active run. The run will end with a
+/// [`CancellationCause::User`](crossflow::CancellationCause::User).
+pub async fn delete_run(state: State<ExecutorState>, Path(run): Path<String>) -> StatusCode {
+    let Some(run) = parse_run_id(&run) else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    let (reply, reply_rx) = tokio::sync::oneshot::channel();
+    if let Err(err) = state
+        .runs_chan
+        .send(RunsRequest::Cancel { run, reply })
+        .await
+    {
+        error!("{err}");
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    match reply_rx.await {
+        Ok(true) => StatusCode::ACCEPTED,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(err) => {
+            error!("{err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Stop tracking a run once its outcome has been received. Requests that come
+/// in after this returns will not find the run.
+pub(super) async fn finish_run(state: &ExecutorState, run: Entity) {
+    let (reply, reply_rx) = tokio::sync::oneshot::channel();
+    if let Err(err) = state
+        .runs_chan
+        .send(RunsRequest::Finish { run, reply })
+        .await
+    {
+        error!("Failed to finish run: {err}");
+        return;
+    }
+
+    if let Err(err) = reply_rx.await {
+        error!("Failed to finish run: {err}");
+    }
+}
+
+/// Receiver for requests to list or cancel active runs.
+#[derive(bevy_ecs::prelude::Resource)]
+struct RunsRequestReceiver(tokio::sync::mpsc::Receiver<RunsRequest>);
+
+pub(super) fn setup(app: &mut bevy_app::SubApp) -> tokio::sync::mpsc::Sender<RunsRequest> {
+    let (runs_tx, runs_rx) = tokio::sync::mpsc::channel(10);
+    app.insert_resource(RunsRequestReceiver(runs_rx));
+    app.add_systems(bevy_app::Update, handle_runs_requests);
+    runs_tx
+}
+
+fn handle_runs_requests(world: &mut World) {
+    world.resource_scope::<RunsRequestReceiver, _>(|world, mut receiver| {
+        while let Ok(request) = receiver.0.try_recv() {
+            match request {
+                RunsRequest::List { reply } => {
+                    let _ = reply.send(list_active_runs(world));
+                }
+                RunsRequest::Cancel { run, reply } => {
+                    let cancelled = world
+                        .get_mut::<ActiveRun>(run)
+                        .and_then(|mut active| active.cancel.take())
+                        .is_some_and(|cancel| cancel.send(()).is_ok());
+                    let _ = reply.send(cancelled);
+                }
+                RunsRequest::Finish { run, reply } => {
+                    if let Ok(mut session) = world.get_entity_mut(run) {
+                        session.remove::<ActiveRun>();
+                    }
+                    let _ = reply.send(());
+                }
+            }
+        }
+    });
+}
+
+fn list_active_runs(world: &mut World) -> Vec<RunInfo> {
+    let mut query = world.query::<(Entity, &ActiveRun, Has<FeedbackSender>)>();
+    let debug = world.get_resource::<crossflow::Debug>();
+    let mut runs: Vec<_> = query
+        .iter(world)
+        .map(|(session, active, interactive)| {
+            let status = if active.cancel.is_none() {
+                RunStatus::Cancelling
+            } else if debug.is_some_and(|debug| debug.is_paused(session, world)) {
+                RunStatus::Paused
+            } else {
+                RunStatus::Running
+            };
+
+            RunInfo {
+                run: run_id(session),
+                status,
+                started_at_ms: active
+                    .started
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+                interactive,
+            }
+        })
+        .collect();
+
+    runs.sort_by_key(|run| run.started_at_ms);
+    runs
+}
+
+#[cfg(feature = "router")]
+#[cfg(test)]
+mod tests {
+    use axum::extract::ws;
+    use futures_util::SinkExt;
+
+    use super::*;
+    use crate::api::executor::{
+        InteractionSessionEnd, InteractionSessionMessage, InteractionStartRequest, PostRunRequest,
+        tests::{WsTestFixture, new_add7_diagram, next_interaction_message, setup_ws_test},
+        ws_interaction,
+    };
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_list_and_cancel_runs() {
+        let WsTestFixture {
+            executor_state,
+            cleanup_test,
+        } = setup_ws_test();
+
+        let request_body = InteractionStartRequest {
+            run: PostRunRequest {
+                diagram: new_add7_diagram(),
+                request: serde_json::Value::from(5),
+            },
+            breakpoints: Vec::new(),
+            pause: true,
+        };
+
+        let (socket_write, mut test_rx) = futures_channel::mpsc::channel(1024);
+        let (mut test_tx, socket_read) = futures_channel::mpsc::channel(1024);
+
+        tokio::spawn(ws_interaction(
+            socket_write,
+            socket_read,
+            State(executor_state.clone()),
+        ));
+
+        test_tx
+            .send(Ok(ws::Message::Text(
+                serde_json::to_string(&request_body).unwrap().into(),
+            )))
+            .await
+            .unwrap();
+
+        let run = match next_interaction_message(&mut test_rx).await {
+            InteractionSessionMessage::Started { run } => run,
+            _ => panic!("expected the run to start"),
+        };
+        let msg = next_interaction_message(&mut test_rx).await;
+        assert!(matches!(msg, InteractionSessionMessage::Paused { .. }));
+
+        let Json(runs) = get_runs(State(executor_state.clone())).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run, run);
+        assert_eq!(runs[0].status, RunStatus::Paused);
+        assert!(runs[0].interactive);
+
+        let status = delete_run(State(executor_state.clone()), Path(run.clone())).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let resp = loop {
+            if let InteractionSessionMessage::Finish(end) =
+                next_interaction_message(&mut test_rx).await
+            {
+                break end;
+            }
+        };
+        assert!(matches!(
+            resp,
+            InteractionSessionEnd::Err(err) if err.contains("cancellation triggered by user")
+        ));
+
+        let Json(runs) = get_runs(State(executor_state.clone())).await.unwrap();
+        assert!(runs.is_empty());
+
+        let status = delete_run(State(executor_state), Path(run)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        cleanup_test();
+    }
+}
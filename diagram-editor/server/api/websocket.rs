@@ -64,7 +64,7 @@ where
     T: Serialize,
 {
     async fn send_json(&mut self, value: &T) -> Option<()> {
-        let json_str = match serde_json::to_string(value).into() {
+        let json_str = match serde_json::to_string(value) {
             Ok(json_str) => json_str,
             Err(err) => {
                 debug!("{}", err);
@@ -0,0 +1,555 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+#[cfg(feature = "router")]
+use axum::{Router, routing::get};
+use crossflow::{Diagram, DiagramErrorCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path as FsPath, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+use tokio::sync::Mutex;
+
+/// Previous versions of each diagram are kept in this subdirectory of the
+/// workspace.
+const REVISIONS_DIR: &str = ".revisions";
+
+#[non_exhaustive]
+pub struct WorkspaceOptions {
+    /// Diagrams are stored in this directory. The workspace API is disabled
+    /// when this is [`None`].
+    pub dir: Option<PathBuf>,
+    /// How many revisions of each diagram to keep, including the current one.
+    pub max_revisions: usize,
+}
+
+impl Default for WorkspaceOptions {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_revisions: 50,
+        }
+    }
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramFileInfo {
+    pub name: String,
+    /// The revision of the current version of the diagram.
+    pub revision: u32,
+    pub saved_at_ms: u64,
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramRevision {
+    pub revision: u32,
+    pub saved_at_ms: u64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WorkspaceError {
+    #[error("the diagram workspace is not enabled on this server")]
+    Disabled,
+    #[error(
+        "[{0}] is not a valid diagram name, only letters, digits, '-', '_' and '.' are allowed"
+    )]
+    InvalidName(String),
+    #[error("no diagram named [{0}] in the workspace")]
+    NotFound(String),
+    #[error("diagram [{0}] has no revision {1}")]
+    RevisionNotFound(String, u32),
+    #[error("the diagram is not valid: {0}")]
+    Invalid(Box<DiagramErrorCode>),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<DiagramErrorCode> for WorkspaceError {
+    fn from(err: DiagramErrorCode) -> Self {
+        Self::Invalid(Box::new(err))
+    }
+}
+
+impl IntoResponse for WorkspaceError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Disabled | Self::NotFound(_) | Self::RevisionNotFound(..) => {
+                StatusCode::NOT_FOUND
+            }
+            Self::InvalidName(_) => StatusCode::BAD_REQUEST,
+            Self::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Io(_) | Self::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Reads and writes [`Diagram`] files in a directory. Every save is kept as a
+/// numbered revision so earlier versions of a diagram can be recovered.
+///
+/// The current version of a diagram named `name` is stored in `name.json`, and
+/// its revisions are stored in `.revisions/name/<revision>.json`.
+#[derive(Debug)]
+pub struct Workspace {
+    dir: PathBuf,
+    max_revisions: usize,
+    /// Saves are serialized so that two clients saving the same diagram cannot
+    /// end up with the same revision number.
+    save_lock: Mutex<()>,
+}
+
+impl Workspace {
+    pub fn new(dir: impl Into<PathBuf>, max_revisions: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            max_revisions: max_revisions.max(1),
+            save_lock: Mutex::new(()),
+        }
+    }
+
+    /// Validate a diagram and save it as the newest revision.
+    pub async fn save(&self, name: &str, diagram: &Diagram) -> Result<u32, WorkspaceError> {
+        validate_diagram_name(name)?;
+        diagram.validate_operation_names()?;
+        diagram.validate_template_usage()?;
+
+        let mut data = serde_json::to_vec_pretty(diagram)?;
+        data.push(b'\n');
+
+        let _guard = self.save_lock.lock().await;
+        let revisions_dir = self.revisions_dir(name);
+        tokio::fs::create_dir_all(&revisions_dir).await?;
+        let revision = self
+            .revision_numbers(name)
+            .await?
+            .last()
+            .map_or(1, |last| last + 1);
+
+        tokio::fs::write(revision_path(&revisions_dir, revision), &data).await?;
+        tokio::fs::write(self.diagram_path(name), &data).await?;
+        self.prune_revisions(name).await?;
+        Ok(revision)
+    }
+
+    pub async fn load(&self, name: &str) -> Result<Diagram, WorkspaceError> {
+        validate_diagram_name(name)?;
+        read_diagram(&self.diagram_path(name))
+            .await?
+            .ok_or_else(|| WorkspaceError::NotFound(name.to_owned()))
+    }
+
+    pub async fn load_revision(
+        &self,
+        name: &str,
+        revision: u32,
+    ) -> Result<Diagram, WorkspaceError> {
+        validate_diagram_name(name)?;
+        read_diagram(&revision_path(&self.revisions_dir(name), revision))
+            .await?
+            .ok_or_else(|| WorkspaceError::RevisionNotFound(name.to_owned(), revision))
+    }
+
+    /// Remove the current version of a diagram. Its revisions are kept, so
+    /// saving a diagram with the same name later continues the numbering.
+    pub async fn remove(&self, name: &str) -> Result<(), WorkspaceError> {
+        validate_diagram_name(name)?;
+        match tokio::fs::remove_file(self.diagram_path(name)).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Err(WorkspaceError::NotFound(name.to_owned()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// List the diagrams in the workspace, sorted by name.
+    pub async fn list(&self) -> Result<Vec<DiagramFileInfo>, WorkspaceError> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut diagrams = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            if validate_diagram_name(name).is_err() {
+                continue;
+            }
+
+            let revision = self
+                .revision_numbers(name)
+                .await?
+                .last()
+                .copied()
+                .unwrap_or(0);
+            diagrams.push(DiagramFileInfo {
+                name: name.to_owned(),
+                revision,
+                saved_at_ms: modified_at_ms(&entry.metadata().await?),
+            });
+        }
+
+        diagrams.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(diagrams)
+    }
+
+    /// List the revisions of a diagram, oldest first.
+    pub async fn revisions(&self, name: &str) -> Result<Vec<DiagramRevision>, WorkspaceError> {
+        validate_diagram_name(name)?;
+        let revisions_dir = self.revisions_dir(name);
+        let mut revisions = Vec::new();
+        for revision in self.revision_numbers(name).await? {
+            let metadata = tokio::fs::metadata(revision_path(&revisions_dir, revision)).await?;
+            revisions.push(DiagramRevision {
+                revision,
+                saved_at_ms: modified_at_ms(&metadata),
+            });
+        }
+
+        if revisions.is_empty() && !tokio::fs::try_exists(self.diagram_path(name)).await? {
+            return Err(WorkspaceError::NotFound(name.to_owned()));
+        }
+
+        Ok(revisions)
+    }
+
+    /// Sorted revision numbers that exist for a diagram.
+    async fn revision_numbers(&self, name: &str) -> Result<Vec<u32>, WorkspaceError> {
+        let mut entries = match tokio::fs::read_dir(self.revisions_dir(name)).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut revisions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(revision) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|revision| revision.parse().ok())
+            {
+                revisions.push(revision);
+            }
+        }
+
+        revisions.sort();
+        Ok(revisions)
+    }
+
+    async fn prune_revisions(&self, name: &str) -> Result<(), WorkspaceError> {
+        let revisions = self.revision_numbers(name).await?;
+        let excess = revisions.len().saturating_sub(self.max_revisions);
+        let revisions_dir = self.revisions_dir(name);
+        for revision in &revisions[..excess] {
+            tokio::fs::remove_file(revision_path(&revisions_dir, *revision)).await?;
+        }
+        Ok(())
+    }
+
+    fn diagram_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.json"))
+    }
+
+    fn revisions_dir(&self, name: &str) -> PathBuf {
+        self.dir.join(REVISIONS_DIR).join(name)
+    }
+}
+
+/// Names become file names, so they are restricted to characters that are
+/// safe on every platform and cannot refer to other directories.
+fn validate_diagram_name(name: &str) -> Result<(), WorkspaceError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(WorkspaceError::InvalidName(name.to_owned()))
+    }
+}
+
+fn revision_path(revisions_dir: &FsPath, revision: u32) -> PathBuf {
+    revisions_dir.join(format!("{revision:06}.json"))
+}
+
+async fn read_diagram(path: &FsPath) -> Result<Option<Diagram>, WorkspaceError> {
+    match tokio::fs::read(path).await {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn modified_at_ms(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiagramResponse {
+    pub name: String,
+    pub revision: u32,
+}
+
+#[derive(Clone)]
+pub struct WorkspaceState {
+    pub workspace: Option<Arc<Workspace>>,
+}
+
+impl WorkspaceState {
+    fn workspace(&self) -> Result<&Workspace, WorkspaceError> {
+        self.workspace.as_deref().ok_or(WorkspaceError::Disabled)
+    }
+}
+
+pub async fn get_diagrams(
+    state: State<WorkspaceState>,
+) -> Result<Json<Vec<DiagramFileInfo>>, WorkspaceError> {
+    Ok(Json(state.workspace()?.list().await?))
+}
+
+pub async fn get_diagram(
+    state: State<WorkspaceState>,
+    Path(name): Path<String>,
+) -> Result<Json<Diagram>, WorkspaceError> {
+    Ok(Json(state.workspace()?.load(&name).await?))
+}
+
+/// Save a diagram as its newest revision. Diagrams with invalid operation
+/// names or template usage are rejected.
+pub async fn put_diagram(
+    state: State<WorkspaceState>,
+    Path(name): Path<String>,
+    Json(diagram): Json<Diagram>,
+) -> Result<Json<SaveDiagramResponse>, WorkspaceError> {
+    let revision = state.workspace()?.save(&name, &diagram).await?;
+    Ok(Json(SaveDiagramResponse { name, revision }))
+}
+
+pub async fn delete_diagram(
+    state: State<WorkspaceState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, WorkspaceError> {
+    state.workspace()?.remove(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_diagram_revisions(
+    state: State<WorkspaceState>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<DiagramRevision>>, WorkspaceError> {
+    Ok(Json(state.workspace()?.revisions(&name).await?))
+}
+
+pub async fn get_diagram_revision(
+    state: State<WorkspaceState>,
+    Path((name, revision)): Path<(String, u32)>,
+) -> Result<Json<Diagram>, WorkspaceError> {
+    Ok(Json(
+        state.workspace()?.load_revision(&name, revision).await?,
+    ))
+}
+
+#[cfg(feature = "router")]
+pub(super) fn new_router(options: WorkspaceOptions) -> Router {
+    let state = WorkspaceState {
+        workspace: options
+            .dir
+            .map(|dir| Arc::new(Workspace::new(dir, options.max_revisions))),
+    };
+
+    Router::new()
+        .route("/diagrams", get(get_diagrams))
+        .route(
+            "/diagrams/{name}",
+            get(get_diagram).put(put_diagram).delete(delete_diagram),
+        )
+        .route("/diagrams/{name}/revisions", get(get_diagram_revisions))
+        .route(
+            "/diagrams/{name}/revisions/{revision}",
+            get(get_diagram_revision),
+        )
+        .with_state(state)
+}
+
+#[cfg(feature = "router")]
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{self, Body},
+        http::{Request, header},
+    };
+    use mime_guess::mime;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        router: &Router,
+        request: Request<Body>,
+    ) -> (StatusCode, Option<T>) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = body::to_bytes(response.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).ok())
+    }
+
+    fn put(path: &str, diagram: &serde_json::Value) -> Request<Body> {
+        Request::put(path)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.to_string())
+            .body(diagram.to_string().into())
+            .unwrap()
+    }
+
+    fn get(path: &str) -> Request<Body> {
+        Request::get(path).body(Body::empty()).unwrap()
+    }
+
+    fn diagram_with_start(start: &str) -> serde_json::Value {
+        json!({
+            "version": "0.1.0",
+            "start": start,
+            "ops": {
+                start: {
+                    "type": "node",
+                    "builder": "add7",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_diagrams() {
+        let dir = TempDir::new().unwrap();
+        let router = new_router(WorkspaceOptions {
+            dir: Some(dir.path().to_owned()),
+            max_revisions: 2,
+        });
+
+        let (status, saved) = send::<SaveDiagramResponse>(
+            &router,
+            put("/diagrams/example", &diagram_with_start("first")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(saved.unwrap().revision, 1);
+
+        for (start, revision) in [("second", 2), ("third", 3)] {
+            let (_, saved) = send::<SaveDiagramResponse>(
+                &router,
+                put("/diagrams/example", &diagram_with_start(start)),
+            )
+            .await;
+            assert_eq!(saved.unwrap().revision, revision);
+        }
+
+        let (status, diagram) = send::<Diagram>(&router, get("/diagrams/example")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(diagram.unwrap().start.to_string(), "third");
+
+        let (_, diagrams) = send::<Vec<DiagramFileInfo>>(&router, get("/diagrams")).await;
+        let diagrams = diagrams.unwrap();
+        assert_eq!(diagrams.len(), 1);
+        assert_eq!(diagrams[0].name, "example");
+        assert_eq!(diagrams[0].revision, 3);
+
+        // Only the newest revisions are kept
+        let (_, revisions) =
+            send::<Vec<DiagramRevision>>(&router, get("/diagrams/example/revisions")).await;
+        let revisions: Vec<_> = revisions.unwrap().iter().map(|r| r.revision).collect();
+        assert_eq!(revisions, [2, 3]);
+
+        let (status, diagram) =
+            send::<Diagram>(&router, get("/diagrams/example/revisions/2")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(diagram.unwrap().start.to_string(), "second");
+
+        let (status, _) =
+            send::<serde_json::Value>(&router, get("/diagrams/example/revisions/1")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send::<serde_json::Value>(
+            &router,
+            Request::delete("/diagrams/example")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send::<serde_json::Value>(&router, get("/diagrams/example")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_diagrams() {
+        let dir = TempDir::new().unwrap();
+        let router = new_router(WorkspaceOptions {
+            dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        });
+
+        // "builtin" is a reserved operation name
+        let mut diagram = diagram_with_start("first");
+        diagram["ops"]["builtin"] = diagram["ops"]["first"].clone();
+        let (status, _) =
+            send::<serde_json::Value>(&router, put("/diagrams/example", &diagram)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let workspace = Workspace::new(dir.path(), 1);
+        let err = workspace
+            .save("example", &serde_json::from_value(diagram).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WorkspaceError::Invalid(_)));
+
+        let (status, _) = send::<serde_json::Value>(
+            &router,
+            put("/diagrams/.hidden", &diagram_with_start("first")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, diagrams) = send::<Vec<DiagramFileInfo>>(&router, get("/diagrams")).await;
+        assert!(diagrams.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_disabled() {
+        let router = new_router(WorkspaceOptions::default());
+        let (status, _) = send::<serde_json::Value>(&router, get("/diagrams")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -20,9 +20,10 @@ use bevy_app::App;
 use clap::Parser;
 use crossflow::{
     CrossflowExecutorApp, Diagram, DiagramError, Outcome, RequestExt, RunCommandsOnWorldExt,
+    bevy_time::TimePlugin,
 };
-use std::thread;
 use std::{fs::File, str::FromStr};
+use std::{path::PathBuf, thread};
 
 pub use crossflow::DiagramElementRegistry;
 pub use std::error::Error;
@@ -64,6 +65,19 @@ pub struct RunArgs {
 pub struct ServeArgs {
     #[arg(short, long, default_value_t = 3000)]
     port: u16,
+
+    #[arg(long, help = "directory to save the history of runs in")]
+    history_dir: Option<PathBuf>,
+
+    #[arg(long, help = "directory to save and load diagrams in")]
+    workspace_dir: Option<PathBuf>,
+}
+
+/// Delay and timeout operations need a clock, so make sure the app has one.
+fn add_time_plugin(app: &mut App) {
+    if !app.is_plugin_added::<TimePlugin>() {
+        app.add_plugins(TimePlugin);
+    }
 }
 
 pub fn headless(
@@ -72,6 +86,7 @@ pub fn headless(
 ) -> Result<(), Box<dyn Error>> {
     let BasicExecutorSetup { mut app, registry } = setup();
     app.add_plugins(CrossflowExecutorApp::default());
+    add_time_plugin(&mut app);
     let file = File::open(args.diagram).unwrap();
     let diagram = Diagram::from_reader(file)?;
 
@@ -106,7 +121,11 @@ pub async fn serve(
         // because App does not implement Send.
         let BasicExecutorSetup { mut app, registry } = setup();
         app.add_plugins(CrossflowExecutorApp::default());
-        let router = new_router(&mut app, registry, ServerOptions::default());
+        add_time_plugin(&mut app);
+        let mut options = ServerOptions::default();
+        options.api.executor.history_dir = args.history_dir;
+        options.api.workspace.dir = args.workspace_dir;
+        let router = new_router(&mut app, registry, options);
         let _ = router_sender.send(router);
         app.run()
     });
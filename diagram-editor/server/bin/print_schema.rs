@@ -1,8 +1,13 @@
 use crossflow_diagram_editor::api::{
     RegistryResponse,
+    buffers::{BufferContents, PushBufferRequest},
     executor::{
-        CompatibilityRequest, CompatibilityResponse, InteractionSessionMessage, PostRunRequest,
+        CompatibilityRequest, CompatibilityResponse, InteractionCommand, InteractionSessionMessage,
+        InteractionStartRequest, PostRunRequest, ValidationRequest, ValidationResponse,
     },
+    history::{RunRecord, RunSummary},
+    runs::RunInfo,
+    workspace::{DiagramFileInfo, DiagramRevision, SaveDiagramResponse},
 };
 use indexmap::IndexMap;
 use schemars::SchemaGenerator;
@@ -12,8 +17,20 @@ fn main() {
     schema_generator.subschema_for::<PostRunRequest>();
     schema_generator.subschema_for::<CompatibilityRequest>();
     schema_generator.subschema_for::<CompatibilityResponse>();
+    schema_generator.subschema_for::<ValidationRequest>();
+    schema_generator.subschema_for::<ValidationResponse>();
     schema_generator.subschema_for::<RegistryResponse>();
     schema_generator.subschema_for::<InteractionSessionMessage>();
+    schema_generator.subschema_for::<InteractionStartRequest>();
+    schema_generator.subschema_for::<InteractionCommand>();
+    schema_generator.subschema_for::<BufferContents>();
+    schema_generator.subschema_for::<PushBufferRequest>();
+    schema_generator.subschema_for::<RunInfo>();
+    schema_generator.subschema_for::<RunSummary>();
+    schema_generator.subschema_for::<RunRecord>();
+    schema_generator.subschema_for::<DiagramFileInfo>();
+    schema_generator.subschema_for::<DiagramRevision>();
+    schema_generator.subschema_for::<SaveDiagramResponse>();
 
     // using `IndexMap` to preserve ordering
     let schema: IndexMap<&'static str, serde_json::Value> = IndexMap::from_iter([
@@ -25,7 +25,7 @@ use std::{
 };
 
 use bevy_ecs::{
-    prelude::{Commands, Entity, EntityRef, World},
+    prelude::{Commands, Component, Entity, EntityRef, World},
     system::SystemState,
 };
 
@@ -42,10 +42,10 @@ use crate::{
     BufferKeyTag, BufferLocation, BufferManager, BufferMap, BufferMapLayout, BufferMapLayoutHints,
     BufferStorage, BufferView, BufferWorldAccess, Bufferable, Buffering, Builder, CloneFromBuffer,
     DrainBuffer, FetchBehavior, Gate, GateState, IdentifierRef, IncompatibleLayout,
-    InspectBufferSessions, Joined, Joining, ManageBufferSessions, MessageTypeHint,
+    InspectBufferSessions, Joined, Joining, ManageBufferSessions, ManageSession, MessageTypeHint,
     MessageTypeHintEvaluation, NotifyAwaitingBuffer, NotifyBufferUpdate, OperationError,
-    OperationResult, OrBroken, OverlapError, RequestId, Seq, TypeInfo, add_listener_to_source,
-    is_buffer_reachable,
+    OperationResult, OrBroken, OverlapError, RequestId, Seq, SessionOfScope, SessionStatus,
+    TypeInfo, add_listener_to_source, is_buffer_reachable,
 };
 
 #[cfg(feature = "trace")]
@@ -629,6 +629,76 @@ impl JsonBufferWorldAccess for World {
     }
 }
 
+/// Attached to buffer entities that can be found and accessed through
+/// [`JsonBufferInspection`].
+#[derive(Component, Clone, Copy)]
+pub(crate) struct InspectableJsonBuffer(pub(crate) JsonBuffer);
+
+/// Find and access the buffers of running workflows without holding onto a
+/// key for them, e.g. to inspect buffer contents while debugging.
+///
+/// Only buffers that were created by a [`Diagram`](crate::Diagram) and whose
+/// message type supports serialization can be inspected this way.
+pub trait JsonBufferInspection {
+    /// Get the [`JsonBuffer`] of a buffer entity if it can be inspected.
+    fn inspect_json_buffer(&self, buffer: Entity) -> Option<JsonBuffer>;
+
+    /// Get the active sessions of the buffer's scope that are running inside
+    /// of `session`. The buffer contents of each of these sessions are kept
+    /// separately.
+    fn json_buffer_sessions_within(&mut self, buffer: &JsonBuffer, session: Entity) -> Vec<Entity>;
+
+    /// Make a key to use with [`JsonBufferWorldAccess`] for one session of a
+    /// buffer. The key is not tracked, so holding onto it does not keep the
+    /// session of the buffer alive, and any access through it will be traced
+    /// as coming from the buffer itself.
+    fn inspection_key(
+        &mut self,
+        buffer: &JsonBuffer,
+        session: Entity,
+    ) -> Result<JsonBufferKey, BufferError>;
+}
+
+impl JsonBufferInspection for World {
+    fn inspect_json_buffer(&self, buffer: Entity) -> Option<JsonBuffer> {
+        self.get::<InspectableJsonBuffer>(buffer).map(|b| b.0)
+    }
+
+    fn json_buffer_sessions_within(&mut self, buffer: &JsonBuffer, session: Entity) -> Vec<Entity> {
+        let scope = buffer.scope();
+        let mut query = self.query::<(Entity, &SessionOfScope, &SessionStatus)>();
+        query
+            .iter(self)
+            .filter(|(_, of_scope, status)| of_scope.scope() == scope && status.is_active())
+            .map(|(scoped_session, _, _)| scoped_session)
+            .filter(|scoped_session| self.is_descendent_session(session, *scoped_session))
+            .collect()
+    }
+
+    fn inspection_key(
+        &mut self,
+        buffer: &JsonBuffer,
+        session: Entity,
+    ) -> Result<JsonBufferKey, BufferError> {
+        let mut broadcasters_state = self.query();
+        let mut broadcasters = broadcasters_state.query_mut(self);
+        let mut key_builder = BufferKeyBuilder::without_tracking(
+            buffer.scope(),
+            session,
+            buffer.id(),
+            &mut broadcasters,
+        );
+
+        Ok(JsonBufferKey {
+            body: key_builder
+                .make_body(buffer.id())
+                .map_err(|_| BufferError::BufferStorageMissing)?,
+            interface: buffer.interface,
+            fetch_behavior: buffer.join_behavior,
+        })
+    }
+}
+
 pub struct JsonRef<'a> {
     entry: &'a dyn JsonView,
 }
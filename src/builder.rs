@@ -17,23 +17,30 @@
 
 use bevy_ecs::prelude::{Commands, Entity};
 
-use std::{collections::HashSet, future::Future};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    time::Duration,
+};
 
 use smallvec::SmallVec;
 use thiserror::Error as ThisError;
 
 use crate::{
-    Accessible, Accessing, Accessor, AddOperation, Buffer, BufferKeys, BufferLocation, BufferMap,
-    BufferSettings, Bufferable, Buffering, Chain, Collect, DuplicateBuffer, ForkClone,
+    Accessible, Accessing, Accessor, AddOperation, Async, Buffer, BufferKeys, BufferLocation,
+    BufferMap, BufferSettings, Bufferable, Buffering, Chain, Collect, DuplicateBuffer, ForkClone,
     ForkCloneOutput, ForkOptionOutput, ForkResultOutput, ForkTargetStorage, Gate, GateRequest,
     IncompatibleLayout, Injection, InputSlot, IntoAsyncMap, IntoBlockingMap, IntoMap, Joinable,
     Joined, Node, OperateBuffer, OperateCancel, OperateDynamicGate, OperateQuietCancel,
-    OperateScope, OperateSplit, OperateStaticGate, Output, Provider, RequestOfMap, ResponseOfMap,
-    Scope, ScopeEndpoints, ScopeSettings, ScopeSettingsStorage, Sendish, ServiceInstructions,
-    SplitOutputs, Splittable, StreamPack, StreamTargetMap, StreamsOfMap, Trim, TrimBranch,
-    UnusedTarget, Unzippable, make_option_branching, make_result_branching,
+    OperateScope, OperateSplit, OperateStaticGate, OperationReachability, Output, Provider,
+    RequestOfMap, ResponseOfMap, Scope, ScopeEndpoints, ScopeSettings, ScopeSettingsStorage,
+    Sendish, ServiceInstructions, SplitOutputs, Splittable, Spread, StreamPack, StreamTargetMap,
+    StreamsOfMap, Timeout, Trim, TrimBranch, TrimPoint, UnusedTarget, Unzippable,
+    make_option_branching, make_result_branching, start_operation_timer,
 };
 
+use crate::dyn_node::DynInputSlot;
+
 pub(crate) mod connect;
 pub(crate) use connect::*;
 
@@ -465,6 +472,31 @@ impl<'w, 's, 'a> Builder<'w, 's, 'a> {
         self.create_collect(n, Some(n))
     }
 
+    /// Create a node that fires off each element of an iterable message as a
+    /// new thread within the workflow. Each thread will still have the same
+    /// session ID.
+    ///
+    /// See [`Chain::spread`] for more details.
+    pub fn create_spread<T>(&mut self) -> Node<T, T::Item>
+    where
+        T: 'static + Send + Sync + IntoIterator,
+        T::Item: 'static + Send + Sync,
+    {
+        let source = self.commands.spawn(()).id();
+        let target = self.commands.spawn(UnusedTarget).id();
+        self.commands.queue(AddOperation::new(
+            Some(self.scope()),
+            source,
+            Spread::<T>::new(target),
+        ));
+
+        Node {
+            input: InputSlot::new(self.scope(), source),
+            output: Output::new(self.scope(), target),
+            streams: (),
+        }
+    }
+
     /// Create a new split operation in the workflow. The [`InputSlot`] can take
     /// in values that you want to split, and [`SplitOutputs::build`] will let
     /// you build connections to the split value.
@@ -678,6 +710,98 @@ impl<'w, 's, 'a> Builder<'w, 's, 'a> {
         }
     }
 
+    /// Create a node that passes along its input once `duration` has passed
+    /// according to the [`Time`](bevy_time::Time) resource of the world.
+    ///
+    /// Since the delay follows the virtual clock of `bevy_time`, it will be
+    /// affected by pausing or scaling [`Time<Virtual>`](bevy_time::Virtual).
+    /// If the world has no `Time` resource, the input will be disposed and
+    /// the problem will be reported in [`UnhandledErrors`](crate::UnhandledErrors).
+    pub fn create_delay<T>(&mut self, duration: Duration) -> Node<T, T>
+    where
+        T: 'static + Send + Sync,
+    {
+        let node = self.create_map(move |input: Async<T>| async move {
+            let timer = input
+                .channel
+                .world(move |world| start_operation_timer(world, duration))
+                .await?;
+            timer.await;
+            Some(input.request)
+        });
+
+        let output = self.chain(node.output).dispose_on_none().output();
+        Node {
+            input: node.input,
+            output,
+            streams: (),
+        }
+    }
+
+    /// Create a timeout that watches over a `target` operation. Messages that
+    /// are passed into the timeout will immediately be passed along to its
+    /// output, which should be connected to `target`, and each message starts
+    /// a timer.
+    ///
+    /// If the target is still active for the session when `duration` has
+    /// passed, the target will be trimmed and [`Timeout::on_timeout`] will be
+    /// triggered. Otherwise the timer is simply disposed. Like
+    /// [`Self::create_delay`], the timer follows the [`Time`](bevy_time::Time)
+    /// resource of the world.
+    pub fn create_timeout<T>(
+        &mut self,
+        duration: Duration,
+        target: impl Into<DynInputSlot>,
+    ) -> Timeout<T>
+    where
+        T: 'static + Send + Sync,
+    {
+        let target: DynInputSlot = target.into();
+        assert_eq!(target.scope(), self.scope());
+
+        let start = self.create_map_block(|request: T| (request, ()));
+        let (output, timer) = self.chain(start.output).unzip();
+
+        let feed = start.input.id();
+        let point = TrimPoint::from_dyn_input(&target, true);
+        let target = target.id();
+        let on_timeout = self
+            .chain(timer)
+            .map(move |input: Async<()>| async move {
+                let timer = input
+                    .channel
+                    .world(move |world| start_operation_timer(world, duration))
+                    .await?;
+                timer.await;
+
+                let session = input.id.session;
+                let active = input
+                    .channel
+                    .world(move |world| {
+                        // Ignore the path that passes through this timeout,
+                        // otherwise the target would always seem reachable.
+                        let mut visited = HashMap::from_iter([(feed, false)]);
+                        OperationReachability::new(session, target, None, world, &mut visited)
+                            .check_upstream(target)
+                            .unwrap_or(false)
+                    })
+                    .await;
+
+                active.then_some(())
+            })
+            .dispose_on_none()
+            // A span that begins and ends at the same inclusive point only
+            // trims that point.
+            .then_trim([TrimBranch::between(point, point)])
+            .output();
+
+        Timeout {
+            input: start.input,
+            output,
+            on_timeout,
+        }
+    }
+
     /// Create a gate node that can open and close the gates on one or more
     /// buffers. Feed a [`GateRequest`] into the node and all the associated
     /// buffers will be opened or closed based on the action inside the request.
@@ -26,12 +26,15 @@ use tokio::sync::{
     oneshot,
 };
 
-use std::sync::{Arc, Mutex, atomic::Ordering};
+use std::{
+    sync::{Arc, Mutex, atomic::Ordering},
+    time::Duration,
+};
 
 use crate::{
     AccessError, Accessor, BufferWorldAccess, MiscellaneousFailure, OperationError,
     OperationRoster, Outcome, Promise, ProvideOnce, Reply, RequestExt, RequestId, Seq, StreamPack,
-    UnhandledErrors, async_execution::spawn_task,
+    TimeUnavailable, UnhandledErrors, async_execution::spawn_task, start_timer,
 };
 
 use anyhow::anyhow;
@@ -275,6 +278,22 @@ impl Channel {
         Reply::new(receiver)
     }
 
+    /// Wait until `duration` has passed according to the [`Time`] resource of
+    /// the world.
+    ///
+    /// Unlike the timers of an async runtime, this follows the virtual clock of
+    /// `bevy_time`, so pausing or scaling [`Time<Virtual>`] will affect it.
+    ///
+    /// [`Time`]: bevy_time::Time
+    /// [`Time<Virtual>`]: bevy_time::Virtual
+    pub async fn sleep(&self, duration: Duration) -> Result<(), TimeUnavailable> {
+        let timer = self
+            .world(move |world| start_timer(world, duration))
+            .await?;
+        timer.await;
+        Ok(())
+    }
+
     /// Trigger a callback until it returns `Some(u)` then reply with the `u`.
     /// The callback will be triggered each time a change happens in any of the
     /// buffers included in the `dependencies` [`Accessor`].
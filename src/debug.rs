@@ -15,7 +15,10 @@
  *
 */
 
-use crate::{DeferredRoster, ManageSession, OperationRoster, RequestId, SessionEvent};
+use crate::{
+    DeferredRoster, InScope, ManageSession, OperationRef, OperationRoster, RequestId, SessionEvent,
+    Trace, WorkflowStorage,
+};
 
 use bevy_ecs::{
     prelude::{Commands, Entity, Resource, World},
@@ -86,25 +89,30 @@ impl Debug {
     /// Turn off debugging for a session and unpause it.
     pub fn stop_debugging_for(&mut self, session: Entity) {
         self.debug_sessions.remove(&session);
-        self.paused_sessions.remove(&session);
+        if self.paused_sessions.remove(&session) {
+            self.session_changes
+                .push_back(SessionPauseChange::Unpaused(session));
+        }
     }
 
     /// Pause a session immediately. Note that this does not activate debugging
     /// for the session, so the session will not respond to breakpoints. It will
     /// simply remain paused until it gets unpaused.
     pub fn pause(&mut self, session: Entity) {
-        self.session_changes
-            .push_back(SessionPauseChange::Paused(session));
-        self.paused_sessions.insert(session);
+        if self.paused_sessions.insert(session) {
+            self.session_changes
+                .push_back(SessionPauseChange::Paused(session));
+        }
     }
 
     /// Unpause a session immediately. Note that this does not deactivate debugging,
     /// so if debugging is enabled for the session then it will pause again when
     /// it reaches a breakpoint.
     pub fn unpause(&mut self, session: Entity) {
-        self.paused_sessions.remove(&session);
-        self.session_changes
-            .push_back(SessionPauseChange::Unpaused(session));
+        if self.paused_sessions.remove(&session) {
+            self.session_changes
+                .push_back(SessionPauseChange::Unpaused(session));
+        }
     }
 
     /// Check if any debugging is active.
@@ -193,6 +201,12 @@ impl DebugRoster {
         false
     }
 
+    /// Check whether this request has been given permission to move past a
+    /// pause, without consuming that permission.
+    pub(crate) fn is_stepping(&self, id: RequestId) -> bool {
+        self.allow.contains(&id)
+    }
+
     pub(crate) fn pop_next_in_session(
         &mut self,
         session: Entity,
@@ -272,10 +286,61 @@ impl<'w, 's> DebugStepExt for Commands<'w, 's> {
     }
 }
 
+pub trait WorkflowOperationsExt {
+    /// Get every operation of a workflow that has an operation ID, including
+    /// operations inside of nested scopes. The `workflow` is the provider
+    /// entity of the workflow service.
+    ///
+    /// This can be used to find the entities to use for [`Debug::breakpoints`]
+    /// or [`DebugStepExt::debug_step_for_operation`].
+    fn workflow_operations(&mut self, workflow: Entity) -> Vec<(Entity, OperationRef)>;
+}
+
+impl WorkflowOperationsExt for World {
+    fn workflow_operations(&mut self, workflow: Entity) -> Vec<(Entity, OperationRef)> {
+        let Some(root) = self.get::<WorkflowStorage>(workflow).map(|w| w.scope()) else {
+            return Vec::new();
+        };
+
+        let mut operations = Vec::new();
+        let mut query = self.query::<(Entity, &Trace, &InScope)>();
+        for (entity, trace, in_scope) in query.iter(self) {
+            let Some(id) = trace.info().id() else {
+                continue;
+            };
+
+            // Walk up through any nested scopes until we reach the root scope
+            // of the workflow, or run out of parents.
+            let mut scope = in_scope.scope();
+            let is_in_workflow = loop {
+                if scope == root {
+                    break true;
+                }
+
+                match self.get::<InScope>(scope) {
+                    Some(parent) => scope = parent.scope(),
+                    None => break false,
+                }
+            };
+
+            if is_in_workflow {
+                operations.push((entity, id.clone()));
+            }
+        }
+
+        operations
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{prelude::*, testing::*};
-    use std::collections::HashSet;
+    use crate::{PauseCause, SessionChange, TracedEvent, TracedEventKind, prelude::*, testing::*};
+    use bevy_ecs::prelude::{Entity, Trigger, World};
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+    use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
 
     #[test]
     fn test_debug_step() {
@@ -508,4 +573,187 @@ mod tests {
         context.run_with_conditions(&mut outcome, 1);
         assert_eq!(outcome.try_recv().unwrap().unwrap(), 125.0);
     }
+
+    #[derive(Debug, PartialEq)]
+    enum PauseEvent {
+        User,
+        Breakpoint(Entity),
+        Unpaused,
+    }
+
+    /// Record every pause and unpause that gets announced through the trace.
+    fn record_pause_events(context: &mut TestingContext) -> Arc<Mutex<Vec<PauseEvent>>> {
+        let events: Arc<Mutex<Vec<PauseEvent>>> = Default::default();
+        let sink = Arc::clone(&events);
+        context
+            .app
+            .add_observer(move |trigger: Trigger<TracedEvent>| {
+                let TracedEventKind::SessionEvent(event) = &trigger.event().event else {
+                    return;
+                };
+
+                let event = match &event.change {
+                    SessionChange::Paused(PauseCause::UserRequest) => PauseEvent::User,
+                    SessionChange::Paused(PauseCause::Breakpoint(op)) => {
+                        PauseEvent::Breakpoint(*op)
+                    }
+                    SessionChange::Unpaused => PauseEvent::Unpaused,
+                    _ => return,
+                };
+                sink.lock().unwrap().push(event);
+            });
+        events
+    }
+
+    /// A workflow that passes its input through three operations in a row.
+    /// Each operation reports to its receiver when it runs.
+    fn spawn_three_step_workflow(
+        context: &mut TestingContext,
+    ) -> (Service<(), ()>, Vec<Entity>, Vec<UnboundedReceiver<()>>) {
+        let mut operations = Vec::new();
+        let mut receivers = Vec::new();
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            let mut next = scope.start;
+            for _ in 0..3 {
+                let (sender, receiver) = unbounded_channel();
+                receivers.push(receiver);
+                let node = builder.create_map_block(move |_: ()| {
+                    let _ = sender.send(());
+                });
+                operations.push(node.input.id());
+                builder.connect(next, node.input);
+                next = node.output;
+            }
+            builder.connect(next, scope.terminate);
+        });
+
+        (workflow, operations, receivers)
+    }
+
+    #[test]
+    fn test_debug_step_over_breakpoint() {
+        let mut context = TestingContext::minimal_plugins();
+        let events = record_pause_events(&mut context);
+        let (workflow, operations, mut receivers) = spawn_three_step_workflow(&mut context);
+
+        let Capture {
+            mut outcome,
+            session,
+            ..
+        } = context.command(|commands| commands.request((), workflow).capture());
+
+        let mut debug = context.app.world_mut().get_resource_or_init::<Debug>();
+        debug.start_debugging_for(session, false);
+        debug.breakpoints = HashSet::from([operations[0]]);
+
+        context.run_with_conditions(&mut outcome, 10);
+        assert!(receivers[0].try_recv().is_err());
+        assert_eq!(
+            *events.lock().unwrap(),
+            [PauseEvent::Breakpoint(operations[0])]
+        );
+
+        // Step past the breakpoint and unpause before the step is carried
+        // out. The input that was stepped must not trigger the breakpoint it
+        // was paused at a second time.
+        context.command(|commands| {
+            commands.debug_step(session);
+            commands.queue(move |world: &mut World| {
+                world.resource_mut::<Debug>().unpause(session);
+            });
+        });
+        context.run_with_conditions(&mut outcome, 10);
+        for receiver in &mut receivers {
+            assert!(receiver.try_recv().is_ok());
+        }
+        outcome.try_recv().unwrap().unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            [PauseEvent::Breakpoint(operations[0]), PauseEvent::Unpaused]
+        );
+    }
+
+    #[test]
+    fn test_debug_step_then_break() {
+        let mut context = TestingContext::minimal_plugins();
+        let events = record_pause_events(&mut context);
+        let (workflow, operations, mut receivers) = spawn_three_step_workflow(&mut context);
+
+        let Capture {
+            mut outcome,
+            session,
+            ..
+        } = context.command(|commands| commands.request((), workflow).capture());
+
+        let mut debug = context.app.world_mut().get_resource_or_init::<Debug>();
+        debug.start_debugging_for(session, false);
+        debug.breakpoints = HashSet::from([operations[0], operations[2]]);
+
+        context.run_with_conditions(&mut outcome, 10);
+        assert!(receivers[0].try_recv().is_err());
+
+        context.command(|commands| commands.debug_step(session));
+        context.run_with_conditions(&mut outcome, 10);
+        assert!(receivers[0].try_recv().is_ok());
+        assert!(receivers[1].try_recv().is_err());
+
+        // Unpausing after a step runs the session until it reaches the next
+        // breakpoint.
+        context
+            .app
+            .world_mut()
+            .resource_mut::<Debug>()
+            .unpause(session);
+        context.run_with_conditions(&mut outcome, 10);
+        assert!(receivers[1].try_recv().is_ok());
+        assert!(receivers[2].try_recv().is_err());
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                PauseEvent::Breakpoint(operations[0]),
+                PauseEvent::Unpaused,
+                PauseEvent::Breakpoint(operations[2]),
+            ]
+        );
+
+        context.command(|commands| commands.debug_step(session));
+        context.run_with_conditions(&mut outcome, 10);
+        assert!(receivers[2].try_recv().is_ok());
+        assert!(outcome.try_recv().is_none());
+
+        context
+            .app
+            .world_mut()
+            .resource_mut::<Debug>()
+            .stop_debugging_for(session);
+        context.run_with_conditions(&mut outcome, 1);
+        outcome.try_recv().unwrap().unwrap();
+        assert_eq!(events.lock().unwrap().last(), Some(&PauseEvent::Unpaused));
+    }
+
+    #[test]
+    fn test_no_pause_notifications_without_debug_session() {
+        let mut context = TestingContext::minimal_plugins();
+        let events = record_pause_events(&mut context);
+        let (workflow, operations, mut receivers) = spawn_three_step_workflow(&mut context);
+
+        let Capture {
+            mut outcome,
+            session,
+            ..
+        } = context.command(|commands| commands.request((), workflow).capture());
+
+        // Breakpoints have no effect on sessions that are not being debugged,
+        // and unpausing a session that was never paused is not announced.
+        let mut debug = context.app.world_mut().get_resource_or_init::<Debug>();
+        debug.breakpoints = operations.iter().copied().collect();
+        debug.unpause(session);
+
+        context.run_with_conditions(&mut outcome, 10);
+        for receiver in &mut receivers {
+            assert!(receiver.try_recv().is_ok());
+        }
+        outcome.try_recv().unwrap().unwrap();
+        assert!(events.lock().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,326 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_app::{App, Last, Plugin};
+use bevy_diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+use bevy_ecs::prelude::{Entity, Query, ResMut, Resource, World};
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::Seq;
+
+#[cfg(feature = "trace")]
+use crate::Trace;
+
+/// Gather per-operation measurements of workflow activity into the
+/// [`DiagnosticsStore`] of `bevy_diagnostic`, so bottlenecks can be found
+/// without turning on message tracing.
+///
+/// Each operation gets a group of diagnostics under the path
+/// `crossflow/<operation>/<metric>`, where `<operation>` is the ID from the
+/// `OperationInfo` of the operation, or its entity if it has no ID. Operation
+/// IDs are only available when the `trace` feature is also enabled. The
+/// metrics are:
+/// * [`Self::MESSAGES_IN`] - messages that arrived at the operation during the update
+/// * [`Self::MESSAGES_OUT`] - messages that the operation sent out during the update
+/// * [`Self::INPUT_WAIT`] - average milliseconds that inputs waited in storage
+///   before the operation took them
+/// * [`Self::TASK_DURATION`] - average milliseconds that async tasks of the
+///   operation ran for
+/// * [`Self::BUFFER_MAX_QUEUED`] - the most messages that a buffer was holding
+///   for any one session during the update
+///
+/// Diagnostics only appear once an operation has activity to report.
+#[derive(Default)]
+pub struct WorkflowDiagnosticsPlugin {}
+
+impl WorkflowDiagnosticsPlugin {
+    pub const MESSAGES_IN: &'static str = "messages_in";
+    pub const MESSAGES_OUT: &'static str = "messages_out";
+    pub const INPUT_WAIT: &'static str = "input_wait_ms";
+    pub const TASK_DURATION: &'static str = "task_duration_ms";
+    pub const BUFFER_MAX_QUEUED: &'static str = "buffer_max_queued";
+
+    /// Get the path of a diagnostic for an operation. `operation` should be
+    /// the string form of an [`OperationRef`](crate::OperationRef) or an
+    /// entity.
+    pub fn diagnostic_path(operation: &str, metric: &str) -> DiagnosticPath {
+        // Slashes would be interpreted as extra path components.
+        let operation = operation.replace('/', ".");
+        DiagnosticPath::from_components(["crossflow", &operation, metric])
+    }
+}
+
+impl Plugin for WorkflowDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsStore>()
+            .init_resource::<OperationMetrics>()
+            .add_systems(Last, publish_workflow_diagnostics);
+    }
+}
+
+/// Measurements gathered since the diagnostics were last published. This is
+/// only present when [`WorkflowDiagnosticsPlugin`] has been added.
+#[derive(Resource, Default)]
+pub(crate) struct OperationMetrics {
+    samples: HashMap<Entity, OperationSample>,
+    /// When each input that is still in storage arrived
+    arrivals: HashMap<(Entity, Entity, Seq), Instant>,
+}
+
+#[derive(Default)]
+struct OperationSample {
+    messages_in: u32,
+    messages_out: u32,
+    input_wait: Average,
+    task_duration: Average,
+    buffer_max_queued: Option<usize>,
+}
+
+#[derive(Default)]
+struct Average {
+    total: Duration,
+    count: u32,
+}
+
+impl Average {
+    fn add(&mut self, duration: Duration) {
+        self.total += duration;
+        self.count += 1;
+    }
+
+    fn millis(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total.as_secs_f64() * 1000.0 / self.count as f64)
+    }
+}
+
+impl OperationMetrics {
+    /// A message has been placed into the input storage of `target` by the
+    /// `sources`.
+    pub(crate) fn input_arrived(
+        world: &mut World,
+        target: Entity,
+        session: Entity,
+        seq: Seq,
+        sources: impl Iterator<Item = Entity>,
+    ) {
+        let Some(mut metrics) = world.get_resource_mut::<OperationMetrics>() else {
+            return;
+        };
+
+        metrics.samples.entry(target).or_default().messages_in += 1;
+        for source in sources {
+            metrics.samples.entry(source).or_default().messages_out += 1;
+        }
+        metrics
+            .arrivals
+            .insert((target, session, seq), Instant::now());
+    }
+
+    /// An input has been taken out of the storage of `source`.
+    pub(crate) fn input_taken(world: &mut World, source: Entity, session: Entity, seq: Seq) {
+        let Some(mut metrics) = world.get_resource_mut::<OperationMetrics>() else {
+            return;
+        };
+
+        if let Some(arrived) = metrics.arrivals.remove(&(source, session, seq)) {
+            metrics
+                .samples
+                .entry(source)
+                .or_default()
+                .input_wait
+                .add(arrived.elapsed());
+        }
+    }
+
+    /// The inputs of a session were cleaned out of the storage of `source`.
+    pub(crate) fn inputs_cleared(world: &mut World, source: Entity, session: Entity) {
+        let Some(mut metrics) = world.get_resource_mut::<OperationMetrics>() else {
+            return;
+        };
+
+        metrics
+            .arrivals
+            .retain(|(op, s, _), _| *op != source || *s != session);
+    }
+
+    /// An async task of `node` finished after running for `duration`.
+    pub(crate) fn task_finished(world: &mut World, node: Entity, duration: Duration) {
+        let Some(mut metrics) = world.get_resource_mut::<OperationMetrics>() else {
+            return;
+        };
+
+        metrics
+            .samples
+            .entry(node)
+            .or_default()
+            .task_duration
+            .add(duration);
+    }
+
+    /// A buffer now holds `queued` messages for one of its sessions.
+    pub(crate) fn buffer_queued(world: &mut World, buffer: Entity, queued: usize) {
+        let Some(mut metrics) = world.get_resource_mut::<OperationMetrics>() else {
+            return;
+        };
+
+        let sample = metrics.samples.entry(buffer).or_default();
+        sample.buffer_max_queued = Some(sample.buffer_max_queued.unwrap_or(0).max(queued));
+    }
+}
+
+fn publish_workflow_diagnostics(
+    mut metrics: ResMut<OperationMetrics>,
+    mut store: ResMut<DiagnosticsStore>,
+    #[cfg(feature = "trace")] operations: Query<Option<&Trace>>,
+    #[cfg(not(feature = "trace"))] operations: Query<()>,
+) {
+    let now = Instant::now();
+    let samples = std::mem::take(&mut metrics.samples);
+    for (entity, sample) in samples {
+        let Ok(_trace) = operations.get(entity) else {
+            // The operation has despawned
+            continue;
+        };
+
+        #[cfg(feature = "trace")]
+        let operation = _trace
+            .and_then(|trace| trace.info().id().as_ref())
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| entity.to_string());
+        #[cfg(not(feature = "trace"))]
+        let operation = entity.to_string();
+
+        let mut measure = |metric: &str, value: f64| {
+            let path = WorkflowDiagnosticsPlugin::diagnostic_path(&operation, metric);
+            if store.get(&path).is_none() {
+                store.add(Diagnostic::new(path.clone()));
+            }
+
+            if let Some(diagnostic) = store.get_mut(&path) {
+                diagnostic.add_measurement(DiagnosticMeasurement { time: now, value });
+            }
+        };
+
+        measure(
+            WorkflowDiagnosticsPlugin::MESSAGES_IN,
+            sample.messages_in as f64,
+        );
+        measure(
+            WorkflowDiagnosticsPlugin::MESSAGES_OUT,
+            sample.messages_out as f64,
+        );
+        if let Some(wait) = sample.input_wait.millis() {
+            measure(WorkflowDiagnosticsPlugin::INPUT_WAIT, wait);
+        }
+        if let Some(duration) = sample.task_duration.millis() {
+            measure(WorkflowDiagnosticsPlugin::TASK_DURATION, duration);
+        }
+        if let Some(queued) = sample.buffer_max_queued {
+            measure(WorkflowDiagnosticsPlugin::BUFFER_MAX_QUEUED, queued as f64);
+        }
+    }
+
+    // Forget about inputs whose operations have despawned
+    metrics
+        .arrivals
+        .retain(|(op, _, _), _| operations.contains(*op));
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use crate::{WorkflowDiagnosticsPlugin, diagram::testing::*, prelude::*};
+    use bevy_diagnostic::DiagnosticsStore;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn test_workflow_diagnostics() {
+        let mut fixture = DiagramTestFixture::new();
+        fixture
+            .context
+            .app
+            .add_plugins(WorkflowDiagnosticsPlugin::default());
+
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("sleep"),
+            |builder: &mut Builder, _config: ()| {
+                builder.create_map(|input: Async<i64>| async move {
+                    std::thread::sleep(Duration::from_millis(10));
+                    input.request
+                })
+            },
+        );
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "sleep",
+            "ops": {
+                "sleep": {
+                    "type": "node",
+                    "builder": "sleep",
+                    "next": "buffer",
+                },
+                "buffer": {
+                    "type": "buffer",
+                },
+                "join": {
+                    "type": "join",
+                    "buffers": ["buffer"],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let workflow = fixture
+            .spawn_io_workflow::<i64, JsonMessage>(&diagram)
+            .unwrap();
+        let mut outcome = fixture
+            .context
+            .command(|commands| commands.request(5_i64, workflow).outcome());
+        fixture
+            .context
+            .run_with_conditions(&mut outcome, Duration::from_secs(2));
+        assert!(outcome.try_recv().is_some());
+        fixture.context.run(1);
+        assert!(fixture.context.no_unhandled_errors());
+
+        let store = fixture.context.app.world().resource::<DiagnosticsStore>();
+        let value = |operation: &str, metric: &str| {
+            store
+                .get(&WorkflowDiagnosticsPlugin::diagnostic_path(
+                    operation, metric,
+                ))
+                .and_then(|d| d.values().copied().reduce(f64::max))
+        };
+
+        assert_eq!(
+            value("sleep", WorkflowDiagnosticsPlugin::MESSAGES_IN),
+            Some(1.0)
+        );
+        assert!(value("sleep", WorkflowDiagnosticsPlugin::INPUT_WAIT).is_some());
+        assert!(value("sleep", WorkflowDiagnosticsPlugin::TASK_DURATION).unwrap() >= 10.0);
+        assert_eq!(
+            value("buffer", WorkflowDiagnosticsPlugin::BUFFER_MAX_QUEUED),
+            Some(1.0)
+        );
+    }
+}
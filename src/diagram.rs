@@ -16,28 +16,40 @@
 */
 
 mod buffer_schema;
+mod codegen;
+mod collect_schema;
+mod delay_schema;
 mod diagram_context;
 mod fork_clone_schema;
 mod fork_result_schema;
+mod gate_schema;
+mod hot_reload;
 mod inference;
 mod join_schema;
 mod node_schema;
 mod operation_ref;
 mod output_ref;
 mod registration;
+mod render;
 mod scope_schema;
 mod script_schema;
 mod section_schema;
 mod serialization;
 mod split_schema;
+mod spread_schema;
 mod stream_out_schema;
 mod supported;
+mod switch_schema;
+mod timeout_schema;
 mod transform_schema;
+mod trim_schema;
 mod unzip_schema;
 mod workflow_builder;
 
 #[cfg(feature = "grpc")]
 pub mod grpc;
+#[cfg(feature = "grpc_server")]
+pub(crate) use grpc::serve_grpc_calls;
 
 #[cfg(feature = "zenoh")]
 pub mod zenoh;
@@ -46,11 +58,20 @@ pub mod zenoh;
 pub mod process_bound_python;
 
 use bevy_derive::{Deref, DerefMut};
-use bevy_ecs::system::Commands;
+use bevy_ecs::{system::Commands, world::World};
 pub use buffer_schema::*;
+pub use codegen::{RustCodegenError, RustCodegenOptions};
+pub use collect_schema::*;
+pub use delay_schema::DelaySchema;
+use delay_schema::duration_from_secs;
 pub use diagram_context::*;
 pub use fork_clone_schema::{DynForkClone, ForkCloneSchema, RegisterClone};
 pub use fork_result_schema::{DynForkResult, ForkResultSchema};
+pub use gate_schema::{GateCloseSchema, GateOpenSchema};
+pub(crate) use hot_reload::reload_diagram_services;
+pub use hot_reload::{
+    DiagramLoadError, HotReloadDiagram, HotReloadService, SpawnHotReloadDiagramExt,
+};
 pub use inference::*;
 pub use join_schema::{JoinRegistration, JoinSchema};
 pub use node_schema::NodeSchema;
@@ -62,9 +83,13 @@ pub use script_schema::*;
 pub use section_schema::*;
 pub use serialization::*;
 pub use split_schema::*;
+pub use spread_schema::{RegisterSpread, SpreadFn, SpreadRegistration, SpreadSchema};
 pub use stream_out_schema::*;
+pub use switch_schema::{SwitchCase, SwitchSchema};
+pub use timeout_schema::TimeoutSchema;
 use tracing::debug;
-pub use transform_schema::{TransformError, TransformSchema};
+pub use transform_schema::{TransformError, TransformSchema, create_cel_transform};
+pub use trim_schema::{TrimBranchSchema, TrimPointSchema, TrimSchema};
 pub use unzip_schema::UnzipSchema;
 pub use workflow_builder::*;
 
@@ -75,8 +100,8 @@ use std::{borrow::Cow, collections::HashMap, fmt::Display, io::Read, sync::Arc};
 pub use crate::type_info::TypeInfo;
 use crate::{
     Builder, DuplicateBuffer, IdentifierRef, IncompatibleLayout, IncrementalScopeError,
-    JsonMessage, MessageTypeHint, Scope, Service, SpawnWorkflowExt, SplitConnectionError,
-    StreamPack, TryJoinError, format_list, is_default,
+    JsonMessage, MessageTypeHint, RunCommandsOnWorldExt, Scope, Service, SpawnWorkflowExt,
+    SplitConnectionError, StreamPack, TryJoinError, format_list, is_default,
 };
 
 use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
@@ -345,8 +370,16 @@ pub enum DiagramOperation {
     Unzip(UnzipSchema),
     ForkResult(ForkResultSchema),
     Split(SplitSchema),
+    Spread(SpreadSchema),
+    Collect(CollectSchema),
     Join(JoinSchema),
+    GateOpen(GateOpenSchema),
+    GateClose(GateCloseSchema),
+    Trim(TrimSchema),
+    Delay(DelaySchema),
+    Timeout(TimeoutSchema),
     Transform(TransformSchema),
+    Switch(SwitchSchema),
     Buffer(BufferSchema),
     BufferAccess(BufferAccessSchema),
     Listen(ListenSchema),
@@ -362,17 +395,25 @@ impl BuildDiagramOperation for DiagramOperation {
         match self {
             Self::Buffer(op) => op.build_diagram_operation(id, ctx),
             Self::BufferAccess(op) => op.build_diagram_operation(id, ctx),
+            Self::Collect(op) => op.build_diagram_operation(id, ctx),
             Self::ForkClone(op) => op.build_diagram_operation(id, ctx),
             Self::ForkResult(op) => op.build_diagram_operation(id, ctx),
             Self::Join(op) => op.build_diagram_operation(id, ctx),
+            Self::GateOpen(op) => op.build_diagram_operation(id, ctx),
+            Self::GateClose(op) => op.build_diagram_operation(id, ctx),
+            Self::Trim(op) => op.build_diagram_operation(id, ctx),
+            Self::Delay(op) => op.build_diagram_operation(id, ctx),
+            Self::Timeout(op) => op.build_diagram_operation(id, ctx),
             Self::Listen(op) => op.build_diagram_operation(id, ctx),
             Self::Node(op) => op.build_diagram_operation(id, ctx),
             Self::Scope(op) => op.build_diagram_operation(id, ctx),
             Self::Script(op) => op.build_diagram_operation(id, ctx),
             Self::Section(op) => op.build_diagram_operation(id, ctx),
             Self::Split(op) => op.build_diagram_operation(id, ctx),
+            Self::Spread(op) => op.build_diagram_operation(id, ctx),
             Self::StreamOut(op) => op.build_diagram_operation(id, ctx),
             Self::Transform(op) => op.build_diagram_operation(id, ctx),
+            Self::Switch(op) => op.build_diagram_operation(id, ctx),
             Self::Unzip(op) => op.build_diagram_operation(id, ctx),
         }
     }
@@ -385,17 +426,25 @@ impl BuildDiagramOperation for DiagramOperation {
         match self {
             Self::Buffer(op) => op.apply_message_type_constraints(id, ctx),
             Self::BufferAccess(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Collect(op) => op.apply_message_type_constraints(id, ctx),
             Self::ForkClone(op) => op.apply_message_type_constraints(id, ctx),
             Self::ForkResult(op) => op.apply_message_type_constraints(id, ctx),
             Self::Join(op) => op.apply_message_type_constraints(id, ctx),
+            Self::GateOpen(op) => op.apply_message_type_constraints(id, ctx),
+            Self::GateClose(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Trim(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Delay(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Timeout(op) => op.apply_message_type_constraints(id, ctx),
             Self::Listen(op) => op.apply_message_type_constraints(id, ctx),
             Self::Node(op) => op.apply_message_type_constraints(id, ctx),
             Self::Scope(op) => op.apply_message_type_constraints(id, ctx),
             Self::Script(op) => op.apply_message_type_constraints(id, ctx),
             Self::Section(op) => op.apply_message_type_constraints(id, ctx),
             Self::Split(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Spread(op) => op.apply_message_type_constraints(id, ctx),
             Self::StreamOut(op) => op.apply_message_type_constraints(id, ctx),
             Self::Transform(op) => op.apply_message_type_constraints(id, ctx),
+            Self::Switch(op) => op.apply_message_type_constraints(id, ctx),
             Self::Unzip(op) => op.apply_message_type_constraints(id, ctx),
         }
     }
@@ -407,17 +456,25 @@ impl BuildDiagramOperation for DiagramOperation {
         match self {
             Self::Buffer(op) => op.child_operations(templates),
             Self::BufferAccess(op) => op.child_operations(templates),
+            Self::Collect(op) => op.child_operations(templates),
             Self::ForkClone(op) => op.child_operations(templates),
             Self::ForkResult(op) => op.child_operations(templates),
             Self::Join(op) => op.child_operations(templates),
+            Self::GateOpen(op) => op.child_operations(templates),
+            Self::GateClose(op) => op.child_operations(templates),
+            Self::Trim(op) => op.child_operations(templates),
+            Self::Delay(op) => op.child_operations(templates),
+            Self::Timeout(op) => op.child_operations(templates),
             Self::Listen(op) => op.child_operations(templates),
             Self::Node(op) => op.child_operations(templates),
             Self::Scope(op) => op.child_operations(templates),
             Self::Script(op) => op.child_operations(templates),
             Self::Section(op) => op.child_operations(templates),
             Self::Split(op) => op.child_operations(templates),
+            Self::Spread(op) => op.child_operations(templates),
             Self::StreamOut(op) => op.child_operations(templates),
             Self::Transform(op) => op.child_operations(templates),
+            Self::Switch(op) => op.child_operations(templates),
             Self::Unzip(op) => op.child_operations(templates),
         }
     }
@@ -636,6 +693,22 @@ impl Diagram {
         cmds: &mut Commands,
         registry: &DiagramElementRegistry,
     ) -> Result<Service<Request, Response, Streams>, DiagramError>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+        Streams: StreamPack,
+    {
+        self.spawn_workflow_with_options(cmds, registry, &Default::default())
+    }
+
+    /// Spawns a workflow from this diagram, with extra options that change how
+    /// the operations get built.
+    pub(crate) fn spawn_workflow_with_options<Request, Response, Streams>(
+        &self,
+        cmds: &mut Commands,
+        registry: &DiagramElementRegistry,
+        options: &WorkflowBuildOptions,
+    ) -> Result<Service<Request, Response, Streams>, DiagramError>
     where
         Request: 'static + Send + Sync,
         Response: 'static + Send + Sync,
@@ -651,7 +724,7 @@ impl Diagram {
                     scope.terminate.id()
                 );
 
-                if let Err(had_err) = create_workflow(scope, builder, registry, self) {
+                if let Err(had_err) = create_workflow(scope, builder, registry, self, options) {
                     err = Some(had_err);
                 }
             },
@@ -666,6 +739,67 @@ impl Diagram {
         Ok(w)
     }
 
+    /// Build this diagram in a scratch [`World`] to find the errors that only
+    /// show up while building, e.g. when a node builder rejects its config.
+    /// The errors that are found get added to `errors`.
+    ///
+    /// Building a workflow stops at its first error, so each node is first
+    /// built on its own to find the errors of every node. The whole workflow is
+    /// only built if `errors` is still empty after that, since otherwise it
+    /// would just fail on an error that was already reported.
+    pub fn trial_build<Request, Response, Streams>(
+        &self,
+        registry: &DiagramElementRegistry,
+        errors: &mut Vec<DiagramError>,
+    ) where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+        Streams: StreamPack,
+    {
+        let mut world = World::new();
+        let mut pending = vec![(Vec::<Arc<str>>::new(), self.ops.clone())];
+        while let Some((namespaces, ops)) = pending.pop() {
+            let mut ops: Vec<_> = ops.iter().collect();
+            ops.sort_by_key(|(id, _)| *id);
+            for (id, op) in ops {
+                if let Ok(Some(children)) = op.child_operations(&self.templates) {
+                    let mut child_namespaces = namespaces.clone();
+                    child_namespaces.push(Arc::clone(id));
+                    pending.push((child_namespaces, children));
+                }
+
+                let DiagramOperation::Node(node) = op.as_ref() else {
+                    continue;
+                };
+
+                let result = world.command(|cmds| {
+                    let mut result = Ok(());
+                    cmds.spawn_io_workflow(|_: Scope<(), ()>, builder: &mut Builder| {
+                        result = registry
+                            .get_node_registration(&node.builder)
+                            .and_then(|registration| {
+                                registration.create_node(builder, (*node.config).clone())
+                            })
+                            .map(|_| ());
+                    });
+                    result
+                });
+
+                if let Err(code) = result {
+                    errors.push(code.in_port(OperationRef::from(id).in_namespaces(&namespaces)));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            let result = world
+                .command(|cmds| self.spawn_workflow::<Request, Response, Streams>(cmds, registry));
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+    }
+
     /// Spawns a workflow from this diagram.
     ///
     /// # Examples
@@ -946,6 +1080,13 @@ pub struct DiagramErrorContext {
     port_id: Option<PortRef>,
 }
 
+impl DiagramErrorContext {
+    /// The port where the error was found, if it is known.
+    pub fn port(&self) -> Option<&PortRef> {
+        self.port_id.as_ref()
+    }
+}
+
 impl Display for DiagramErrorContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(op_id) = &self.port_id {
@@ -1050,6 +1191,21 @@ pub enum DiagramErrorCode {
     )]
     NotSplittable(Cow<'static, str>),
 
+    #[error(
+        "Message cannot be spread. Make sure to use .with_spread() when registering the message. Type: {0}"
+    )]
+    NotSpreadable(Cow<'static, str>),
+
+    #[error(
+        "Message cannot be collected. Make sure to use .with_collect() when registering the message. Type: {0}"
+    )]
+    NotCollectable(Cow<'static, str>),
+
+    #[error(
+        "Invalid limits for collect operation: min [{min}] must not exceed max [{max}], and max must be greater than 0"
+    )]
+    InvalidCollectLimits { min: usize, max: usize },
+
     #[error(
         "Message cannot be joined. Make sure to use .with_join() when building the target node. Type: {0}"
     )]
@@ -1070,6 +1226,19 @@ pub enum DiagramErrorCode {
     #[error("There was an attempt to connect to an unknown operation: [{0}]")]
     UnknownOperation(OperationRef),
 
+    #[error(
+        "Operation [{0}] cannot be used as a trim point. Trim points must refer to operations that have an input slot, such as nodes."
+    )]
+    InvalidTrimPoint(NextOperation),
+
+    #[error(
+        "Operation [{0}] cannot be watched by a timeout. The target of a timeout must be an operation that has an input slot, such as a node."
+    )]
+    InvalidTimeoutTarget(NextOperation),
+
+    #[error("Invalid duration [{0}]. Durations must be a finite, non-negative number of seconds.")]
+    InvalidDuration(f64),
+
     #[error("There was an attempt to use an unknown section template: [{0}]")]
     UnknownTemplate(OperationName),
 
@@ -532,9 +532,9 @@ mod tests {
 
     use crate::{
         Accessor, AnyBufferKey, AnyBufferWorldAccess, Blocking, BufferAccess, BufferAccessMut,
-        BufferKey, BufferWorldAccess, Diagram, DiagramErrorCode, IntoCallback, JsonBufferKey,
-        JsonBufferWorldAccess, JsonMessage, Node, NodeBuilderOptions, RequestId,
-        diagram::testing::DiagramTestFixture,
+        BufferKey, BufferWorldAccess, Diagram, DiagramErrorCode, InspectableJsonBuffer,
+        IntoCallback, JsonBufferInspection, JsonBufferKey, JsonBufferWorldAccess, JsonMessage,
+        Node, NodeBuilderOptions, RequestExt, RequestId, diagram::testing::DiagramTestFixture,
     };
 
     /// create a new [`DiagramTestFixture`] with some extra builders.
@@ -937,6 +937,83 @@ mod tests {
         assert_eq!(result, 11);
     }
 
+    #[test]
+    fn test_inspect_json_buffer() {
+        let mut fixture = new_fixture();
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("never_finish"),
+            |builder, _config: ()| {
+                builder.create_map_async(|_: JsonMessage| std::future::pending::<JsonMessage>())
+            },
+        );
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fork_input",
+            "ops": {
+                "fork_input": {
+                    "type": "fork_clone",
+                    "next": ["string_output", "never_finish"],
+                },
+                "string_output": {
+                    "type": "node",
+                    "builder": "string_output",
+                    "next": "buffer",
+                },
+                "buffer": {
+                    "type": "buffer",
+                    "settings": { "retention": "keep_all" },
+                },
+                "never_finish": {
+                    "type": "node",
+                    "builder": "never_finish",
+                    "next": { "builtin": "terminate" },
+                },
+            }
+        }))
+        .unwrap();
+
+        let workflow = fixture.spawn_json_io_workflow(&diagram).unwrap();
+        let mut capture = fixture
+            .context
+            .command(|cmds| cmds.request(JsonMessage::Null, workflow).capture());
+        fixture.context.run_with_conditions(&mut capture.outcome, 5);
+        assert!(fixture.context.no_unhandled_errors());
+
+        let world = fixture.context.app.world_mut();
+        let buffers: Vec<_> = world
+            .query::<&InspectableJsonBuffer>()
+            .iter(world)
+            .map(|b| b.0)
+            .collect();
+        assert_eq!(buffers.len(), 1);
+        let buffer = world.inspect_json_buffer(buffers[0].id()).unwrap();
+
+        let sessions = world.json_buffer_sessions_within(&buffer, capture.session);
+        assert_eq!(sessions.len(), 1);
+        let key = world.inspection_key(&buffer, sessions[0]).unwrap();
+
+        {
+            let view = world.json_buffer_view_untraced(&key).unwrap();
+            assert_eq!(view.len(), 1);
+            assert_eq!(view.oldest().unwrap().serialize().unwrap(), "hello");
+        }
+
+        let req = RequestId {
+            session: sessions[0],
+            source: buffer.id(),
+            seq: 0,
+        };
+        world
+            .json_buffer_mut(req, &key, |mut access| {
+                access.push_json(JsonMessage::from("world")).unwrap();
+            })
+            .unwrap();
+        let view = world.json_buffer_view_untraced(&key).unwrap();
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.newest().unwrap().serialize().unwrap(), "world");
+    }
+
     #[test]
     fn test_any_buffer_access() {
         let mut fixture = new_fixture();
@@ -0,0 +1,1432 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    sync::Arc,
+};
+
+use thiserror::Error as ThisError;
+
+use crate::{IdentifierRef, RetentionPolicy};
+
+use super::{
+    BufferSelection, BuiltinTarget, Diagram, DiagramError, DiagramErrorCode, DiagramOperation,
+    InferredMessageTypes, MetadataAccess, NamedOutputRef, NextOperation, OperationName,
+    OperationRef, Operations, PortRef, output_ref,
+};
+
+/// Options for [`Diagram::generate_rust`].
+#[derive(Debug, Clone, Default)]
+pub struct RustCodegenOptions {
+    /// The generated functions will be named `build_{name}` and `spawn_{name}`.
+    /// If this is not specified, `diagram` will be used.
+    pub name: Option<String>,
+    /// The Rust type of requests into the workflow. If this is not specified,
+    /// it will be determined from the operation that the diagram starts with.
+    pub request: Option<String>,
+    /// The Rust type of responses from the workflow. If this is not specified,
+    /// it will be determined from the operations that terminate the diagram.
+    pub response: Option<String>,
+    /// The name of the crate that the generated code will be included in.
+    /// Message types that are defined in this crate will be referred to with
+    /// `crate::` paths.
+    pub crate_name: Option<String>,
+}
+
+impl RustCodegenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_request(mut self, request: impl Into<String>) -> Self {
+        self.request = Some(request.into());
+        self
+    }
+
+    pub fn with_response(mut self, response: impl Into<String>) -> Self {
+        self.response = Some(response.into());
+        self
+    }
+
+    pub fn with_crate_name(mut self, crate_name: impl Into<String>) -> Self {
+        self.crate_name = Some(crate_name.into());
+        self
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum RustCodegenError {
+    /// The operation is not one of the operations that
+    /// [`Diagram::generate_rust`] supports.
+    #[error("operation [{operation}] of type [{kind}] cannot be generated as native code")]
+    UnsupportedOperation {
+        operation: OperationName,
+        kind: String,
+    },
+
+    /// The operation uses a setting that [`Diagram::generate_rust`] does not
+    /// support.
+    #[error(
+        "the [{setting}] setting of operation [{operation}] cannot be generated as native code"
+    )]
+    UnsupportedSetting {
+        operation: OperationName,
+        setting: &'static str,
+    },
+
+    #[error("unable to determine the {0} type of the workflow, specify it in the codegen options")]
+    UnknownBoundaryType(&'static str),
+
+    #[error("unable to determine the message type of {0}")]
+    UnknownMessageType(PortRef),
+
+    #[error(transparent)]
+    Diagram(#[from] DiagramErrorCode),
+
+    /// The message types of the diagram could not be inferred.
+    #[error(transparent)]
+    Inference(#[from] DiagramError),
+}
+
+impl Diagram {
+    /// Generate Rust source code that builds the same workflow as this diagram
+    /// by calling the native [`Builder`][1] API directly.
+    ///
+    /// The generated code contains two functions:
+    /// * `build_{name}(scope, builder, registry)` builds the workflow inside of
+    ///   a [`Scope`][2], which can be used with [`SpawnWorkflowExt`][3].
+    /// * `spawn_{name}(commands, registry)` spawns the workflow as a
+    ///   [`Service`][4].
+    ///
+    /// Each `scope` operation gets a private function of its own that builds
+    /// the operations inside of it.
+    ///
+    /// Nodes are still created by the node builders of the registry, looked up
+    /// by their id, but the outputs of the nodes are connected with their
+    /// concrete message types. Mistakes in the connections become compile
+    /// errors, and no implicit serialization or deserialization is inserted
+    /// between nodes. If a node builder no longer produces the message types
+    /// that the code was generated for, building the workflow will fail with
+    /// [`DiagramErrorCode::TypeMismatch`].
+    ///
+    /// Message types are named with [`std::any::type_name`], so they should be
+    /// reachable through public paths.
+    ///
+    /// # Supported operations
+    ///
+    /// * `node`, including its `stream_out` connections
+    /// * `fork_clone`, `fork_result` and `unzip`
+    /// * `buffer`, `join` and `listen`, except for serialized buffers and joins
+    /// * `split`, whose outputs carry only the split values like they do when
+    ///   the diagram is built dynamically
+    /// * `transform`, using [`create_cel_transform`][5]
+    /// * `scope`, which is generated as a separate function, except for scopes
+    ///   with `stream_out` connections
+    /// * the `terminate`, `dispose`, `cancel` and `implicit_cancel` builtin
+    ///   targets
+    ///
+    /// Any other operation, such as `section`, `script`, `switch`, `spread`,
+    /// `collect`, `buffer_access`, `trim`, `delay`, `timeout` or the gate
+    /// operations, makes this return [`RustCodegenError::UnsupportedOperation`].
+    /// Diagrams that need them should keep being built with
+    /// [`Diagram::spawn_workflow`].
+    ///
+    /// Message types are inferred the same way as when the diagram is built,
+    /// except that the request and response of the workflow come from the
+    /// operations that they connect to.
+    ///
+    /// This can be used from a build script together with the metadata that
+    /// `print-registry` of the `crossflow` command line tool prints:
+    ///
+    /// ```no_run
+    /// use crossflow::{Diagram, DiagramElementMetadata, RustCodegenOptions};
+    ///
+    /// let diagram = Diagram::from_reader(std::fs::File::open("diagram.json").unwrap()).unwrap();
+    /// let registry: DiagramElementMetadata =
+    ///     serde_json::from_reader(std::fs::File::open("registry.json").unwrap()).unwrap();
+    /// let code = diagram
+    ///     .generate_rust(&registry, &RustCodegenOptions::new().with_crate_name("my_crate"))
+    ///     .unwrap();
+    ///
+    /// let out_dir = std::env::var("OUT_DIR").unwrap();
+    /// std::fs::write(format!("{out_dir}/diagram.rs"), code).unwrap();
+    /// ```
+    ///
+    /// [1]: crate::Builder
+    /// [2]: crate::Scope
+    /// [3]: crate::SpawnWorkflowExt
+    /// [4]: crate::Service
+    /// [5]: crate::create_cel_transform
+    pub fn generate_rust(
+        &self,
+        lookup: &dyn MetadataAccess,
+        options: &RustCodegenOptions,
+    ) -> Result<String, RustCodegenError> {
+        RustGenerator::new(self, lookup, options)?.generate()
+    }
+}
+
+struct RustGenerator<'a> {
+    diagram: &'a Diagram,
+    lookup: &'a dyn MetadataAccess,
+    options: &'a RustCodegenOptions,
+    /// The message type of every port whose type could be inferred
+    message_types: InferredMessageTypes,
+    /// Functions that build the scope operations of the diagram
+    scope_functions: Vec<String>,
+}
+
+impl<'a> RustGenerator<'a> {
+    fn new(
+        diagram: &'a Diagram,
+        lookup: &'a dyn MetadataAccess,
+        options: &'a RustCodegenOptions,
+    ) -> Result<Self, RustCodegenError> {
+        let message_types = diagram.infer_message_types_with_open_boundary(lookup)?;
+        Ok(Self {
+            diagram,
+            lookup,
+            options,
+            message_types,
+            scope_functions: Vec::new(),
+        })
+    }
+
+    fn generate(mut self) -> Result<String, RustCodegenError> {
+        let diagram = self.diagram;
+        let request = match &self.options.request {
+            Some(request) => request.clone(),
+            None => {
+                let port = PortRef::from(OperationRef::from(&diagram.start));
+                let index = self
+                    .message_types
+                    .get(&port)
+                    .copied()
+                    .ok_or(RustCodegenError::UnknownBoundaryType("request"))?;
+                self.type_path(index)?
+            }
+        };
+        let response = match &self.options.response {
+            Some(response) => response.clone(),
+            None => {
+                let port = PortRef::from(OperationRef::from(&NextOperation::terminate()));
+                let index = self
+                    .message_types
+                    .get(&port)
+                    .copied()
+                    .ok_or(RustCodegenError::UnknownBoundaryType("response"))?;
+                self.type_path(index)?
+            }
+        };
+        let name = self.options.name.as_deref().unwrap_or("diagram");
+
+        let mut root = ScopeGenerator::new(
+            &diagram.ops,
+            Vec::new(),
+            diagram.on_implicit_error(),
+            format!("build_{name}"),
+        );
+        let mut code = String::new();
+        let _ = writeln!(
+            code,
+            "// This file was generated by crossflow from a diagram. Do not edit it by hand.\n"
+        );
+        code.push_str(&self.generate_function(
+            &mut root,
+            &diagram.start,
+            &request,
+            &response,
+            "/// Build the workflow of the diagram inside of `scope`.\npub ",
+        )?);
+
+        let _ = writeln!(
+            code,
+            "\n/// Spawn the workflow of the diagram as a service.\n\
+            pub fn spawn_{name}(\n    \
+                commands: &mut ::crossflow::bevy_ecs::prelude::Commands,\n    \
+                registry: &::crossflow::DiagramElementRegistry,\n\
+            ) -> ::std::result::Result<\n    \
+                ::crossflow::Service<{request}, {response}, ()>,\n    \
+                ::crossflow::DiagramErrorCode,\n\
+            > {{\n    \
+                use ::crossflow::SpawnWorkflowExt;\n\n    \
+                let mut result = ::std::result::Result::Ok(());\n    \
+                let workflow = commands.spawn_workflow(|scope, builder| {{\n        \
+                    result = build_{name}(scope, builder, registry);\n    \
+                }});\n\n    \
+                if let ::std::result::Result::Err(err) = result {{\n        \
+                    // Despawn the workflow because it was not built successfully.\n        \
+                    commands.entity(workflow.provider()).despawn();\n        \
+                    return ::std::result::Result::Err(err);\n    \
+                }}\n\n    \
+                ::std::result::Result::Ok(workflow)\n\
+            }}"
+        );
+
+        for function in &self.scope_functions {
+            code.push('\n');
+            code.push_str(function);
+        }
+
+        Ok(code)
+    }
+
+    /// Generate a function that builds the operations of `scope`. The text of
+    /// `header` goes in front of the `fn` keyword.
+    fn generate_function(
+        &mut self,
+        scope: &mut ScopeGenerator,
+        start: &NextOperation,
+        request: &str,
+        response: &str,
+        header: &str,
+    ) -> Result<String, RustCodegenError> {
+        // Buffers go first because joins and listens refer to them.
+        let mut ops: Vec<_> = scope.ops.iter().collect();
+        ops.sort_by_key(|(name, op)| (!matches!(op.as_ref(), DiagramOperation::Buffer(_)), *name));
+
+        let mut uses_registry = false;
+        let mut operations = String::new();
+        let mut connections = String::new();
+        for (op_name, op) in ops {
+            let var = scope.variables[op_name].clone();
+            let _ = writeln!(operations, "    // {op_name}");
+            match op.as_ref() {
+                DiagramOperation::Node(node) => {
+                    uses_registry = true;
+                    let metadata = self.lookup.node_metadata(&node.builder)?;
+                    let input_type = self.type_path(metadata.request())?;
+                    let output_type = self.type_path(metadata.response())?;
+                    let mutable = if node.stream_out.is_empty() {
+                        ""
+                    } else {
+                        "mut "
+                    };
+                    let config = match node.config.as_ref() {
+                        serde_json::Value::Null => "::crossflow::JsonMessage::Null",
+                        config => {
+                            let _ = writeln!(
+                                operations,
+                                "    let config = {:?}\n        \
+                                .parse::<::crossflow::JsonMessage>()\n        \
+                                .map_err(|err| {{\n            \
+                                ::crossflow::DiagramErrorCode::ConfigError(\
+                                ::std::sync::Arc::new(err))\n        \
+                                }})?;",
+                                config.to_string(),
+                            );
+                            "config"
+                        }
+                    };
+                    let _ = writeln!(operations, "    let {mutable}{var} = registry");
+                    let _ = writeln!(
+                        operations,
+                        "        .get_node_registration({:?})?",
+                        node.builder.as_ref()
+                    );
+                    let _ = writeln!(operations, "        .create_node(builder, {config})?;");
+                    let _ = writeln!(
+                        operations,
+                        "    let {var}_input: ::crossflow::InputSlot<{input_type}> = \
+                        {var}.input.into_input()?;"
+                    );
+                    let _ = writeln!(
+                        operations,
+                        "    let {var}_output: ::crossflow::Output<{output_type}> = \
+                        {var}.output.into_output()?;"
+                    );
+                    scope.connect(&mut connections, format!("{var}_output"), false, &node.next)?;
+
+                    let mut streams: Vec<_> = node.stream_out.iter().collect();
+                    streams.sort_by_key(|(stream, _)| *stream);
+                    for (stream, target) in streams {
+                        let index = metadata.streams().get(stream.as_ref()).ok_or_else(|| {
+                            DiagramErrorCode::MissingStream(super::MissingStream {
+                                missing_name: stream.clone(),
+                                available_names: metadata
+                                    .streams()
+                                    .keys()
+                                    .map(|name| name.as_ref().into())
+                                    .collect(),
+                            })
+                        })?;
+                        let stream_type = self.type_path(*index)?;
+                        let stream_var = format!("{var}_stream_{}", rust_identifier(stream));
+                        let _ = writeln!(
+                            operations,
+                            "    let {stream_var}: ::crossflow::Output<{stream_type}> = \
+                            {var}.streams.take_stream({:?})?.into_output()?;",
+                            stream.as_ref()
+                        );
+                        scope.connect(&mut connections, stream_var, false, target)?;
+                    }
+                }
+                DiagramOperation::ForkClone(fork_clone) => {
+                    let _ = writeln!(
+                        operations,
+                        "    let ({var}_input, {var}_output) = builder.create_fork_clone();"
+                    );
+                    for target in &fork_clone.next {
+                        scope.connect(
+                            &mut connections,
+                            format!("{var}_output.clone_output(builder)"),
+                            true,
+                            target,
+                        )?;
+                    }
+                }
+                DiagramOperation::ForkResult(fork_result) => {
+                    let _ = writeln!(
+                        operations,
+                        "    let ({var}_input, {var}_output) = builder.create_fork_result();"
+                    );
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output.ok"),
+                        false,
+                        &fork_result.ok,
+                    )?;
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output.err"),
+                        false,
+                        &fork_result.err,
+                    )?;
+                }
+                DiagramOperation::Buffer(buffer) => {
+                    if buffer.serialize {
+                        return Err(RustCodegenError::UnsupportedSetting {
+                            operation: op_name.clone(),
+                            setting: "serialize",
+                        });
+                    }
+
+                    let message_type = self.port_type(scope.input_port(op_name))?;
+                    let settings = match buffer.settings.retention() {
+                        RetentionPolicy::KeepLast(1) => {
+                            "::crossflow::BufferSettings::default()".to_owned()
+                        }
+                        RetentionPolicy::KeepLast(n) => {
+                            format!("::crossflow::BufferSettings::keep_last({n})")
+                        }
+                        RetentionPolicy::KeepFirst(n) => {
+                            format!("::crossflow::BufferSettings::keep_first({n})")
+                        }
+                        RetentionPolicy::KeepAll => {
+                            "::crossflow::BufferSettings::keep_all()".to_owned()
+                        }
+                    };
+                    let _ = writeln!(
+                        operations,
+                        "    let {var}: ::crossflow::Buffer<{message_type}> = \
+                        builder.create_buffer({settings});"
+                    );
+                }
+                DiagramOperation::Join(join) => {
+                    if join.serialize {
+                        return Err(RustCodegenError::UnsupportedSetting {
+                            operation: op_name.clone(),
+                            setting: "serialize",
+                        });
+                    }
+
+                    let output_type =
+                        self.port_type(scope.output_port(output_ref(op_name).next()))?;
+                    scope.buffer_map(&mut operations, &var, &join.buffers, &join.clone)?;
+                    let _ = writeln!(
+                        operations,
+                        "    let {var}_output: ::crossflow::Output<{output_type}> = \
+                        builder.try_join(&{var}_buffers)?.output();"
+                    );
+                    scope.connect(&mut connections, format!("{var}_output"), false, &join.next)?;
+                }
+                DiagramOperation::Listen(listen) => {
+                    let output_type =
+                        self.port_type(scope.output_port(output_ref(op_name).next()))?;
+                    scope.buffer_map(&mut operations, &var, &listen.buffers, &[])?;
+                    let _ = writeln!(
+                        operations,
+                        "    let {var}_output: ::crossflow::Output<{output_type}> = \
+                        builder.try_listen(&{var}_buffers)?.output();"
+                    );
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output"),
+                        false,
+                        &listen.next,
+                    )?;
+                }
+                DiagramOperation::Transform(transform) => {
+                    let _ = writeln!(
+                        operations,
+                        "    let {var} = ::crossflow::create_cel_transform(builder, {:?})?;",
+                        transform.cel
+                    );
+                    let _ = writeln!(
+                        operations,
+                        "    let ({var}_fork_input, {var}_output) = builder.create_fork_result();"
+                    );
+                    let _ = writeln!(
+                        operations,
+                        "    builder.connect({var}.output, {var}_fork_input);"
+                    );
+                    let _ = writeln!(operations, "    let {var}_input = {var}.input;");
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output.ok"),
+                        false,
+                        &transform.next,
+                    )?;
+                    // Without an explicit error target, a failed transform is
+                    // treated as an implicit error of the scope.
+                    let on_error = transform
+                        .on_error
+                        .clone()
+                        .unwrap_or_else(|| scope.on_implicit_error.clone());
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output.err"),
+                        false,
+                        &on_error,
+                    )?;
+                }
+                DiagramOperation::Unzip(unzip) => {
+                    let input_type = self.port_type(scope.input_port(op_name))?;
+                    let _ = writeln!(
+                        operations,
+                        "    let ({var}_input, {var}_output) = \
+                        builder.create_unzip::<{input_type}>();"
+                    );
+                    for (i, target) in unzip.next.iter().enumerate() {
+                        scope.connect(
+                            &mut connections,
+                            format!("{var}_output.{i}"),
+                            false,
+                            target,
+                        )?;
+                    }
+                }
+                DiagramOperation::Split(split) => {
+                    let input_type = self.port_type(scope.input_port(op_name))?;
+                    let _ = writeln!(
+                        operations,
+                        "    let ({var}_input, {var}_split) = \
+                        builder.create_split::<{input_type}>();"
+                    );
+                    let _ = writeln!(
+                        operations,
+                        "    let mut {var}_split = {var}_split.build(builder);"
+                    );
+
+                    // The outputs of a split only carry the values, the same
+                    // as when the diagram is built dynamically.
+                    let chain = "|chain| chain.map_block(|(_, value)| value).output()";
+                    let mut outputs = Vec::new();
+                    for (i, target) in split.sequential.iter().enumerate() {
+                        let port = scope.output_port(output_ref(op_name).next_index(i));
+                        outputs.push((
+                            port,
+                            format!("{var}_next_{i}"),
+                            format!("sequential_chain({i}, {chain})"),
+                            target,
+                        ));
+                    }
+
+                    let mut keyed: Vec<_> = split.keyed.iter().collect();
+                    keyed.sort_by_key(|(key, _)| *key);
+                    for (key, target) in keyed {
+                        let port = scope.output_port(output_ref(op_name).keyed(key));
+                        outputs.push((
+                            port,
+                            format!("{var}_keyed_{}", rust_identifier(key)),
+                            format!("specific_chain({:?}.to_owned(), {chain})", key.as_ref()),
+                            target,
+                        ));
+                    }
+
+                    if let Some(target) = &split.remaining {
+                        let port = scope.output_port(output_ref(op_name).remaining());
+                        outputs.push((
+                            port,
+                            format!("{var}_remaining"),
+                            format!("remaining_chain({chain})"),
+                            target,
+                        ));
+                    }
+
+                    for (port, output_var, method, target) in outputs {
+                        let output_type = self.port_type(port)?;
+                        let _ = writeln!(
+                            operations,
+                            "    let {output_var}: ::crossflow::Output<{output_type}> =\n        \
+                            {var}_split.{method}?;"
+                        );
+                        scope.connect(&mut connections, output_var, false, target)?;
+                    }
+                }
+                DiagramOperation::Scope(schema) => {
+                    if !schema.stream_out.is_empty() {
+                        return Err(RustCodegenError::UnsupportedSetting {
+                            operation: op_name.clone(),
+                            setting: "stream_out",
+                        });
+                    }
+
+                    uses_registry = true;
+                    let request = self.port_type(scope.input_port(op_name))?;
+                    let response = self.port_type(
+                        PortRef::from(OperationRef::terminate_for(op_name))
+                            .in_namespaces(&scope.namespaces),
+                    )?;
+
+                    let function = format!("{}_{}", scope.function, rust_identifier(op_name));
+                    let mut namespaces = scope.namespaces.clone();
+                    namespaces.push(Arc::clone(op_name));
+                    let mut child = ScopeGenerator::new(
+                        &schema.ops,
+                        namespaces,
+                        schema.on_implicit_error(),
+                        function.clone(),
+                    );
+
+                    // Reserve a place for the function of this scope so it gets
+                    // written out before the functions of its own scopes.
+                    let index = self.scope_functions.len();
+                    self.scope_functions.push(String::new());
+                    self.scope_functions[index] = self.generate_function(
+                        &mut child,
+                        &schema.start,
+                        &request,
+                        &response,
+                        &format!(
+                            "/// Build the operations of the [{op_name}] scope inside of `scope`.\n"
+                        ),
+                    )?;
+
+                    let settings = if schema.settings.is_uninterruptible() {
+                        "::crossflow::ScopeSettings::uninterruptible()"
+                    } else {
+                        "::crossflow::ScopeSettings::default()"
+                    };
+                    let _ = writeln!(
+                        operations,
+                        "    let mut {var}_result = ::std::result::Result::Ok(());\n    \
+                        let {var} = builder.create_io_scope(|scope, builder| {{\n        \
+                            {var}_result = {function}(scope, builder, registry);\n        \
+                            {settings}\n    \
+                        }});\n    \
+                        {var}_result?;\n    \
+                        let {var}_input: ::crossflow::InputSlot<{request}> = {var}.input;\n    \
+                        let {var}_output: ::crossflow::Output<{response}> = {var}.output;"
+                    );
+                    scope.connect(
+                        &mut connections,
+                        format!("{var}_output"),
+                        false,
+                        &schema.next,
+                    )?;
+                }
+                other => {
+                    return Err(RustCodegenError::UnsupportedOperation {
+                        operation: op_name.clone(),
+                        kind: other.to_string(),
+                    });
+                }
+            }
+            operations.push('\n');
+        }
+
+        let mut start_connection = String::new();
+        scope.connect(
+            &mut start_connection,
+            "scope.start".to_owned(),
+            false,
+            start,
+        )?;
+
+        let registry = if uses_registry {
+            "registry"
+        } else {
+            "_registry"
+        };
+        let mut code = String::new();
+        let _ = writeln!(
+            code,
+            "{header}fn {}(\n    \
+                scope: ::crossflow::Scope<{request}, {response}, ()>,\n    \
+                builder: &mut ::crossflow::Builder,\n    \
+                {registry}: &::crossflow::DiagramElementRegistry,\n\
+            ) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {{",
+            scope.function,
+        );
+        code.push_str(&operations);
+        code.push_str(&start_connection);
+        code.push_str(&connections);
+        let _ = writeln!(code, "    ::std::result::Result::Ok(())\n}}");
+
+        Ok(code)
+    }
+
+    fn port_type(&self, port: PortRef) -> Result<String, RustCodegenError> {
+        let index = self
+            .message_types
+            .get(&port)
+            .copied()
+            .ok_or(RustCodegenError::UnknownMessageType(port))?;
+        self.type_path(index)
+    }
+
+    fn type_path(&self, index: usize) -> Result<String, RustCodegenError> {
+        if self.lookup.json_message_index().ok() == Some(index) {
+            return Ok("::crossflow::JsonMessage".to_owned());
+        }
+
+        let type_name = self.lookup.message_type_name(index)?;
+        Ok(rust_type_path(
+            type_name,
+            self.options.crate_name.as_deref(),
+        ))
+    }
+}
+
+/// The state of generating the function for one scope of the diagram. The
+/// root of the diagram counts as a scope.
+struct ScopeGenerator<'a> {
+    ops: &'a Operations,
+    /// Namespaces of the operations inside this scope
+    namespaces: Vec<Arc<str>>,
+    on_implicit_error: NextOperation,
+    /// Name of the function that builds this scope
+    function: String,
+    /// Variable names for each operation
+    variables: HashMap<OperationName, String>,
+    /// Counts temporary variables so each one gets a unique name
+    temporaries: usize,
+}
+
+impl<'a> ScopeGenerator<'a> {
+    fn new(
+        ops: &'a Operations,
+        namespaces: Vec<Arc<str>>,
+        on_implicit_error: NextOperation,
+        function: String,
+    ) -> Self {
+        let mut used = HashSet::new();
+        let mut names: Vec<_> = ops.keys().collect();
+        names.sort();
+        let variables = names
+            .into_iter()
+            .map(|name| {
+                let base = format!("op_{}", rust_identifier(name));
+                let mut variable = base.clone();
+                let mut suffix = 1;
+                while !used.insert(variable.clone()) {
+                    suffix += 1;
+                    variable = format!("{base}_{suffix}");
+                }
+                (name.clone(), variable)
+            })
+            .collect();
+
+        Self {
+            ops,
+            namespaces,
+            on_implicit_error,
+            function,
+            variables,
+            temporaries: 0,
+        }
+    }
+
+    fn input_port(&self, operation: &OperationName) -> PortRef {
+        PortRef::from(OperationRef::from(operation)).in_namespaces(&self.namespaces)
+    }
+
+    fn output_port(&self, output: NamedOutputRef) -> PortRef {
+        PortRef::from(output).in_namespaces(&self.namespaces)
+    }
+
+    /// Write the code that puts the buffers of `selection` into a
+    /// [`BufferMap`][crate::BufferMap] named `{var}_buffers`.
+    fn buffer_map(
+        &self,
+        code: &mut String,
+        var: &str,
+        selection: &BufferSelection,
+        clone: &[IdentifierRef<'static>],
+    ) -> Result<(), RustCodegenError> {
+        let mut members: Vec<_> = selection.iter().collect();
+        members.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let _ = writeln!(
+            code,
+            "    let mut {var}_buffers = ::crossflow::BufferMap::new();"
+        );
+        for (member, buffer) in members {
+            let buffer = match buffer {
+                NextOperation::Name(name) => match self.ops.get_op(name)?.as_ref() {
+                    DiagramOperation::Buffer(_) => &self.variables[name],
+                    other => {
+                        return Err(RustCodegenError::UnsupportedOperation {
+                            operation: name.clone(),
+                            kind: other.to_string(),
+                        });
+                    }
+                },
+                other => {
+                    return Err(DiagramErrorCode::UnknownOperation(other.into()).into());
+                }
+            };
+
+            let key = match &member {
+                IdentifierRef::Name(name) => {
+                    format!("::crossflow::IdentifierRef::name_str({:?})", name.as_ref())
+                }
+                IdentifierRef::Index(index) => {
+                    format!("::crossflow::IdentifierRef::Index({index})")
+                }
+            };
+            let value = if clone.contains(&member.to_owned()) {
+                format!("{buffer}.join_by_cloning().into()")
+            } else {
+                format!("{buffer}.into()")
+            };
+            let _ = writeln!(code, "    {var}_buffers.insert({key}, {value});");
+        }
+
+        Ok(())
+    }
+
+    /// Write the code that connects `output` to `target`. If `output` needs
+    /// to borrow the builder, it will be stored in a temporary variable first.
+    fn connect(
+        &mut self,
+        code: &mut String,
+        mut output: String,
+        output_uses_builder: bool,
+        target: &NextOperation,
+    ) -> Result<(), RustCodegenError> {
+        let input = match target {
+            NextOperation::Name(name) => {
+                let op = self.ops.get_op(name)?;
+                match op.as_ref() {
+                    DiagramOperation::Node(_)
+                    | DiagramOperation::ForkClone(_)
+                    | DiagramOperation::ForkResult(_)
+                    | DiagramOperation::Transform(_)
+                    | DiagramOperation::Unzip(_)
+                    | DiagramOperation::Split(_)
+                    | DiagramOperation::Scope(_) => format!("{}_input", self.variables[name]),
+                    DiagramOperation::Buffer(_) => {
+                        format!("{}.input_slot()", self.variables[name])
+                    }
+                    other => {
+                        return Err(RustCodegenError::UnsupportedOperation {
+                            operation: name.clone(),
+                            kind: other.to_string(),
+                        });
+                    }
+                }
+            }
+            NextOperation::Builtin { builtin } => match builtin {
+                BuiltinTarget::Terminate => "scope.terminate".to_owned(),
+                // Outputs that are not connected to anything get disposed.
+                BuiltinTarget::Dispose => {
+                    if !output_uses_builder {
+                        let _ = writeln!(code, "    let _ = {output};");
+                    }
+                    return Ok(());
+                }
+                BuiltinTarget::Cancel | BuiltinTarget::ImplicitCancel => {
+                    let create = if *builtin == BuiltinTarget::Cancel {
+                        "create_cancel"
+                    } else {
+                        "create_implicit_cancel"
+                    };
+                    self.temporaries += 1;
+                    let cancel = format!("cancel_{}", self.temporaries);
+                    let _ = writeln!(code, "    let {cancel} = builder.{create}();");
+                    cancel
+                }
+            },
+            NextOperation::Namespace(namespaced) => {
+                return Err(DiagramErrorCode::UnknownOperation(namespaced.into()).into());
+            }
+        };
+
+        if output_uses_builder {
+            self.temporaries += 1;
+            let temporary = format!("output_{}", self.temporaries);
+            let _ = writeln!(code, "    let {temporary} = {output};");
+            output = temporary;
+        }
+
+        let _ = writeln!(code, "    builder.connect({output}, {input});");
+        Ok(())
+    }
+}
+
+/// Turn a name from [`std::any::type_name`] into a path that can be used in
+/// code outside of the crate that defines the type.
+fn rust_type_path(type_name: &str, crate_name: Option<&str>) -> String {
+    let mut path = String::new();
+    let mut segment_start = true;
+    let mut rest = type_name;
+    while let Some(c) = rest.chars().next() {
+        if segment_start && (c.is_alphabetic() || c == '_') {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+                .unwrap_or(rest.len());
+            path.push_str(&rust_item_path(&rest[..end], crate_name));
+            rest = &rest[end..];
+            segment_start = false;
+            continue;
+        }
+
+        segment_start = !(c.is_alphanumeric() || c == '_');
+        path.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    path
+}
+
+fn rust_item_path(item: &str, crate_name: Option<&str>) -> String {
+    let Some((root, path)) = item.split_once("::") else {
+        // Primitive types and lifetimes
+        return item.to_owned();
+    };
+
+    if crate_name == Some(root) {
+        return format!("crate::{path}");
+    }
+
+    let root = match root {
+        "alloc" | "core" => "std",
+        root => root,
+    };
+
+    // These modules are private, but their types are re-exported publicly.
+    let path = path
+        .replace("collections::hash::map::", "collections::")
+        .replace("collections::hash::set::", "collections::")
+        .replace("collections::btree::map::", "collections::")
+        .replace("collections::btree::set::", "collections::");
+
+    format!("::{root}::{path}")
+}
+
+fn rust_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Accessor, Blocking, BufferAccessMut, BufferKey, DiagramElementRegistry, IntoCallback,
+        Joined, JsonMessage, NodeBuilderOptions, RequestExt, Service, diagram::testing::*,
+    };
+    use bevy_ecs::prelude::Commands;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    fn multiply_diagram() -> Diagram {
+        Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "multiply",
+            "ops": {
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply3",
+                    "next": "fork",
+                },
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["add", { "builtin": "dispose" }],
+                },
+                "add": {
+                    "type": "node",
+                    "builder": "add_to",
+                    "config": 1,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    mod generated_multiply {
+        include!("codegen/generated_multiply.rs");
+    }
+
+    #[test]
+    fn test_generated_code_matches_example() {
+        let fixture = DiagramTestFixture::new();
+        let code = multiply_diagram()
+            .generate_rust(
+                &fixture.registry,
+                &RustCodegenOptions::new().with_name("multiply"),
+            )
+            .unwrap();
+        assert_eq!(code, include_str!("codegen/generated_multiply.rs"));
+    }
+
+    #[test]
+    fn test_generated_code_runs() {
+        let mut fixture = DiagramTestFixture::new();
+        let workflow = fixture
+            .context
+            .command(|cmds| generated_multiply::spawn_multiply(cmds, &fixture.registry))
+            .unwrap();
+
+        let mut outcome = fixture
+            .context
+            .command(|cmds| cmds.request(2_i64, workflow).outcome());
+        fixture.context.run_with_conditions(&mut outcome, 1);
+        fixture.context.assert_no_errors();
+        assert_eq!(outcome.try_recv().unwrap().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_generate_fork_result() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "less_than",
+            "ops": {
+                "less_than": {
+                    "type": "node",
+                    "builder": "less_than",
+                    "config": 10,
+                    "next": "fork_result",
+                },
+                "fork_result": {
+                    "type": "fork_result",
+                    "ok": { "builtin": "terminate" },
+                    "err": { "builtin": "dispose" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let code = diagram
+            .generate_rust(&fixture.registry, &RustCodegenOptions::new())
+            .unwrap();
+        assert!(code.contains("pub fn build_diagram("));
+        assert!(code.contains(
+            "::crossflow::Scope<::crossflow::JsonMessage, ::crossflow::JsonMessage, ()>"
+        ));
+        assert!(code.contains("builder.create_fork_result();"));
+        assert!(code.contains("builder.connect(op_fork_result_output.ok, scope.terminate);"));
+        assert!(code.contains("let _ = op_fork_result_output.err;"));
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema, Joined)]
+    struct Pair {
+        a: i64,
+        b: i64,
+    }
+
+    #[derive(Accessor, Clone)]
+    struct Latest {
+        value: BufferKey<i64>,
+    }
+
+    fn pull_latest(input: Blocking<Latest>, mut access: BufferAccessMut<i64>) -> i64 {
+        access
+            .get_mut(input.id, &input.request.value)
+            .unwrap()
+            .pull()
+            .unwrap()
+    }
+
+    /// Create a new [`DiagramTestFixture`] with builders for the targets of
+    /// joins, listens and splits.
+    fn new_fixture() -> DiagramTestFixture {
+        let mut fixture = DiagramTestFixture::new();
+        fixture
+            .registry
+            .register_node_builder(NodeBuilderOptions::new("sum"), |builder, _config: ()| {
+                builder.create_map_block(|values: Vec<i64>| values.into_iter().sum::<i64>())
+            })
+            .with_join();
+        fixture
+            .registry
+            .opt_out()
+            .no_cloning()
+            .register_node_builder(
+                NodeBuilderOptions::new("add_pair"),
+                |builder, _config: ()| builder.create_map_block(|pair: Pair| pair.a + pair.b),
+            )
+            .with_join();
+        fixture
+            .registry
+            .opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder(
+                NodeBuilderOptions::new("pull_latest"),
+                |builder, _config: ()| builder.create_node(pull_latest.into_callback()),
+            )
+            .with_listen()
+            .with_common_response();
+        fixture
+            .registry
+            .register_node_builder(
+                NodeBuilderOptions::new("count_up"),
+                |builder, _config: ()| {
+                    builder.create_map_block(|start: i64| vec![start, start + 1, start + 2])
+                },
+            )
+            .with_split();
+        fixture
+    }
+
+    fn generate(diagram: serde_json::Value, name: &str) -> String {
+        let fixture = new_fixture();
+        Diagram::from_json(diagram)
+            .unwrap()
+            .generate_rust(
+                &fixture.registry,
+                &RustCodegenOptions::new()
+                    .with_name(name)
+                    .with_crate_name("crossflow"),
+            )
+            .unwrap()
+    }
+
+    fn run_generated<Request, Response>(
+        spawn: impl FnOnce(
+            &mut Commands,
+            &DiagramElementRegistry,
+        ) -> Result<Service<Request, Response>, DiagramErrorCode>,
+        request: Request,
+    ) -> Response
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+    {
+        let mut fixture = new_fixture();
+        let workflow = fixture
+            .context
+            .command(|cmds| spawn(cmds, &fixture.registry))
+            .unwrap();
+
+        let mut outcome = fixture
+            .context
+            .command(|cmds| cmds.request(request, workflow).outcome());
+        fixture.context.run_with_conditions(&mut outcome, 1);
+        fixture.context.assert_no_errors();
+        outcome.try_recv().unwrap().unwrap()
+    }
+
+    mod generated_buffer {
+        include!("codegen/generated_buffer.rs");
+    }
+
+    #[test]
+    fn test_generate_buffer() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "fork",
+                "ops": {
+                    "fork": {
+                        "type": "fork_clone",
+                        "next": ["multiply", "second"],
+                    },
+                    "multiply": {
+                        "type": "node",
+                        "builder": "multiply3",
+                        "next": "first",
+                    },
+                    "first": {
+                        "type": "buffer",
+                        "settings": { "retention": "keep_all" },
+                    },
+                    "second": { "type": "buffer" },
+                    "join": {
+                        "type": "join",
+                        "buffers": ["first", "second"],
+                        "next": "sum",
+                    },
+                    "sum": {
+                        "type": "node",
+                        "builder": "sum",
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }),
+            "buffer",
+        );
+        assert_eq!(code, include_str!("codegen/generated_buffer.rs"));
+        assert_eq!(run_generated(generated_buffer::spawn_buffer, 2), 8);
+    }
+
+    mod generated_join {
+        include!("codegen/generated_join.rs");
+    }
+
+    #[test]
+    fn test_generate_join() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "multiply",
+                "ops": {
+                    "multiply": {
+                        "type": "node",
+                        "builder": "multiply3",
+                        "next": "fork",
+                    },
+                    "fork": {
+                        "type": "fork_clone",
+                        "next": ["a", "b"],
+                    },
+                    "a": { "type": "buffer" },
+                    "b": { "type": "buffer" },
+                    "join": {
+                        "type": "join",
+                        "buffers": { "a": "a", "b": "b" },
+                        "clone": ["b"],
+                        "next": "add_pair",
+                    },
+                    "add_pair": {
+                        "type": "node",
+                        "builder": "add_pair",
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }),
+            "join",
+        );
+        assert_eq!(code, include_str!("codegen/generated_join.rs"));
+        assert_eq!(run_generated(generated_join::spawn_join, 2), 12);
+    }
+
+    mod generated_listen {
+        include!("codegen/generated_listen.rs");
+    }
+
+    #[test]
+    fn test_generate_listen() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "multiply",
+                "ops": {
+                    "multiply": {
+                        "type": "node",
+                        "builder": "multiply3",
+                        "next": "buffer",
+                    },
+                    "buffer": { "type": "buffer" },
+                    "listen": {
+                        "type": "listen",
+                        "buffers": { "value": "buffer" },
+                        "next": "pull",
+                    },
+                    "pull": {
+                        "type": "node",
+                        "builder": "pull_latest",
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }),
+            "listen",
+        );
+        assert_eq!(code, include_str!("codegen/generated_listen.rs"));
+        assert_eq!(run_generated(generated_listen::spawn_listen, 2), 6);
+    }
+
+    mod generated_transform {
+        include!("codegen/generated_transform.rs");
+    }
+
+    #[test]
+    fn test_generate_transform() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "transform",
+                "ops": {
+                    "transform": {
+                        "type": "transform",
+                        "cel": "int(request) * 2",
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }),
+            "transform",
+        );
+        assert_eq!(code, include_str!("codegen/generated_transform.rs"));
+        let result = run_generated(generated_transform::spawn_transform, JsonMessage::from(3));
+        assert_eq!(result, 6);
+    }
+
+    mod generated_unzip {
+        include!("codegen/generated_unzip.rs");
+    }
+
+    #[test]
+    fn test_generate_unzip() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "multiply",
+                "ops": {
+                    "multiply": {
+                        "type": "node",
+                        "builder": "multiply3_5",
+                        "next": "unzip",
+                    },
+                    "unzip": {
+                        "type": "unzip",
+                        "next": [{ "builtin": "dispose" }, { "builtin": "terminate" }],
+                    },
+                },
+            }),
+            "unzip",
+        );
+        assert_eq!(code, include_str!("codegen/generated_unzip.rs"));
+        assert_eq!(run_generated(generated_unzip::spawn_unzip, 2), 10);
+    }
+
+    mod generated_split {
+        include!("codegen/generated_split.rs");
+    }
+
+    #[test]
+    fn test_generate_split() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "count_up",
+                "ops": {
+                    "count_up": {
+                        "type": "node",
+                        "builder": "count_up",
+                        "next": "split",
+                    },
+                    "split": {
+                        "type": "split",
+                        "sequential": [{ "builtin": "dispose" }, { "builtin": "terminate" }],
+                        "remaining": { "builtin": "dispose" },
+                    },
+                },
+            }),
+            "split",
+        );
+        assert_eq!(code, include_str!("codegen/generated_split.rs"));
+        assert_eq!(run_generated(generated_split::spawn_split, 2), 3);
+    }
+
+    mod generated_scope {
+        include!("codegen/generated_scope.rs");
+    }
+
+    #[test]
+    fn test_generate_scope() {
+        let code = generate(
+            json!({
+                "version": "0.1.0",
+                "start": "scope",
+                "ops": {
+                    "scope": {
+                        "type": "scope",
+                        "start": "multiply",
+                        "ops": {
+                            "multiply": {
+                                "type": "node",
+                                "builder": "multiply3",
+                                "next": { "builtin": "terminate" },
+                            },
+                        },
+                        "next": "add",
+                    },
+                    "add": {
+                        "type": "node",
+                        "builder": "add_to",
+                        "config": 1,
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }),
+            "scope",
+        );
+        assert_eq!(code, include_str!("codegen/generated_scope.rs"));
+        assert_eq!(run_generated(generated_scope::spawn_scope, 2), 7);
+    }
+
+    #[test]
+    fn test_unsupported_operation() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "spread",
+            "ops": {
+                "spread": {
+                    "type": "spread",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result = diagram.generate_rust(
+            &fixture.registry,
+            &RustCodegenOptions::new()
+                .with_request("i64")
+                .with_response("i64"),
+        );
+        assert!(matches!(
+            result,
+            Err(RustCodegenError::UnsupportedOperation { kind, .. }) if kind == "spread"
+        ));
+    }
+
+    #[test]
+    fn test_rust_type_path() {
+        assert_eq!(rust_type_path("i64", None), "i64");
+        assert_eq!(
+            rust_type_path("alloc::vec::Vec<alloc::string::String>", None),
+            "::std::vec::Vec<::std::string::String>"
+        );
+        assert_eq!(
+            rust_type_path(
+                "core::result::Result<my_crate::Msg, (f64, &str)>",
+                Some("my_crate"),
+            ),
+            "::std::result::Result<crate::Msg, (f64, &str)>"
+        );
+        assert_eq!(
+            rust_type_path("std::collections::hash::map::HashMap<u8, u8>", None),
+            "::std::collections::HashMap<u8, u8>"
+        );
+    }
+}
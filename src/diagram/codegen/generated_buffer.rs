@@ -0,0 +1,71 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_buffer(
+    scope: ::crossflow::Scope<i64, i64, ()>,
+    builder: &mut ::crossflow::Builder,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // first
+    let op_first: ::crossflow::Buffer<i64> = builder.create_buffer(::crossflow::BufferSettings::keep_all());
+
+    // second
+    let op_second: ::crossflow::Buffer<i64> = builder.create_buffer(::crossflow::BufferSettings::default());
+
+    // fork
+    let (op_fork_input, op_fork_output) = builder.create_fork_clone();
+
+    // join
+    let mut op_join_buffers = ::crossflow::BufferMap::new();
+    op_join_buffers.insert(::crossflow::IdentifierRef::Index(0), op_first.into());
+    op_join_buffers.insert(::crossflow::IdentifierRef::Index(1), op_second.into());
+    let op_join_output: ::crossflow::Output<::std::vec::Vec<i64>> = builder.try_join(&op_join_buffers)?.output();
+
+    // multiply
+    let op_multiply = registry
+        .get_node_registration("multiply3")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_multiply_input: ::crossflow::InputSlot<i64> = op_multiply.input.into_input()?;
+    let op_multiply_output: ::crossflow::Output<i64> = op_multiply.output.into_output()?;
+
+    // sum
+    let op_sum = registry
+        .get_node_registration("sum")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_sum_input: ::crossflow::InputSlot<::std::vec::Vec<i64>> = op_sum.input.into_input()?;
+    let op_sum_output: ::crossflow::Output<i64> = op_sum.output.into_output()?;
+
+    builder.connect(scope.start, op_fork_input);
+    let output_1 = op_fork_output.clone_output(builder);
+    builder.connect(output_1, op_multiply_input);
+    let output_2 = op_fork_output.clone_output(builder);
+    builder.connect(output_2, op_second.input_slot());
+    builder.connect(op_join_output, op_sum_input);
+    builder.connect(op_multiply_output, op_first.input_slot());
+    builder.connect(op_sum_output, scope.terminate);
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_buffer(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<i64, i64, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_buffer(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
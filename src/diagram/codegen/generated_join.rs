@@ -0,0 +1,71 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_join(
+    scope: ::crossflow::Scope<i64, i64, ()>,
+    builder: &mut ::crossflow::Builder,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // a
+    let op_a: ::crossflow::Buffer<i64> = builder.create_buffer(::crossflow::BufferSettings::default());
+
+    // b
+    let op_b: ::crossflow::Buffer<i64> = builder.create_buffer(::crossflow::BufferSettings::default());
+
+    // add_pair
+    let op_add_pair = registry
+        .get_node_registration("add_pair")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_add_pair_input: ::crossflow::InputSlot<crate::diagram::codegen::tests::Pair> = op_add_pair.input.into_input()?;
+    let op_add_pair_output: ::crossflow::Output<i64> = op_add_pair.output.into_output()?;
+
+    // fork
+    let (op_fork_input, op_fork_output) = builder.create_fork_clone();
+
+    // join
+    let mut op_join_buffers = ::crossflow::BufferMap::new();
+    op_join_buffers.insert(::crossflow::IdentifierRef::name_str("a"), op_a.into());
+    op_join_buffers.insert(::crossflow::IdentifierRef::name_str("b"), op_b.join_by_cloning().into());
+    let op_join_output: ::crossflow::Output<crate::diagram::codegen::tests::Pair> = builder.try_join(&op_join_buffers)?.output();
+
+    // multiply
+    let op_multiply = registry
+        .get_node_registration("multiply3")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_multiply_input: ::crossflow::InputSlot<i64> = op_multiply.input.into_input()?;
+    let op_multiply_output: ::crossflow::Output<i64> = op_multiply.output.into_output()?;
+
+    builder.connect(scope.start, op_multiply_input);
+    builder.connect(op_add_pair_output, scope.terminate);
+    let output_1 = op_fork_output.clone_output(builder);
+    builder.connect(output_1, op_a.input_slot());
+    let output_2 = op_fork_output.clone_output(builder);
+    builder.connect(output_2, op_b.input_slot());
+    builder.connect(op_join_output, op_add_pair_input);
+    builder.connect(op_multiply_output, op_fork_input);
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_join(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<i64, i64, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_join(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
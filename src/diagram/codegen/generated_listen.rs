@@ -0,0 +1,60 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_listen(
+    scope: ::crossflow::Scope<i64, i64, ()>,
+    builder: &mut ::crossflow::Builder,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // buffer
+    let op_buffer: ::crossflow::Buffer<i64> = builder.create_buffer(::crossflow::BufferSettings::default());
+
+    // listen
+    let mut op_listen_buffers = ::crossflow::BufferMap::new();
+    op_listen_buffers.insert(::crossflow::IdentifierRef::name_str("value"), op_buffer.into());
+    let op_listen_output: ::crossflow::Output<crate::diagram::codegen::tests::Latest> = builder.try_listen(&op_listen_buffers)?.output();
+
+    // multiply
+    let op_multiply = registry
+        .get_node_registration("multiply3")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_multiply_input: ::crossflow::InputSlot<i64> = op_multiply.input.into_input()?;
+    let op_multiply_output: ::crossflow::Output<i64> = op_multiply.output.into_output()?;
+
+    // pull
+    let op_pull = registry
+        .get_node_registration("pull_latest")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_pull_input: ::crossflow::InputSlot<crate::diagram::codegen::tests::Latest> = op_pull.input.into_input()?;
+    let op_pull_output: ::crossflow::Output<i64> = op_pull.output.into_output()?;
+
+    builder.connect(scope.start, op_multiply_input);
+    builder.connect(op_listen_output, op_pull_input);
+    builder.connect(op_multiply_output, op_buffer.input_slot());
+    builder.connect(op_pull_output, scope.terminate);
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_listen(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<i64, i64, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_listen(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
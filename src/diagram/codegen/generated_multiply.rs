@@ -0,0 +1,61 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_multiply(
+    scope: ::crossflow::Scope<i64, i64, ()>,
+    builder: &mut ::crossflow::Builder,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // add
+    let config = "1"
+        .parse::<::crossflow::JsonMessage>()
+        .map_err(|err| {
+            ::crossflow::DiagramErrorCode::ConfigError(::std::sync::Arc::new(err))
+        })?;
+    let op_add = registry
+        .get_node_registration("add_to")?
+        .create_node(builder, config)?;
+    let op_add_input: ::crossflow::InputSlot<i64> = op_add.input.into_input()?;
+    let op_add_output: ::crossflow::Output<i64> = op_add.output.into_output()?;
+
+    // fork
+    let (op_fork_input, op_fork_output) = builder.create_fork_clone();
+
+    // multiply
+    let op_multiply = registry
+        .get_node_registration("multiply3")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_multiply_input: ::crossflow::InputSlot<i64> = op_multiply.input.into_input()?;
+    let op_multiply_output: ::crossflow::Output<i64> = op_multiply.output.into_output()?;
+
+    builder.connect(scope.start, op_multiply_input);
+    builder.connect(op_add_output, scope.terminate);
+    let output_1 = op_fork_output.clone_output(builder);
+    builder.connect(output_1, op_add_input);
+    builder.connect(op_multiply_output, op_fork_input);
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_multiply(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<i64, i64, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_multiply(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
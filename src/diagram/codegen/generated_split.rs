@@ -0,0 +1,56 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_split(
+    scope: ::crossflow::Scope<i64, i64, ()>,
+    builder: &mut ::crossflow::Builder,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // count_up
+    let op_count_up = registry
+        .get_node_registration("count_up")?
+        .create_node(builder, ::crossflow::JsonMessage::Null)?;
+    let op_count_up_input: ::crossflow::InputSlot<i64> = op_count_up.input.into_input()?;
+    let op_count_up_output: ::crossflow::Output<::std::vec::Vec<i64>> = op_count_up.output.into_output()?;
+
+    // split
+    let (op_split_input, op_split_split) = builder.create_split::<::std::vec::Vec<i64>>();
+    let mut op_split_split = op_split_split.build(builder);
+    let op_split_next_0: ::crossflow::Output<i64> =
+        op_split_split.sequential_chain(0, |chain| chain.map_block(|(_, value)| value).output())?;
+    let op_split_next_1: ::crossflow::Output<i64> =
+        op_split_split.sequential_chain(1, |chain| chain.map_block(|(_, value)| value).output())?;
+    let op_split_remaining: ::crossflow::Output<i64> =
+        op_split_split.remaining_chain(|chain| chain.map_block(|(_, value)| value).output())?;
+
+    builder.connect(scope.start, op_count_up_input);
+    builder.connect(op_count_up_output, op_split_input);
+    let _ = op_split_next_0;
+    builder.connect(op_split_next_1, scope.terminate);
+    let _ = op_split_remaining;
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_split(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<i64, i64, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_split(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
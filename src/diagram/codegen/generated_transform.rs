@@ -0,0 +1,44 @@
+// This file was generated by crossflow from a diagram. Do not edit it by hand.
+
+/// Build the workflow of the diagram inside of `scope`.
+pub fn build_transform(
+    scope: ::crossflow::Scope<::crossflow::JsonMessage, ::crossflow::JsonMessage, ()>,
+    builder: &mut ::crossflow::Builder,
+    _registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<(), ::crossflow::DiagramErrorCode> {
+    // transform
+    let op_transform = ::crossflow::create_cel_transform(builder, "int(request) * 2")?;
+    let (op_transform_fork_input, op_transform_output) = builder.create_fork_result();
+    builder.connect(op_transform.output, op_transform_fork_input);
+    let op_transform_input = op_transform.input;
+
+    builder.connect(scope.start, op_transform_input);
+    builder.connect(op_transform_output.ok, scope.terminate);
+    let cancel_1 = builder.create_implicit_cancel();
+    builder.connect(op_transform_output.err, cancel_1);
+    ::std::result::Result::Ok(())
+}
+
+/// Spawn the workflow of the diagram as a service.
+pub fn spawn_transform(
+    commands: &mut ::crossflow::bevy_ecs::prelude::Commands,
+    registry: &::crossflow::DiagramElementRegistry,
+) -> ::std::result::Result<
+    ::crossflow::Service<::crossflow::JsonMessage, ::crossflow::JsonMessage, ()>,
+    ::crossflow::DiagramErrorCode,
+> {
+    use ::crossflow::SpawnWorkflowExt;
+
+    let mut result = ::std::result::Result::Ok(());
+    let workflow = commands.spawn_workflow(|scope, builder| {
+        result = build_transform(scope, builder, registry);
+    });
+
+    if let ::std::result::Result::Err(err) = result {
+        // Despawn the workflow because it was not built successfully.
+        commands.entity(workflow.provider()).despawn();
+        return ::std::result::Result::Err(err);
+    }
+
+    ::std::result::Result::Ok(workflow)
+}
@@ -0,0 +1,314 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+use crate::{Builder, is_default};
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, DynNode,
+    InferenceContext, MessageRegistry, NextOperation, OperationName, Operations, RegisterClone,
+    SerializeMessage, Templates, TraceInfo, TraceSettings, supported::*,
+};
+
+/// Collect incoming messages into a list. Each message that arrives at this
+/// operation is added to the list, and the list is sent to `next` once it is
+/// ready.
+///
+/// * `min` - The collection will not be sent out until it has at least this
+///   many elements. If the minimum can never be reached because there are no
+///   more workflow threads that can reach this operation, the workflow will be
+///   cancelled. The default is 0, which means an empty list will be sent out
+///   if no messages arrive.
+/// * `max` - The collection will be sent out as soon as it reaches this many
+///   elements, and a new collection will be started. If unspecified, there is
+///   no maximum.
+///
+/// Whenever `min` is satisfied and no workflow threads can reach this
+/// operation anymore, the collection will be sent out with however many
+/// elements it has.
+///
+/// # Examples
+///
+/// Run each robot's inspection in parallel and gather all the reports into
+/// one list once every inspection has finished.
+///
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "fork",
+///     "ops": {
+///         "fork": {
+///             "type": "fork_clone",
+///             "next": ["inspect_a", "inspect_b", "inspect_c"]
+///         },
+///         "inspect_a": {
+///             "type": "node",
+///             "builder": "inspect",
+///             "config": "robot_a",
+///             "next": "reports"
+///         },
+///         "inspect_b": {
+///             "type": "node",
+///             "builder": "inspect",
+///             "config": "robot_b",
+///             "next": "reports"
+///         },
+///         "inspect_c": {
+///             "type": "node",
+///             "builder": "inspect",
+///             "config": "robot_c",
+///             "next": "reports"
+///         },
+///         "reports": {
+///             "type": "collect",
+///             "min": 1,
+///             "next": { "builtin": "terminate" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CollectSchema {
+    pub next: NextOperation,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub min: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl CollectSchema {
+    fn validate_limits(&self) -> Result<(), DiagramErrorCode> {
+        if let Some(max) = self.max
+            && (max == 0 || self.min > max)
+        {
+            return Err(DiagramErrorCode::InvalidCollectLimits { min: self.min, max });
+        }
+
+        Ok(())
+    }
+}
+
+impl BuildDiagramOperation for CollectSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        self.validate_limits()?;
+        let inferred_type = ctx.inferred_message_type(id)?;
+
+        let collect = ctx
+            .registry
+            .messages
+            .collect(&inferred_type, self, ctx.builder)?;
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, collect.input, trace)?;
+        ctx.add_output_into_target(&self.next, collect.output);
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.collect(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+pub type CollectFn = fn(&CollectSchema, &mut Builder) -> DynNode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CollectRegistration {
+    pub create: CollectFn,
+    pub output_type: usize,
+}
+
+pub trait RegisterCollect {
+    fn create_collect(collect_op: &CollectSchema, builder: &mut Builder) -> DynNode;
+
+    fn register_collect(messages: &mut MessageRegistry);
+}
+
+impl<T, Serializer, Cloneable> RegisterCollect for Supported<(T, Serializer, Cloneable)>
+where
+    T: Send + Sync + 'static,
+    Serializer: SerializeMessage<Vec<T>>,
+    Cloneable: RegisterClone<Vec<T>>,
+{
+    fn create_collect(collect_op: &CollectSchema, builder: &mut Builder) -> DynNode {
+        let collect = builder.create_collect::<T, 16>(collect_op.min, collect_op.max);
+        let output = builder
+            .chain(collect.output)
+            .map_block(SmallVec::into_vec)
+            .output();
+
+        DynNode {
+            input: collect.input.into(),
+            output: output.into(),
+            streams: Default::default(),
+        }
+    }
+
+    fn register_collect(messages: &mut MessageRegistry) {
+        let item_type = messages.registration.get_index_or_insert::<T>();
+        let output_type = messages.registration.get_index_or_insert::<Vec<T>>();
+
+        let ops = &mut messages.registration.get_or_insert_operations::<T>();
+        ops.collect = Some(CollectRegistration {
+            create: Self::create_collect,
+            output_type,
+        });
+
+        messages.register_serialize::<Vec<T>, Serializer>();
+        messages.register_clone::<Vec<T>, Cloneable>();
+
+        messages
+            .registration
+            .reverse_lookup
+            .collect
+            .insert(output_type, item_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_log::test;
+
+    use crate::{Diagram, JsonMessage, diagram::testing::DiagramTestFixture};
+
+    use super::*;
+
+    #[test]
+    fn test_collect_fork_clone_branches() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fork",
+            "ops": {
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["op1", "op2", "op3"],
+                },
+                "op1": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 1,
+                    "next": "collect",
+                },
+                "op2": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 2,
+                    "next": "collect",
+                },
+                "op3": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 3,
+                    "next": "collect",
+                },
+                "collect": {
+                    "type": "collect",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, JsonMessage::from(4))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        let mut result: Vec<i64> = serde_json::from_value(result).unwrap();
+        result.sort();
+        assert_eq!(result, [4, 8, 12]);
+    }
+
+    #[test]
+    fn test_collect_max() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "split",
+            "ops": {
+                "split": {
+                    "type": "split",
+                    "remaining": "collect",
+                },
+                "collect": {
+                    "type": "collect",
+                    "max": 2,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, json!([1, 2, 3, 4]))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!([1, 2]));
+    }
+
+    #[test]
+    fn test_collect_invalid_limits() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "collect",
+            "ops": {
+                "collect": {
+                    "type": "collect",
+                    "min": 3,
+                    "max": 2,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(
+                err.code,
+                DiagramErrorCode::InvalidCollectLimits { min: 3, max: 2 }
+            ),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, InferenceContext,
+    NextOperation, OperationName, Operations, Templates, TraceInfo, TraceSettings,
+};
+
+/// Wait for a period of time, then pass the incoming message along to `next`
+/// unchanged.
+///
+/// * `duration` - How long to wait, in seconds.
+///
+/// The delay is measured with the `Time` resource of the executor, so it
+/// follows the virtual clock of the app. Pausing or scaling the virtual clock,
+/// e.g. during simulation or testing, will affect the delay accordingly.
+///
+/// # Examples
+///
+/// Give a door time to finish opening before moving through it.
+///
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "open_door",
+///     "ops": {
+///         "open_door": {
+///             "type": "node",
+///             "builder": "open_door",
+///             "next": "wait"
+///         },
+///         "wait": {
+///             "type": "delay",
+///             "duration": 2.5,
+///             "next": "move_through"
+///         },
+///         "move_through": {
+///             "type": "node",
+///             "builder": "move_through",
+///             "next": { "builtin": "terminate" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DelaySchema {
+    /// How long to wait, in seconds.
+    pub duration: f64,
+    pub next: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl BuildDiagramOperation for DelaySchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        let duration = duration_from_secs(self.duration)?;
+        let inferred_type = ctx.inferred_message_type(id)?;
+        let delay = ctx
+            .registry
+            .messages
+            .delay(&inferred_type, duration, ctx.builder)?;
+
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, delay.input, trace)?;
+        ctx.add_output_into_target(&self.next, delay.output);
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.pass_through(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+/// Convert a number of seconds from a diagram into a [`Duration`].
+pub(super) fn duration_from_secs(seconds: f64) -> Result<Duration, DiagramErrorCode> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| DiagramErrorCode::InvalidDuration(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_time::TimeUpdateStrategy;
+    use serde_json::json;
+    use std::time::Duration;
+    use test_log::test;
+
+    use crate::{Diagram, JsonMessage, diagram::testing::DiagramTestFixture};
+
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        let mut fixture = DiagramTestFixture::new();
+        fixture
+            .context
+            .app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "delay",
+            "ops": {
+                "delay": {
+                    "type": "delay",
+                    "duration": 5.0,
+                    "next": "multiply",
+                },
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 3,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        // Each update advances the virtual clock by a fixed amount, regardless
+        // of how long it takes on the wall clock.
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, JsonMessage::from(4), 100)
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, 12);
+    }
+
+    #[test]
+    fn test_delay_invalid_duration() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "delay",
+            "ops": {
+                "delay": {
+                    "type": "delay",
+                    "duration": -1.0,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(err.code, DiagramErrorCode::InvalidDuration(_)),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -36,7 +36,8 @@ use super::{
 ///   finish "wins" the race and gets to the be output
 /// * join - connect each branch into a buffer and then use the `join`
 ///   operation to reunite them
-/// * collect - TODO(@mxgrey): [add the collect operation](https://github.com/open-rmf/crossflow/issues/59)
+/// * collect - connect all branches to a `collect` operation to gather their
+///   results into a single list
 ///
 /// # Examples
 /// ```
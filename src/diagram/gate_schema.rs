@@ -0,0 +1,288 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Gate;
+
+use super::{
+    BufferSelection, BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode,
+    InferenceContext, NextOperation, OperationName, Operations, Templates, TraceInfo,
+    TraceSettings,
+};
+
+/// Open the gates of one or more buffers, then pass the incoming message
+/// along to `next` unchanged.
+///
+/// Listeners of a buffer, including `join` and `listen` operations, will
+/// receive a wakeup as soon as its gate opens, even if the data inside the
+/// buffer has not changed.
+///
+/// # Examples
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "dock",
+///     "ops": {
+///         "dock": {
+///             "type": "node",
+///             "builder": "dock",
+///             "next": "resume_tasks"
+///         },
+///         "resume_tasks": {
+///             "type": "gate_open",
+///             "buffers": ["task_queue"],
+///             "next": { "builtin": "terminate" }
+///         },
+///         "task_queue": {
+///             "type": "buffer"
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GateOpenSchema {
+    /// The buffers whose gates will be opened.
+    pub buffers: BufferSelection,
+    pub next: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl BuildDiagramOperation for GateOpenSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        build_gate(
+            Gate::Open,
+            &self.buffers,
+            &self.next,
+            TraceInfo::new(self, self.trace_settings.trace)?,
+            id,
+            ctx,
+        )
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.pass_through(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+/// Close the gates of one or more buffers, then pass the incoming message
+/// along to `next` unchanged.
+///
+/// While a buffer's gate is closed, its listeners, including `join` and
+/// `listen` operations, will not be woken up when the data in the buffer
+/// changes. Data will build up in the buffer according to its settings until
+/// the gate is opened again.
+///
+/// # Examples
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "suspend_tasks",
+///     "ops": {
+///         "suspend_tasks": {
+///             "type": "gate_close",
+///             "buffers": ["task_queue"],
+///             "next": "dock"
+///         },
+///         "dock": {
+///             "type": "node",
+///             "builder": "dock",
+///             "next": { "builtin": "terminate" }
+///         },
+///         "task_queue": {
+///             "type": "buffer"
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GateCloseSchema {
+    /// The buffers whose gates will be closed.
+    pub buffers: BufferSelection,
+    pub next: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl BuildDiagramOperation for GateCloseSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        build_gate(
+            Gate::Closed,
+            &self.buffers,
+            &self.next,
+            TraceInfo::new(self, self.trace_settings.trace)?,
+            id,
+            ctx,
+        )
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.pass_through(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+fn build_gate(
+    action: Gate,
+    buffers: &BufferSelection,
+    next: &NextOperation,
+    trace: TraceInfo,
+    id: &OperationName,
+    ctx: &mut BuilderContext,
+) -> Result<BuildStatus, DiagramErrorCode> {
+    let buffer_map = match ctx.create_buffer_map(buffers) {
+        Ok(buffer_map) => buffer_map,
+        Err(reason) => return Ok(BuildStatus::defer(reason)),
+    };
+
+    let inferred_type = ctx.inferred_message_type(id)?;
+    let gate = ctx
+        .registry
+        .messages
+        .gate(&inferred_type, action, &buffer_map, ctx.builder)?;
+
+    ctx.set_input_for_target(id, gate.input, trace)?;
+    ctx.add_output_into_target(next, gate.output);
+    Ok(BuildStatus::Finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::time::Duration;
+    use test_log::test;
+
+    use crate::{Cancellation, Diagram, JsonMessage, diagram::testing::DiagramTestFixture};
+
+    #[test]
+    fn test_gate_close_blocks_join() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "close",
+            "ops": {
+                "close": {
+                    "type": "gate_close",
+                    "buffers": ["buffer"],
+                    "next": "buffer",
+                },
+                "buffer": {
+                    "type": "buffer",
+                },
+                "join": {
+                    "type": "join",
+                    "buffers": ["buffer"],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        // The gate is closed before the message arrives in the buffer, so the
+        // join never fires. Nothing can open the gate again, but the message
+        // held in the buffer keeps the workflow from being cancelled as
+        // unreachable, so the outcome stays pending.
+        let err = fixture
+            .spawn_and_run_with_conditions::<_, JsonMessage>(
+                &diagram,
+                JsonMessage::from(5),
+                Duration::from_millis(100),
+            )
+            .unwrap_err();
+        assert!(err.downcast_ref::<Cancellation>().is_none());
+        assert_eq!(err.to_string(), "Outcome has not resolved yet");
+    }
+
+    #[test]
+    fn test_gate_reopen() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "close",
+            "ops": {
+                "close": {
+                    "type": "gate_close",
+                    "buffers": ["buffer"],
+                    "next": "open",
+                },
+                "open": {
+                    "type": "gate_open",
+                    "buffers": ["buffer"],
+                    "next": "multiply",
+                },
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 3,
+                    "next": "buffer",
+                },
+                "buffer": {
+                    "type": "buffer",
+                },
+                "join": {
+                    "type": "join",
+                    "buffers": ["buffer"],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, JsonMessage::from(5))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!([15]));
+    }
+}
@@ -21,18 +21,23 @@ use crate::{Async, Identifier, NeverFinish};
 use bevy_derive::{Deref, DerefMut};
 
 use std::{
+    collections::HashMap,
     future::Future,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Mutex, PoisonError},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
 
 use http::uri::PathAndQuery;
 
-use futures::{FutureExt, Stream as FutureStream, stream::once};
+use futures::{
+    FutureExt, Stream as FutureStream,
+    future::{BoxFuture, Shared, ready},
+    stream::once,
+};
 use futures_lite::future::race;
 
 use prost::Message;
@@ -44,12 +49,15 @@ use tonic::{
     client::Grpc as Client,
     codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
     codegen::tokio_stream::wrappers::UnboundedReceiverStream,
+    metadata::{KeyAndValueRef, MetadataKey, MetadataMap, MetadataValue},
     transport::Channel,
 };
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use thiserror::Error as ThisError;
+
 use tokio::{
     runtime::Runtime,
     sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
@@ -58,6 +66,14 @@ use tokio::{
 
 use async_std::future::timeout as until_timeout;
 
+mod reflection;
+use reflection::fetch_descriptors;
+
+#[cfg(feature = "grpc_server")]
+mod server;
+#[cfg(feature = "grpc_server")]
+pub use server::*;
+
 #[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GrpcConfig {
     /// Name of the [service](https://grpc.io/docs/what-is-grpc/core-concepts/#service-definition)
@@ -71,12 +87,183 @@ pub struct GrpcConfig {
     /// cancelling it. This is optional. Leaving it unset will allow the client to
     /// wait indefinitely.
     pub timeout: Option<f64>,
+    /// Fetch the descriptions of the service from the server using the
+    /// [gRPC server reflection protocol](https://github.com/grpc/grpc/blob/master/doc/server-reflection.md)
+    /// instead of looking for them in the global descriptor pool. This allows
+    /// calling services whose .proto files were not compiled into the executor.
+    /// The descriptions are fetched when the node receives its first request,
+    /// sending the same `metadata` and using the same `timeout` as the
+    /// requests to the service.
+    #[serde(default)]
+    pub reflection: bool,
+    /// Metadata headers to send along with every request.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Input for the `grpc_dynamic_request` node. This describes which server and
+/// method to call along with the message to send, so these can be decided
+/// while the workflow is running.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GrpcDynamicRequest {
+    /// URI of where the service should be accessed.
+    pub uri: Arc<str>,
+    /// Name of the [service](https://grpc.io/docs/what-is-grpc/core-concepts/#service-definition)
+    /// that the client should call.
+    pub service: Arc<str>,
+    /// Name of the method within the chosen service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<Identifier>,
+    /// Metadata headers to send along with the request. These take precedence
+    /// over any metadata with the same key in the node configuration.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+    /// The message to send to the service.
+    pub body: JsonMessage,
+    /// A timeout (in seconds) for how long to wait for the service to finish
+    /// before cancelling it. If this is unset then the timeout in the node
+    /// configuration will be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+}
+
+/// Configuration for the `grpc_dynamic_request` node.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GrpcDynamicConfig {
+    /// A timeout (in seconds) for requests that do not specify their own.
+    /// Leaving this unset will allow those requests to wait indefinitely.
+    pub timeout: Option<f64>,
+    /// Metadata headers to send along with every request.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
 }
 
 #[derive(StreamPack)]
 pub struct GrpcStreams {
     out: JsonMessage,
     canceller: UnboundedSender<Option<String>>,
+    /// The metadata that the server sent at the start of its response.
+    headers: HashMap<String, String>,
+    /// The metadata that the server sent after its last message. This is only
+    /// streamed if the server sent any custom metadata in its trailers.
+    trailers: HashMap<String, String>,
+}
+
+/// The error produced by gRPC nodes. This carries the status code of the call
+/// so that a diagram can use a fork_result followed by a switch to decide how
+/// to handle different kinds of failure.
+///
+/// Errors that happen on the client side are given the code that a gRPC
+/// client would normally use for them, e.g. a failure to connect is
+/// [`GrpcStatusCode::Unavailable`] and running out of time is
+/// [`GrpcStatusCode::DeadlineExceeded`].
+#[derive(ThisError, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[error("{code}: {message}")]
+pub struct GrpcError {
+    /// The status code of the call.
+    pub code: GrpcStatusCode,
+    /// A description of the error.
+    pub message: String,
+    /// Metadata that the server sent along with the error status.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, String>,
+}
+
+impl GrpcError {
+    pub fn new(code: impl Into<GrpcStatusCode>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+            metadata: HashMap::new(),
+        }
+    }
+}
+
+impl From<Status> for GrpcError {
+    fn from(status: Status) -> Self {
+        Self {
+            code: status.code().into(),
+            message: status.message().to_owned(),
+            metadata: metadata_to_map(status.metadata()),
+        }
+    }
+}
+
+/// The [status codes](https://grpc.io/docs/guides/status-codes/) of gRPC. This
+/// mirrors [`tonic::Code`] in a form that can be serialized into diagram
+/// messages.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Hash, PartialEq, Eq, strum::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum GrpcStatusCode {
+    Ok,
+    Cancelled,
+    Unknown,
+    InvalidArgument,
+    DeadlineExceeded,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    ResourceExhausted,
+    FailedPrecondition,
+    Aborted,
+    OutOfRange,
+    Unimplemented,
+    Internal,
+    Unavailable,
+    DataLoss,
+    Unauthenticated,
+}
+
+impl From<Code> for GrpcStatusCode {
+    fn from(code: Code) -> Self {
+        match code {
+            Code::Ok => Self::Ok,
+            Code::Cancelled => Self::Cancelled,
+            Code::Unknown => Self::Unknown,
+            Code::InvalidArgument => Self::InvalidArgument,
+            Code::DeadlineExceeded => Self::DeadlineExceeded,
+            Code::NotFound => Self::NotFound,
+            Code::AlreadyExists => Self::AlreadyExists,
+            Code::PermissionDenied => Self::PermissionDenied,
+            Code::ResourceExhausted => Self::ResourceExhausted,
+            Code::FailedPrecondition => Self::FailedPrecondition,
+            Code::Aborted => Self::Aborted,
+            Code::OutOfRange => Self::OutOfRange,
+            Code::Unimplemented => Self::Unimplemented,
+            Code::Internal => Self::Internal,
+            Code::Unavailable => Self::Unavailable,
+            Code::DataLoss => Self::DataLoss,
+            Code::Unauthenticated => Self::Unauthenticated,
+        }
+    }
+}
+
+impl From<GrpcStatusCode> for Code {
+    fn from(code: GrpcStatusCode) -> Self {
+        match code {
+            GrpcStatusCode::Ok => Self::Ok,
+            GrpcStatusCode::Cancelled => Self::Cancelled,
+            GrpcStatusCode::Unknown => Self::Unknown,
+            GrpcStatusCode::InvalidArgument => Self::InvalidArgument,
+            GrpcStatusCode::DeadlineExceeded => Self::DeadlineExceeded,
+            GrpcStatusCode::NotFound => Self::NotFound,
+            GrpcStatusCode::AlreadyExists => Self::AlreadyExists,
+            GrpcStatusCode::PermissionDenied => Self::PermissionDenied,
+            GrpcStatusCode::ResourceExhausted => Self::ResourceExhausted,
+            GrpcStatusCode::FailedPrecondition => Self::FailedPrecondition,
+            GrpcStatusCode::Aborted => Self::Aborted,
+            GrpcStatusCode::OutOfRange => Self::OutOfRange,
+            GrpcStatusCode::Unimplemented => Self::Unimplemented,
+            GrpcStatusCode::Internal => Self::Internal,
+            GrpcStatusCode::Unavailable => Self::Unavailable,
+            GrpcStatusCode::DataLoss => Self::DataLoss,
+            GrpcStatusCode::Unauthenticated => Self::Unauthenticated,
+        }
+    }
 }
 
 /// A wrapper struct that will have a tokio task get aborted when dropped.
@@ -115,6 +302,15 @@ impl DiagramElementRegistry {
     /// nodes. This supports unary, server-streaming, client-streaming, and
     /// bidirectional gRPC clients.
     ///
+    /// The `grpc_request` and `grpc_client` nodes call the service described
+    /// by their [`GrpcConfig`]. The `grpc_dynamic_request` node instead takes a
+    /// [`GrpcDynamicRequest`] as input, so the server and method can be decided
+    /// while the workflow is running.
+    ///
+    /// All of these nodes respond with a `Result<(), GrpcError>`. Any metadata
+    /// that the server sends back is streamed out through the `headers` and
+    /// `trailers` streams.
+    ///
     /// ```
     /// # use std::sync::Arc;
     /// use crossflow::prelude::*;
@@ -148,22 +344,16 @@ impl DiagramElementRegistry {
             .register_node_builder_fallible(
                 NodeBuilderOptions::new("grpc_request").with_default_display_text("gRPC Request"),
                 move |builder, config: GrpcConfig| {
-                    let GrpcDescriptions {
-                        method,
-                        codec,
-                        path,
-                    } = get_descriptions(&config)?;
-
-                    let uri: Box<[u8]> = config.uri.as_bytes().into();
-                    let client = make_client(uri).shared();
-                    let is_server_streaming = method.is_server_streaming();
-                    let path = PathAndQuery::from_maybe_shared(path.clone())?;
+                    let clients = ClientCache::default();
+                    let mut metadata = MetadataMap::new();
+                    insert_metadata(&mut metadata, &config.metadata)?;
+                    let descriptions = prepare_descriptions(&config, &metadata, &clients)?;
 
                     let rt = Arc::clone(&rt);
                     let node = builder.create_map(move |input: Async<JsonMessage, GrpcStreams>| {
-                        let client = client.clone();
-                        let codec = codec.clone();
-                        let path = path.clone();
+                        let client = clients.get(&config.uri);
+                        let descriptions = descriptions.get();
+                        let metadata = metadata.clone();
 
                         // The tonic gRPC client needs to be run inside a tokio
                         // async runtime, so we spawn a tokio task here and use the
@@ -171,10 +361,16 @@ impl DiagramElementRegistry {
                         let task = rt
                             .spawn(async move {
                                 let client = client.await?;
+                                let GrpcDescriptions {
+                                    method,
+                                    codec,
+                                    path,
+                                } = descriptions.await?;
 
                                 // Convert the request message into a stream of a single dynamic message
                                 let request = input.request;
-                                let request = Request::new(once(async move { request }));
+                                let mut request = Request::new(once(async move { request }));
+                                *request.metadata_mut() = metadata;
                                 execute(
                                     request,
                                     client,
@@ -182,13 +378,17 @@ impl DiagramElementRegistry {
                                     path,
                                     config.timeout,
                                     input.streams,
-                                    is_server_streaming,
+                                    method.is_server_streaming(),
                                 )
                                 .await
                             })
                             .abort_on_drop();
 
-                        async move { task.await.map_err(|e| format!("{e}")).flatten() }
+                        async move {
+                            task.await
+                                .map_err(|e| GrpcError::new(Code::Internal, format!("{e}")))
+                                .flatten()
+                        }
                     });
 
                     Ok(node)
@@ -198,7 +398,7 @@ impl DiagramElementRegistry {
             .with_common_response()
             .with_result();
 
-        let rt = runtime;
+        let rt = Arc::clone(&runtime);
         self.opt_out()
             .no_serializing()
             .no_deserializing()
@@ -206,23 +406,17 @@ impl DiagramElementRegistry {
             .register_node_builder_fallible(
                 NodeBuilderOptions::new("grpc_client").with_default_display_text("gRPC Client"),
                 move |builder, config: GrpcConfig| {
-                    let GrpcDescriptions {
-                        method,
-                        codec,
-                        path,
-                    } = get_descriptions(&config)?;
-
-                    let uri: Box<[u8]> = config.uri.as_bytes().into();
-                    let client = make_client(uri).shared();
-                    let is_server_streaming = method.is_server_streaming();
-                    let path = PathAndQuery::from_maybe_shared(path.clone())?;
+                    let clients = ClientCache::default();
+                    let mut metadata = MetadataMap::new();
+                    insert_metadata(&mut metadata, &config.metadata)?;
+                    let descriptions = prepare_descriptions(&config, &metadata, &clients)?;
 
                     let rt = Arc::clone(&rt);
                     let node = builder.create_map(
                         move |input: Async<UnboundedReceiver<JsonMessage>, GrpcStreams>| {
-                            let client = client.clone();
-                            let codec = codec.clone();
-                            let path = path.clone();
+                            let client = clients.get(&config.uri);
+                            let descriptions = descriptions.get();
+                            let metadata = metadata.clone();
 
                             // The tonic gRPC client needs to be run inside a tokio
                             // async runtime, so we spawn a tokio task here and use the
@@ -230,9 +424,15 @@ impl DiagramElementRegistry {
                             let task = rt
                                 .spawn(async move {
                                     let client = client.await?;
+                                    let GrpcDescriptions {
+                                        method,
+                                        codec,
+                                        path,
+                                    } = descriptions.await?;
 
-                                    let request =
+                                    let mut request =
                                         Request::new(UnboundedReceiverStream::new(input.request));
+                                    *request.metadata_mut() = metadata;
                                     execute(
                                         request,
                                         client,
@@ -240,13 +440,17 @@ impl DiagramElementRegistry {
                                         path,
                                         config.timeout,
                                         input.streams,
-                                        is_server_streaming,
+                                        method.is_server_streaming(),
                                     )
                                     .await
                                 })
                                 .abort_on_drop();
 
-                            async move { task.await.map_err(|e| format!("{e}")).flatten() }
+                            async move {
+                                task.await
+                                    .map_err(|e| GrpcError::new(Code::Internal, format!("{e}")))
+                                    .flatten()
+                            }
                         },
                     );
 
@@ -256,11 +460,150 @@ impl DiagramElementRegistry {
             .with_common_response()
             .with_result();
 
+        let rt = runtime;
+        let clients = ClientCache::default();
+        self.opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder_fallible(
+                NodeBuilderOptions::new("grpc_dynamic_request")
+                    .with_default_display_text("gRPC Dynamic Request"),
+                move |builder, config: Option<GrpcDynamicConfig>| {
+                    let config = config.unwrap_or_default();
+                    let mut metadata = MetadataMap::new();
+                    insert_metadata(&mut metadata, &config.metadata)?;
+                    let rt = Arc::clone(&rt);
+                    let clients = clients.clone();
+                    let node =
+                        builder.create_map(move |input: Async<GrpcDynamicRequest, GrpcStreams>| {
+                            let Async {
+                                request, streams, ..
+                            } = input;
+                            let client = clients.get(&request.uri);
+                            let timeout = request.timeout.or(config.timeout);
+                            let mut metadata = metadata.clone();
+
+                            let task = rt
+                                .spawn(async move {
+                                    let GrpcDescriptions {
+                                        method,
+                                        codec,
+                                        path,
+                                    } = get_descriptions(
+                                        &DescriptorPool::global(),
+                                        &request.service,
+                                        request.method.as_ref(),
+                                    )
+                                    .map_err(|e| {
+                                        GrpcError::new(Code::InvalidArgument, format!("{e}"))
+                                    })?;
+                                    let is_server_streaming = method.is_server_streaming();
+
+                                    insert_metadata(&mut metadata, &request.metadata)?;
+                                    let body = request.body;
+                                    let mut grpc_request = Request::new(once(async move { body }));
+                                    *grpc_request.metadata_mut() = metadata;
+
+                                    let client = client.await?;
+                                    execute(
+                                        grpc_request,
+                                        client,
+                                        codec,
+                                        path,
+                                        timeout,
+                                        streams,
+                                        is_server_streaming,
+                                    )
+                                    .await
+                                })
+                                .abort_on_drop();
+
+                            async move {
+                                task.await
+                                    .map_err(|e| GrpcError::new(Code::Internal, format!("{e}")))
+                                    .flatten()
+                            }
+                        });
+
+                    Ok(node)
+                },
+            )
+            .with_common_request()
+            .with_common_response()
+            .with_result();
+
         self.register_message::<Option<String>>();
+        self.register_message::<HashMap<String, String>>();
+        self.register_message::<GrpcError>();
+    }
+}
+
+type SharedClient = Shared<BoxFuture<'static, Result<Client<Channel>, GrpcError>>>;
+
+/// Keeps one channel per URI so that requests to the same server can share a
+/// connection.
+#[derive(Clone, Default)]
+struct ClientCache {
+    clients: Arc<Mutex<HashMap<Arc<str>, SharedClient>>>,
+}
+
+impl ClientCache {
+    fn get(&self, uri: &Arc<str>) -> SharedClient {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(uri) {
+            // If the connection failed then try again instead of reusing the
+            // failure.
+            if !matches!(client.peek(), Some(Err(_))) {
+                return client.clone();
+            }
+        }
+
+        let client = make_client(uri.as_bytes().into()).boxed().shared();
+        clients.insert(Arc::clone(uri), client.clone());
+        client
+    }
+}
 
-        // TODO(@mxgrey): Support dynamic gRPC requests whose configurations are
-        // decided within the workflow and passed into the node as input.
+fn insert_metadata(
+    target: &mut MetadataMap,
+    metadata: &HashMap<String, String>,
+) -> Result<(), GrpcError> {
+    for (key, value) in metadata {
+        let key = MetadataKey::from_bytes(key.as_bytes()).map_err(|e| {
+            GrpcError::new(
+                Code::InvalidArgument,
+                format!("invalid metadata key [{key}]: {e}"),
+            )
+        })?;
+        let value = MetadataValue::try_from(value).map_err(|e| {
+            GrpcError::new(
+                Code::InvalidArgument,
+                format!("invalid metadata value for [{key}]: {e}"),
+            )
+        })?;
+        target.insert(key, value);
     }
+
+    Ok(())
+}
+
+/// Get the entries of the metadata that have text values. Binary entries and
+/// the headers that are reserved by gRPC itself are left out.
+fn metadata_to_map(metadata: &MetadataMap) -> HashMap<String, String> {
+    metadata
+        .iter()
+        .filter_map(|entry| match entry {
+            KeyAndValueRef::Ascii(key, value) => {
+                let key = key.as_str();
+                if key.starts_with("grpc-") || key == "content-type" {
+                    return None;
+                }
+
+                Some((key.to_owned(), value.to_str().ok()?.to_owned()))
+            }
+            KeyAndValueRef::Binary(..) => None,
+        })
+        .collect()
 }
 
 async fn receive_cancel<T>(
@@ -286,29 +629,114 @@ async fn receive_cancel<T>(
     }
 }
 
+#[derive(Clone)]
 struct GrpcDescriptions {
     method: MethodDescriptor,
     codec: DynamicServiceCodec,
-    path: String,
+    path: PathAndQuery,
+}
+
+type SharedDescriptions = Shared<BoxFuture<'static, Result<GrpcDescriptions, GrpcError>>>;
+
+/// Holds the descriptions for the service of a node. When reflection is used,
+/// the descriptions are fetched from the server the first time they are needed,
+/// and fetched again if that failed.
+#[derive(Clone)]
+struct DescriptionCache {
+    descriptions: Arc<Mutex<SharedDescriptions>>,
+    fetch: Option<Arc<dyn Fn() -> SharedDescriptions + Send + Sync>>,
+}
+
+impl DescriptionCache {
+    fn get(&self) -> SharedDescriptions {
+        let mut descriptions = self
+            .descriptions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(fetch) = &self.fetch {
+            // If the fetch failed then try again instead of reusing the
+            // failure.
+            if matches!(descriptions.peek(), Some(Err(_))) {
+                *descriptions = fetch();
+            }
+        }
+
+        descriptions.clone()
+    }
+}
+
+/// Get the descriptions for the service of a node. If they are fetched with
+/// reflection then the reflection calls send `metadata` and use the timeout of
+/// the node.
+fn prepare_descriptions(
+    config: &GrpcConfig,
+    metadata: &MetadataMap,
+    clients: &ClientCache,
+) -> Result<DescriptionCache, Anyhow> {
+    if config.reflection {
+        let clients = clients.clone();
+        let uri = Arc::clone(&config.uri);
+        let service = Arc::clone(&config.service);
+        let method = config.method.clone();
+        let metadata = metadata.clone();
+        let timeout = config.timeout;
+        let fetch = move || {
+            let clients = clients.clone();
+            let uri = Arc::clone(&uri);
+            let service = Arc::clone(&service);
+            let method = method.clone();
+            let metadata = metadata.clone();
+            async move {
+                let client = clients.get(&uri).await?;
+                let pool = fetch_descriptors(client, &service, &metadata, timeout).await?;
+                get_descriptions(&pool, &service, method.as_ref())
+                    .map_err(|e| GrpcError::new(Code::NotFound, format!("{e}")))
+            }
+            .boxed()
+            .shared()
+        };
+
+        return Ok(DescriptionCache {
+            descriptions: Arc::new(Mutex::new(fetch())),
+            fetch: Some(Arc::new(fetch)),
+        });
+    }
+
+    let descriptions = get_descriptions(
+        &DescriptorPool::global(),
+        &config.service,
+        config.method.as_ref(),
+    )?;
+    Ok(DescriptionCache {
+        descriptions: Arc::new(Mutex::new(ready(Ok(descriptions)).boxed().shared())),
+        fetch: None,
+    })
 }
 
-async fn make_client(uri: Box<[u8]>) -> Result<Client<Channel>, String> {
+async fn make_client(uri: Box<[u8]>) -> Result<Client<Channel>, GrpcError> {
     let channel = Channel::from_shared(uri)
-        .map_err(|e| format!("invalid uri for service: {e}"))?
+        .map_err(|e| {
+            GrpcError::new(
+                Code::InvalidArgument,
+                format!("invalid uri for service: {e}"),
+            )
+        })?
         .connect()
         .await
-        .map_err(|e| format!("failed to connect: {e}"))?;
-    Ok::<_, String>(Client::new(channel))
+        .map_err(|e| GrpcError::new(Code::Unavailable, format!("failed to connect: {e}")))?;
+    Ok(Client::new(channel))
 }
 
-fn get_descriptions(config: &GrpcConfig) -> Result<GrpcDescriptions, Anyhow> {
-    let descriptors = DescriptorPool::global();
-    let service_name = &config.service;
+fn get_descriptions(
+    descriptors: &DescriptorPool,
+    service_name: &str,
+    method: Option<&Identifier>,
+) -> Result<GrpcDescriptions, Anyhow> {
     let service = descriptors
-        .get_service_by_name(&service_name)
-        .ok_or_else(|| anyhow!("could not find service name [{}]", config.service))?;
+        .get_service_by_name(service_name)
+        .ok_or_else(|| anyhow!("could not find service name [{service_name}]"))?;
 
-    let method = match config.method.as_ref().unwrap_or(&Identifier::Index(0)) {
+    let method = match method.unwrap_or(&Identifier::Index(0)) {
         Identifier::Index(index) => service.methods().skip(*index).next().ok_or_else(|| {
             anyhow!("service [{service_name}] does not have a method with index [{index}]")
         })?,
@@ -327,12 +755,12 @@ fn get_descriptions(config: &GrpcConfig) -> Result<GrpcDescriptions, Anyhow> {
         output: method.output(),
     };
 
-    let path = format!(
+    let path = PathAndQuery::from_maybe_shared(format!(
         "/{}.{}/{}",
         service.package_name(),
         service.name(),
         method.name(),
-    );
+    ))?;
 
     Ok(GrpcDescriptions {
         method,
@@ -342,31 +770,34 @@ fn get_descriptions(config: &GrpcConfig) -> Result<GrpcDescriptions, Anyhow> {
 }
 
 async fn execute<S>(
-    request: Request<S>,
+    mut request: Request<S>,
     mut client: Client<Channel>,
     codec: DynamicServiceCodec,
     path: PathAndQuery,
     timeout: Option<f64>,
     output_streams: <GrpcStreams as StreamPack>::StreamChannels,
     is_server_streaming: bool,
-) -> Result<(), String>
+) -> Result<(), GrpcError>
 where
     S: FutureStream<Item = JsonMessage> + Send + 'static,
 {
-    // Wait for client to be ready
-    let timeout = timeout.map(|t| Duration::from_secs_f64(t));
-    client.ready().await.map_err(|e| format!("{e}"))?;
+    let timeout = timeout.map(Duration::from_secs_f64);
     if let Some(t) = timeout {
-        until_timeout(t, client.ready())
-            .await
-            .map_err(|_| "timeout waiting for server to be ready".to_owned())?
-            .map_err(|e| format!("server failed to be ready: {e}"))?;
-    } else {
+        // Let the server know how long we will wait for it
+        request.set_timeout(t);
+    }
+    // Every stage below shares this one deadline so the client never waits
+    // longer than what it told the server.
+    let deadline = timeout.map(|t| Instant::now() + t);
+
+    // Wait for client to be ready
+    with_timeout(deadline, "server to be ready", async {
         client
             .ready()
             .await
-            .map_err(|e| format!("server failed to be ready: {e}"))?;
-    }
+            .map_err(|e| Status::new(Code::Unavailable, format!("server failed to be ready: {e}")))
+    })
+    .await?;
 
     // Set up cancellation channel
     let (sender, mut receiver) = unbounded_channel();
@@ -376,38 +807,79 @@ where
 
     let session = client.streaming(request, path, codec);
     let cancellable_session = race(session, receive_cancel(cancel.clone()));
+    let response = with_timeout(deadline, "response", cancellable_session).await?;
 
-    let r = if let Some(t) = timeout {
-        until_timeout(t, cancellable_session)
-            .await
-            .map_err(|e| format!("{e}"))?
-    } else {
-        cancellable_session.await
-    };
+    let headers = metadata_to_map(response.metadata());
+    if !headers.is_empty() {
+        output_streams.headers.send(headers);
+    }
 
-    let mut streaming = r.map_err(|e| format!("{e}"))?.into_inner();
+    let mut streaming = response.into_inner();
     loop {
-        let r = if let Some(t) = timeout {
-            until_timeout(t, race(streaming.message(), receive_cancel(cancel.clone())))
-                .await
-                .map_err(|_| "timeout waiting for new stream message".to_owned())?
-        } else {
-            race(streaming.message(), receive_cancel(cancel.clone())).await
-        }
-        .map_err(|e| format!("{e}"))?;
+        let r = with_timeout(
+            deadline,
+            "new stream message",
+            race(streaming.message(), receive_cancel(cancel.clone())),
+        )
+        .await?;
 
         let Some(response) = r else {
-            return Ok::<_, String>(());
+            break;
         };
 
-        let value = serde_json::to_value(response)
-            .map_err(|e| format!("failed to convert to json: {e}"))?;
+        let value = serde_json::to_value(response).map_err(|e| {
+            GrpcError::new(Code::Internal, format!("failed to convert to json: {e}"))
+        })?;
         output_streams.out.send(value);
 
         if !is_server_streaming {
-            return Ok(());
+            break;
+        }
+    }
+
+    // The trailers arrive after the last message of the response
+    let trailers = with_timeout(
+        deadline,
+        "trailers",
+        race(streaming.trailers(), receive_cancel(cancel)),
+    )
+    .await?;
+    if let Some(trailers) = trailers {
+        let trailers = metadata_to_map(&trailers);
+        if !trailers.is_empty() {
+            output_streams.trailers.send(trailers);
         }
     }
+
+    Ok(())
+}
+
+async fn with_timeout<T>(
+    deadline: Option<Instant>,
+    waiting_for: &str,
+    future: impl Future<Output = Result<T, Status>>,
+) -> Result<T, GrpcError> {
+    let r = if let Some(deadline) = deadline {
+        let deadline_exceeded = || {
+            GrpcError::new(
+                Code::DeadlineExceeded,
+                format!("timeout waiting for {waiting_for}"),
+            )
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(deadline_exceeded());
+        }
+
+        until_timeout(remaining, future)
+            .await
+            .map_err(|_| deadline_exceeded())?
+    } else {
+        future.await
+    };
+
+    Ok(r?)
 }
 
 #[derive(Clone)]
@@ -482,6 +954,7 @@ mod tests {
     use super::*;
     use crate::{diagram::testing::*, prelude::*, utils::*};
     use futures::channel::oneshot::{self, Sender as OneShotSender};
+    use futures::stream::Pending;
     use prost_reflect::Kind;
     use protos::{
         FibonacciReply, FibonacciRequest, NavigationGoal, NavigationUpdate,
@@ -496,7 +969,13 @@ mod tests {
     };
     use tonic::{
         Request, Response, Status, Streaming,
-        codegen::tokio_stream::wrappers::UnboundedReceiverStream, transport::Server,
+        codegen::tokio_stream::wrappers::UnboundedReceiverStream,
+        service::{Routes, interceptor::InterceptedService},
+        transport::{Server, server::TcpIncoming},
+    };
+    use tonic_reflection::pb::v1::{
+        ServerReflectionRequest, ServerReflectionResponse,
+        server_reflection_server::{ServerReflection, ServerReflectionServer},
     };
 
     #[test]
@@ -674,44 +1153,19 @@ mod tests {
     }
 
     #[test]
-    fn test_grpc_bidirectional_streaming() {
+    fn test_grpc_dynamic_request() {
         let descriptor_set_bytes =
             include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
         DescriptorPool::decode_global_file_descriptor_set(&descriptor_set_bytes[..]).unwrap();
 
         let mut fixture = DiagramTestFixture::new();
-
-        fixture
-            .registry
-            .opt_out()
-            .minimal()
-            .register_node_builder(NodeBuilderOptions::new("guide"), create_guide_to_goal);
-        fixture
-            .registry
-            .opt_out()
-            .minimal()
-            .register_message::<GoalTracker>();
-
-        let reached_listener = fixture.context.app.spawn_service(check_if_reached);
-        fixture
-            .registry
-            .opt_out()
-            .no_serializing()
-            .no_deserializing()
-            .register_node_builder(
-                NodeBuilderOptions::new("reached_listener"),
-                move |builder, _: ()| builder.create_node(reached_listener),
-            )
-            .with_listen();
-
         let port = 50000 + line!();
-        let addr = format!("[::1]:{port}").parse().unwrap();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
 
         let rt = Arc::new(Runtime::new().unwrap());
-        let navigation = TestNavigation::new(Arc::clone(&rt));
         rt.spawn(async move {
             Server::builder()
-                .add_service(NavigationServer::new(navigation))
+                .add_service(FibonacciServer::new(GenerateFibonacci))
                 .serve(addr)
                 .await
                 .unwrap();
@@ -725,64 +1179,583 @@ mod tests {
 
         let diagram = Diagram::from_json(json!({
             "version": "0.1.0",
-            "start": "guider",
+            "start": "fibonacci",
             "ops": {
-                "guider": {
-                    "type": "node",
-                    "builder": "guide",
-                    "config": {
-                        "goals": [
-                            [10.0, 10.0],
-                            [5.0, 5.0],
-                            [-5.0, 0.0]
-                        ]
-                    },
-                    "stream_out": {
-                        "goal_receiver": "guided",
-                        "goal_tracker": "tracker",
-                    },
-                    "next": { "builtin" : "terminate" }
-                },
-                "guided": {
+                "fibonacci": {
                     "type": "node",
-                    "builder": "grpc_client",
-                    "config": {
-                        "service": "example_protos.navigation.Navigation",
-                        "method": "Guide",
-                        "uri": format!("http://localhost:{port}"),
-                    },
+                    "builder": "grpc_dynamic_request",
                     "stream_out": {
-                        "out": "update_buffer"
-                    },
-                    "next": { "builtin": "dispose" }
-                },
-                "update_buffer": { "type": "buffer" },
-                "tracker": { "type": "buffer" },
-                "listen": {
-                    "type": "listen",
-                    "buffers": {
-                        "navigation_update": "update_buffer",
-                        "goal_tracker": "tracker"
+                        "out": { "builtin": "terminate" }
                     },
-                    "next": "reached_listener"
-                },
-                "reached_listener": {
-                    "type": "node",
-                    "builder": "reached_listener",
-                    "next": { "builtin" : "dispose" }
+                    "next": { "builtin": "terminate" }
                 }
             }
         }))
         .unwrap();
 
-        let _: () = fixture.spawn_and_run(&diagram, ()).unwrap();
+        let request = json!({
+            "uri": format!("http://127.0.0.1:{port}"),
+            "service": "example_protos.fibonacci.Fibonacci",
+            "method": "FinalNumber",
+            "metadata": {
+                "x-robot-name": "robot_1"
+            },
+            "body": {
+                "order": 10
+            }
+        });
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, request, Duration::from_secs(2))
+            .unwrap();
+        let value = result["value"].as_number().unwrap().as_u64().unwrap();
+        assert_eq!(value, 55);
+
+        let request = json!({
+            "uri": format!("http://127.0.0.1:{port}"),
+            "service": "example_protos.fibonacci.Fibonacci",
+            "method": "FinalNumber",
+            "metadata": {
+                "invalid key": "robot_1"
+            },
+            "body": {
+                "order": 10
+            }
+        });
+
+        // The node responds with the error since the diagram does not fork
+        // the result.
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, request, Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result["Err"]["code"], "invalid_argument");
+        let err = result["Err"]["message"].as_str().unwrap();
+        assert!(err.contains("invalid metadata key"));
 
         let _ = exit_sender.send(());
     }
 
-    struct GenerateFibonacci;
+    #[test]
+    fn test_grpc_reflection() {
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .build_v1()
+            .unwrap();
+        check_reflection(50000 + line!(), Routes::new(reflection), json!({}));
+    }
 
-    #[tonic::async_trait]
+    #[test]
+    fn test_grpc_reflection_v1alpha() {
+        // Servers that only provide the older version of the protocol should
+        // still work.
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .build_v1alpha()
+            .unwrap();
+        check_reflection(50000 + line!(), Routes::new(reflection), json!({}));
+    }
+
+    #[test]
+    fn test_grpc_reflection_sends_metadata() {
+        // Servers that require metadata for their service usually require it
+        // for reflection too.
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .build_v1()
+            .unwrap();
+        let reflection = InterceptedService::new(reflection, |request: Request<()>| match request
+            .metadata()
+            .get("x-token")
+        {
+            Some(token) if token == "secret" => Ok(request),
+            _ => Err(Status::unauthenticated("missing token")),
+        });
+        check_reflection(
+            50000 + line!(),
+            Routes::new(reflection),
+            json!({ "metadata": { "x-token": "secret" } }),
+        );
+    }
+
+    #[test]
+    fn test_grpc_reflection_timeout() {
+        let mut fixture = DiagramTestFixture::new();
+        let port = 50000 + line!();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        rt.spawn(async move {
+            Server::builder()
+                .add_service(ServerReflectionServer::new(StalledReflection))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        let diagram = reflection_diagram_with(port, json!({ "timeout": 0.5 }));
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, json!({ "order": 10 }), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result["Err"]["code"], "deadline_exceeded");
+        let message = result["Err"]["message"].as_str().unwrap();
+        assert!(message.contains("server reflection"));
+
+        let _ = exit_sender.send(());
+    }
+
+    const FILE_DESCRIPTOR_SET: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+
+    fn reflection_diagram(port: u32) -> Diagram {
+        reflection_diagram_with(port, json!({}))
+    }
+
+    /// A diagram with a node that uses reflection, whose config gets the
+    /// fields of `extra_config` added to it.
+    fn reflection_diagram_with(port: u32, extra_config: JsonMessage) -> Diagram {
+        let mut config = json!({
+            "service": "example_protos.fibonacci.Fibonacci",
+            "method": "FinalNumber",
+            "uri": format!("http://127.0.0.1:{port}"),
+            "reflection": true,
+        });
+        if let (Some(config), JsonMessage::Object(extra_config)) =
+            (config.as_object_mut(), extra_config)
+        {
+            config.extend(extra_config);
+        }
+
+        Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fibonacci",
+            "ops": {
+                "fibonacci": {
+                    "type": "node",
+                    "builder": "grpc_request",
+                    "config": config,
+                    "stream_out": {
+                        "out": { "builtin": "terminate" }
+                    },
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn check_reflection(port: u32, reflection: Routes, extra_config: JsonMessage) {
+        // The descriptors are deliberately not loaded into the global pool
+        // here. The node needs to get them from the reflection service.
+        let mut fixture = DiagramTestFixture::new();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        rt.spawn(async move {
+            Server::builder()
+                .add_routes(reflection)
+                .add_service(FibonacciServer::new(GenerateFibonacci))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        let request = json!({
+            "order": 10
+        });
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(
+                &reflection_diagram_with(port, extra_config),
+                request,
+                Duration::from_secs(2),
+            )
+            .unwrap();
+        let value = result["value"].as_number().unwrap().as_u64().unwrap();
+        assert_eq!(value, 55);
+
+        let _ = exit_sender.send(());
+    }
+
+    #[test]
+    fn test_grpc_reflection_retries_after_failure() {
+        let mut fixture = DiagramTestFixture::new();
+        let port = 50000 + line!();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+        let workflow = fixture
+            .spawn_json_io_workflow(&reflection_diagram(port))
+            .unwrap();
+
+        // Nothing is listening yet, so fetching the descriptions fails.
+        let mut outcome = fixture
+            .context
+            .command(|commands| commands.request(json!({ "order": 10 }), workflow).outcome());
+        fixture
+            .context
+            .run_with_conditions(&mut outcome, Duration::from_secs(2));
+        let result = outcome.try_recv().unwrap().unwrap();
+        assert_eq!(result["Err"]["code"], "unavailable");
+
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .build_v1()
+            .unwrap();
+        let (ready_sender, ready_receiver) = std::sync::mpsc::channel();
+        rt.spawn(async move {
+            let incoming = TcpIncoming::bind(addr).unwrap();
+            let _ = ready_sender.send(());
+            Server::builder()
+                .add_service(reflection)
+                .add_service(FibonacciServer::new(GenerateFibonacci))
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        });
+        ready_receiver.recv().unwrap();
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        // The same node fetches the descriptions again now that the server is up.
+        let mut outcome = fixture
+            .context
+            .command(|commands| commands.request(json!({ "order": 10 }), workflow).outcome());
+        fixture
+            .context
+            .run_with_conditions(&mut outcome, Duration::from_secs(2));
+        let result = outcome.try_recv().unwrap().unwrap();
+        assert_eq!(result["value"], 55);
+
+        let _ = exit_sender.send(());
+    }
+
+    #[test]
+    fn test_grpc_metadata_and_status() {
+        let descriptor_set_bytes =
+            include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+        DescriptorPool::decode_global_file_descriptor_set(&descriptor_set_bytes[..]).unwrap();
+
+        let mut fixture = DiagramTestFixture::new();
+        let port = 50000 + line!();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        rt.spawn(async move {
+            Server::builder()
+                .add_service(FibonacciServer::new(CheckedFibonacci))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        // The headers of a successful response terminate the workflow while
+        // errors get routed based on their status code.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fibonacci",
+            "ops": {
+                "fibonacci": {
+                    "type": "node",
+                    "builder": "grpc_request",
+                    "config": {
+                        "service": "example_protos.fibonacci.Fibonacci",
+                        "method": "FinalNumber",
+                        "uri": format!("http://127.0.0.1:{port}"),
+                        "timeout": 2.0,
+                        "metadata": {
+                            "x-robot-name": "robot_1"
+                        }
+                    },
+                    "stream_out": {
+                        "headers": { "builtin": "terminate" }
+                    },
+                    "next": "fork_result"
+                },
+                "fork_result": {
+                    "type": "fork_result",
+                    "ok": { "builtin": "dispose" },
+                    "err": "route_error"
+                },
+                "route_error": {
+                    "type": "switch",
+                    "cases": [
+                        { "when": "request.code == \"invalid_argument\"", "next": "max_order" }
+                    ],
+                    "default": { "builtin": "terminate" }
+                },
+                "max_order": {
+                    "type": "transform",
+                    "cel": "request.metadata[\"x-max-order\"]",
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, json!({ "order": 10 }), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result["x-robot-name"], "robot_1");
+        assert_eq!(result["x-fibonacci-value"], "55");
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(
+                &diagram,
+                json!({ "order": 100 }),
+                Duration::from_secs(2),
+            )
+            .unwrap();
+        assert_eq!(result, "93");
+
+        let _ = exit_sender.send(());
+    }
+
+    #[test]
+    fn test_grpc_trailers() {
+        let descriptor_set_bytes =
+            include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+        DescriptorPool::decode_global_file_descriptor_set(&descriptor_set_bytes[..]).unwrap();
+
+        let mut fixture = DiagramTestFixture::new();
+        let port = 50000 + line!();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        rt.spawn(async move {
+            Server::builder()
+                .add_service(FibonacciServer::new(CheckedFibonacci))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        // Only the trailers can terminate the workflow.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fibonacci",
+            "ops": {
+                "fibonacci": {
+                    "type": "node",
+                    "builder": "grpc_request",
+                    "config": {
+                        "service": "example_protos.fibonacci.Fibonacci",
+                        "method": "SequenceStream",
+                        "uri": format!("http://127.0.0.1:{port}"),
+                        "timeout": 2.0
+                    },
+                    "stream_out": {
+                        "out": { "builtin": "dispose" },
+                        "trailers": { "builtin": "terminate" }
+                    },
+                    "next": { "builtin": "dispose" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, json!({ "order": 10 }), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result["x-sequence-length"], "11");
+
+        let _ = exit_sender.send(());
+    }
+
+    #[test]
+    fn test_grpc_timeout_covers_whole_stream() {
+        let descriptor_set_bytes =
+            include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+        DescriptorPool::decode_global_file_descriptor_set(&descriptor_set_bytes[..]).unwrap();
+
+        let mut fixture = DiagramTestFixture::new();
+        let port = 50000 + line!();
+        let addr = format!("127.0.0.1:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        rt.spawn(async move {
+            Server::builder()
+                .add_service(FibonacciServer::new(SlowFibonacci))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        // Each message of the stream arrives well within the timeout, but the
+        // whole stream takes longer than that.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fibonacci",
+            "ops": {
+                "fibonacci": {
+                    "type": "node",
+                    "builder": "grpc_request",
+                    "config": {
+                        "service": "example_protos.fibonacci.Fibonacci",
+                        "method": "SequenceStream",
+                        "uri": format!("http://127.0.0.1:{port}"),
+                        "timeout": 0.5
+                    },
+                    "stream_out": {
+                        "out": { "builtin": "dispose" }
+                    },
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, json!({ "order": 10 }), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result["Err"]["code"], "deadline_exceeded");
+
+        let _ = exit_sender.send(());
+    }
+
+    #[test]
+    fn test_grpc_bidirectional_streaming() {
+        let descriptor_set_bytes =
+            include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+        DescriptorPool::decode_global_file_descriptor_set(&descriptor_set_bytes[..]).unwrap();
+
+        let mut fixture = DiagramTestFixture::new();
+
+        fixture
+            .registry
+            .opt_out()
+            .minimal()
+            .register_node_builder(NodeBuilderOptions::new("guide"), create_guide_to_goal);
+        fixture
+            .registry
+            .opt_out()
+            .minimal()
+            .register_message::<GoalTracker>();
+
+        let reached_listener = fixture.context.app.spawn_service(check_if_reached);
+        fixture
+            .registry
+            .opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder(
+                NodeBuilderOptions::new("reached_listener"),
+                move |builder, _: ()| builder.create_node(reached_listener),
+            )
+            .with_listen();
+
+        let port = 50000 + line!();
+        let addr = format!("[::1]:{port}").parse().unwrap();
+
+        let rt = Arc::new(Runtime::new().unwrap());
+        let navigation = TestNavigation::new(Arc::clone(&rt));
+        rt.spawn(async move {
+            Server::builder()
+                .add_service(NavigationServer::new(navigation))
+                .serve(addr)
+                .await
+                .unwrap();
+        });
+        fixture.registry.enable_grpc(Arc::clone(&rt));
+
+        let (exit_sender, exit_receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = rt.block_on(exit_receiver);
+        });
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "guider",
+            "ops": {
+                "guider": {
+                    "type": "node",
+                    "builder": "guide",
+                    "config": {
+                        "goals": [
+                            [10.0, 10.0],
+                            [5.0, 5.0],
+                            [-5.0, 0.0]
+                        ]
+                    },
+                    "stream_out": {
+                        "goal_receiver": "guided",
+                        "goal_tracker": "tracker",
+                    },
+                    "next": { "builtin" : "terminate" }
+                },
+                "guided": {
+                    "type": "node",
+                    "builder": "grpc_client",
+                    "config": {
+                        "service": "example_protos.navigation.Navigation",
+                        "method": "Guide",
+                        "uri": format!("http://localhost:{port}"),
+                    },
+                    "stream_out": {
+                        "out": "update_buffer"
+                    },
+                    "next": { "builtin": "dispose" }
+                },
+                "update_buffer": { "type": "buffer" },
+                "tracker": { "type": "buffer" },
+                "listen": {
+                    "type": "listen",
+                    "buffers": {
+                        "navigation_update": "update_buffer",
+                        "goal_tracker": "tracker"
+                    },
+                    "next": "reached_listener"
+                },
+                "reached_listener": {
+                    "type": "node",
+                    "builder": "reached_listener",
+                    "next": { "builtin" : "dispose" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let _: () = fixture.spawn_and_run(&diagram, ()).unwrap();
+
+        let _ = exit_sender.send(());
+    }
+
+    struct GenerateFibonacci;
+
+    #[tonic::async_trait]
     impl Fibonacci for GenerateFibonacci {
         async fn final_number(
             &self,
@@ -807,6 +1780,112 @@ mod tests {
         }
     }
 
+    /// A fibonacci service that echoes the robot name from the request metadata
+    /// and rejects orders whose result would not fit into a u64. Its sequence
+    /// stream reports how many numbers it sent in the trailers.
+    struct CheckedFibonacci;
+
+    #[tonic::async_trait]
+    impl Fibonacci for CheckedFibonacci {
+        async fn final_number(
+            &self,
+            request: Request<FibonacciRequest>,
+        ) -> Result<Response<FibonacciReply>, Status> {
+            let Some(robot_name) = request.metadata().get("x-robot-name").cloned() else {
+                return Err(Status::unauthenticated("missing x-robot-name"));
+            };
+
+            let order = request.into_inner().order;
+            if order > 93 {
+                let mut metadata = tonic::metadata::MetadataMap::new();
+                metadata.insert("x-max-order", "93".parse().unwrap());
+                return Err(Status::with_metadata(
+                    Code::InvalidArgument,
+                    "order is too large",
+                    metadata,
+                ));
+            }
+
+            let reply = calculate_fibonacci(order, None);
+            let mut response = Response::new(reply);
+            response.metadata_mut().insert("x-robot-name", robot_name);
+            response
+                .metadata_mut()
+                .insert("x-fibonacci-value", reply.value.into());
+            Ok(response)
+        }
+
+        type SequenceStreamStream = UnboundedReceiverStream<Result<FibonacciReply, Status>>;
+
+        async fn sequence_stream(
+            &self,
+            request: Request<FibonacciRequest>,
+        ) -> Result<Response<Self::SequenceStreamStream>, Status> {
+            let order = request.into_inner().order;
+            let (sender, receiver) = unbounded_channel();
+            calculate_fibonacci(order, Some(sender.clone()));
+
+            // A status with an Ok code ends the stream successfully while
+            // sending its metadata as trailers.
+            let mut trailers = tonic::metadata::MetadataMap::new();
+            trailers.insert("x-sequence-length", (order + 1).into());
+            let _ = sender.send(Err(Status::with_metadata(Code::Ok, "", trailers)));
+
+            Ok(Response::new(receiver.into()))
+        }
+    }
+
+    /// A fibonacci service that streams one number of the sequence every 100ms.
+    struct SlowFibonacci;
+
+    #[tonic::async_trait]
+    impl Fibonacci for SlowFibonacci {
+        async fn final_number(
+            &self,
+            _: Request<FibonacciRequest>,
+        ) -> Result<Response<FibonacciReply>, Status> {
+            Err(Status::unimplemented("only the sequence is available"))
+        }
+
+        type SequenceStreamStream = UnboundedReceiverStream<Result<FibonacciReply, Status>>;
+
+        async fn sequence_stream(
+            &self,
+            request: Request<FibonacciRequest>,
+        ) -> Result<Response<Self::SequenceStreamStream>, Status> {
+            let (sender, receiver) = unbounded_channel();
+            let (slow_sender, mut slow_receiver) = unbounded_channel();
+            std::thread::spawn(move || {
+                calculate_fibonacci(request.into_inner().order, Some(slow_sender));
+            });
+            std::thread::spawn(move || {
+                while let Some(reply) = slow_receiver.blocking_recv() {
+                    std::thread::sleep(Duration::from_millis(100));
+                    if sender.send(reply).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            Ok(Response::new(receiver.into()))
+        }
+    }
+
+    /// A reflection service that accepts requests but never answers them.
+    struct StalledReflection;
+
+    #[tonic::async_trait]
+    impl ServerReflection for StalledReflection {
+        type ServerReflectionInfoStream = Pending<Result<ServerReflectionResponse, Status>>;
+
+        async fn server_reflection_info(
+            &self,
+            _: Request<Streaming<ServerReflectionRequest>>,
+        ) -> Result<Response<Self::ServerReflectionInfoStream>, Status> {
+            Ok(Response::new(futures::stream::pending()))
+        }
+    }
+
     fn calculate_fibonacci(
         order: u64,
         sender: Option<UnboundedSender<Result<FibonacciReply, Status>>>,
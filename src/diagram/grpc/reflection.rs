@@ -0,0 +1,190 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+//! A client for the [gRPC server reflection protocol](https://github.com/grpc/grpc/blob/master/doc/server-reflection.md).
+//!
+//! Only the parts of the protocol that are needed to fetch file descriptors are
+//! used.
+
+use super::{GrpcError, GrpcStatusCode, with_timeout};
+
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use http::uri::PathAndQuery;
+use prost::Message;
+use prost_reflect::{DescriptorPool, prost_types::FileDescriptorProto};
+use tonic::{
+    Code, Request, client::Grpc as Client,
+    codegen::tokio_stream::wrappers::UnboundedReceiverStream, metadata::MetadataMap,
+    transport::Channel,
+};
+use tonic_prost::ProstCodec;
+use tonic_reflection::pb::v1::{
+    ServerReflectionRequest, ServerReflectionResponse, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse,
+};
+
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+/// Paths of the reflection method, in order of preference. Servers that predate
+/// v1 of the protocol only provide v1alpha, whose messages are the same as v1
+/// on the wire, so the v1 messages are used for both.
+const REFLECTION_PATHS: [&str; 2] = [
+    "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo",
+    "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo",
+];
+
+/// Ask the server for the proto file that declares `symbol` along with every
+/// file that it depends on, and put them into a new [`DescriptorPool`].
+///
+/// The reflection calls carry the same `metadata` as the calls to the service
+/// itself, and they all share one deadline set by `timeout` (in seconds).
+pub(super) async fn fetch_descriptors(
+    client: Client<Channel>,
+    symbol: &str,
+    metadata: &MetadataMap,
+    timeout: Option<f64>,
+) -> Result<DescriptorPool, GrpcError> {
+    let deadline = timeout.map(|t| Instant::now() + Duration::from_secs_f64(t));
+    let mut files = None;
+    for path in REFLECTION_PATHS {
+        let fetch = fetch_files(client.clone(), path, symbol, metadata, deadline);
+        match with_timeout(deadline, "server reflection", fetch).await {
+            Err(err) if err.code == GrpcStatusCode::Unimplemented => continue,
+            result => {
+                files = Some(result.map_err(|err| GrpcError {
+                    message: format!("server reflection failed: {}", err.message),
+                    ..err
+                })?);
+                break;
+            }
+        }
+    }
+
+    let files = files.ok_or_else(|| {
+        GrpcError::new(
+            Code::Unimplemented,
+            "the server does not support reflection",
+        )
+    })?;
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files).map_err(|e| {
+        GrpcError::new(
+            Code::Internal,
+            format!("invalid descriptors from server reflection: {e}"),
+        )
+    })?;
+    Ok(pool)
+}
+
+async fn fetch_files(
+    mut client: Client<Channel>,
+    path: &'static str,
+    symbol: &str,
+    metadata: &MetadataMap,
+    deadline: Option<Instant>,
+) -> Result<Vec<FileDescriptorProto>, tonic::Status> {
+    let (sender, receiver) = unbounded_channel();
+    let send = |sender: &UnboundedSender<ServerReflectionRequest>, request| {
+        // If the request stream was closed then the response stream will tell
+        // us why.
+        let _ = sender.send(ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(request),
+        });
+    };
+    send(
+        &sender,
+        MessageRequest::FileContainingSymbol(symbol.to_owned()),
+    );
+    let mut pending = 1_usize;
+
+    let mut request = Request::new(UnboundedReceiverStream::new(receiver));
+    *request.metadata_mut() = metadata.clone();
+    if let Some(deadline) = deadline {
+        // Let the server know how long we will wait for it
+        request.set_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+
+    client
+        .ready()
+        .await
+        .map_err(|e| tonic::Status::new(Code::Unavailable, format!("{e}")))?;
+    let mut responses = client
+        .streaming(
+            request,
+            PathAndQuery::from_static(path),
+            ProstCodec::<ServerReflectionRequest, ServerReflectionResponse>::default(),
+        )
+        .await?
+        .into_inner();
+
+    let mut files: Vec<FileDescriptorProto> = Vec::new();
+    let mut received = HashSet::new();
+    let mut requested = HashSet::new();
+    while pending > 0 {
+        let Some(response) = responses.message().await? else {
+            return Err(tonic::Status::new(
+                Code::Unavailable,
+                "reflection stream ended before all files were received",
+            ));
+        };
+        pending -= 1;
+
+        match response.message_response {
+            Some(MessageResponse::FileDescriptorResponse(response)) => {
+                let first_new = files.len();
+                for bytes in response.file_descriptor_proto {
+                    let file = FileDescriptorProto::decode(bytes.as_slice()).map_err(|e| {
+                        tonic::Status::new(Code::DataLoss, format!("invalid file descriptor: {e}"))
+                    })?;
+                    if received.insert(file.name().to_owned()) {
+                        files.push(file);
+                    }
+                }
+
+                // Servers usually send every dependency along with the file,
+                // but they are not required to.
+                for file in &files[first_new..] {
+                    for dependency in &file.dependency {
+                        if !received.contains(dependency) && requested.insert(dependency.clone()) {
+                            send(&sender, MessageRequest::FileByFilename(dependency.clone()));
+                            pending += 1;
+                        }
+                    }
+                }
+            }
+            Some(MessageResponse::ErrorResponse(error)) => {
+                return Err(tonic::Status::new(
+                    Code::from_i32(error.error_code),
+                    error.error_message,
+                ));
+            }
+            _ => {
+                return Err(tonic::Status::new(
+                    Code::Unknown,
+                    "unexpected response from server reflection",
+                ));
+            }
+        }
+    }
+
+    Ok(files)
+}
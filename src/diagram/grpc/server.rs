@@ -0,0 +1,577 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use super::{AbortOnDrop, AbortOnDropExt, DynamicServiceCodec};
+use crate::{
+    Capture, Diagram, DiagramElementRegistry, DiagramError, JsonMessage, RequestExt, Service,
+    StreamPack,
+};
+
+use bevy_ecs::prelude::{ChildOf, Commands, Component, Entity, Query};
+
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr, pin::Pin};
+
+use futures::{
+    Stream as FutureStream, StreamExt,
+    stream::{empty, once},
+};
+
+use prost_reflect::{DescriptorPool, MethodDescriptor};
+use thiserror::Error as ThisError;
+use tonic::{
+    Code, Request, Response, Status,
+    codegen::{
+        BoxFuture, Service as TowerService, tokio_stream::wrappers::UnboundedReceiverStream,
+    },
+    server::{Grpc, ServerStreamingService, UnaryService},
+    service::Routes,
+    transport::{Server, server::TcpIncoming},
+};
+
+use tokio::{
+    runtime::Runtime,
+    sync::{
+        mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+        oneshot,
+    },
+};
+
+/// Describes which diagram should answer each method of a set of gRPC services.
+/// Use [`ServeGrpcDiagramsExt::serve_grpc_diagrams`] to host the services.
+///
+/// Each call spawns a new session of the diagram. The request message is passed
+/// into the diagram as a [`JsonMessage`] that uses the proto field names.
+///
+/// For unary methods, the response of the diagram is sent back to the client.
+///
+/// For server-streaming methods, every message that the diagram sends to its
+/// `out` stream is sent to the client, and the call is finished once the
+/// diagram terminates. The final response of the diagram is ignored.
+///
+/// Client-streaming and bidirectional methods are not supported.
+pub struct GrpcServerConfig {
+    descriptors: DescriptorPool,
+    methods: HashMap<(String, String), Diagram>,
+}
+
+impl GrpcServerConfig {
+    /// Create a config whose service and message descriptions come from
+    /// `descriptors`.
+    pub fn new(descriptors: DescriptorPool) -> Self {
+        Self {
+            descriptors,
+            methods: Default::default(),
+        }
+    }
+
+    /// Answer calls to `method` of `service` with `diagram`. The service name
+    /// must be fully qualified, e.g. `example_protos.fibonacci.Fibonacci`.
+    /// If the method was already given a diagram, it will be replaced.
+    pub fn with_method(
+        mut self,
+        service: impl Into<String>,
+        method: impl Into<String>,
+        diagram: Diagram,
+    ) -> Self {
+        self.methods
+            .insert((service.into(), method.into()), diagram);
+        self
+    }
+}
+
+/// Streams that a diagram can use to answer a server-streaming gRPC method.
+#[derive(StreamPack)]
+pub struct GrpcServerStreams {
+    out: JsonMessage,
+}
+
+/// A problem with setting up a gRPC server for diagrams.
+#[derive(ThisError, Debug)]
+pub enum GrpcServerError {
+    #[error("could not find service [{0}] in the descriptor pool")]
+    MissingService(String),
+    #[error("service [{service}] does not have a method with name [{method}]")]
+    MissingMethod { service: String, method: String },
+    #[error("method [{0}] uses client streaming, which is not supported")]
+    UnsupportedMethod(String),
+    #[error("unable to build the diagram for method [{method}]: {error}")]
+    Diagram { method: String, error: DiagramError },
+    #[error("unable to bind the server address: {0}")]
+    Bind(#[from] std::io::Error),
+}
+
+/// Returned by [`ServeGrpcDiagramsExt::serve_grpc_diagrams`].
+pub struct GrpcServerHandle {
+    /// The address that the server is listening on. This is useful when binding
+    /// to port 0.
+    pub addr: SocketAddr,
+    /// The entity that holds the [`GrpcDiagramServer`] component. Despawn it to
+    /// shut down the server along with the workflows of its diagrams.
+    pub server: Entity,
+}
+
+pub trait ServeGrpcDiagramsExt {
+    /// Build every diagram of `config` into a workflow and host a gRPC server
+    /// at `addr` whose methods are answered by those workflows.
+    ///
+    /// The server runs on `runtime`, which must be kept running while the
+    /// server is in use. The calls are passed into the workflows by a system of
+    /// [`CrossflowPlugin`](crate::CrossflowPlugin), so the app must be updated
+    /// for the calls to make progress.
+    fn serve_grpc_diagrams(
+        &mut self,
+        config: GrpcServerConfig,
+        addr: SocketAddr,
+        registry: &DiagramElementRegistry,
+        runtime: &Runtime,
+    ) -> Result<GrpcServerHandle, GrpcServerError>;
+}
+
+impl ServeGrpcDiagramsExt for Commands<'_, '_> {
+    fn serve_grpc_diagrams(
+        &mut self,
+        config: GrpcServerConfig,
+        addr: SocketAddr,
+        registry: &DiagramElementRegistry,
+        runtime: &Runtime,
+    ) -> Result<GrpcServerHandle, GrpcServerError> {
+        let mut methods = Vec::new();
+        for ((service_name, method_name), diagram) in config.methods {
+            let service = config
+                .descriptors
+                .get_service_by_name(&service_name)
+                .ok_or_else(|| GrpcServerError::MissingService(service_name.clone()))?;
+            let method = service
+                .methods()
+                .find(|m| m.name() == method_name)
+                .ok_or_else(|| GrpcServerError::MissingMethod {
+                    service: service_name,
+                    method: method_name,
+                })?;
+
+            if method.is_client_streaming() {
+                return Err(GrpcServerError::UnsupportedMethod(
+                    method.full_name().into(),
+                ));
+            }
+
+            methods.push((method, diagram));
+        }
+
+        // Bind before spawning anything so that address problems are reported
+        // to the caller.
+        let incoming = {
+            let _guard = runtime.enter();
+            TcpIncoming::bind(addr)?
+        };
+        let addr = incoming.local_addr()?;
+
+        let server = self.spawn_empty().id();
+        let (sender, calls) = unbounded_channel();
+        let mut workflows = Vec::new();
+        // The default routes answer unknown methods with an UNIMPLEMENTED status.
+        let mut router = Routes::default().into_axum_router();
+        for (method, diagram) in methods {
+            let workflow = match diagram
+                .spawn_workflow::<JsonMessage, JsonMessage, GrpcServerStreams>(self, registry)
+            {
+                Ok(workflow) => workflow,
+                Err(error) => {
+                    self.entity(server).despawn();
+                    return Err(GrpcServerError::Diagram {
+                        method: method.full_name().into(),
+                        error,
+                    });
+                }
+            };
+            self.entity(workflow.provider()).insert(ChildOf(server));
+
+            let path = format!("/{}/{}", method.parent_service().full_name(), method.name());
+            router = router.route_service(
+                &path,
+                DiagramMethodService::new(&method, workflows.len(), sender.clone()),
+            );
+            workflows.push(workflow);
+        }
+
+        let task = runtime
+            .spawn(
+                Server::builder()
+                    .add_routes(Routes::from(router))
+                    .serve_with_incoming(incoming),
+            )
+            .abort_on_drop();
+
+        self.entity(server).insert(GrpcDiagramServer {
+            calls,
+            workflows,
+            _task: task,
+        });
+
+        Ok(GrpcServerHandle { addr, server })
+    }
+}
+
+/// Passes the calls that arrive at a gRPC server into the workflows of its
+/// diagrams.
+#[derive(Component)]
+pub struct GrpcDiagramServer {
+    calls: UnboundedReceiver<GrpcCall>,
+    workflows: Vec<Service<JsonMessage, JsonMessage, GrpcServerStreams>>,
+    _task: AbortOnDrop<Result<(), tonic::transport::Error>>,
+}
+
+struct GrpcCall {
+    workflow: usize,
+    request: JsonMessage,
+    reply: oneshot::Sender<Capture<JsonMessage, GrpcServerStreams>>,
+}
+
+pub(crate) fn serve_grpc_calls(mut servers: Query<&mut GrpcDiagramServer>, mut commands: Commands) {
+    for mut server in &mut servers {
+        while let Ok(call) = server.calls.try_recv() {
+            let workflow = server.workflows[call.workflow];
+            let capture = commands.request(call.request, workflow).capture();
+            // If the client has already hung up then the capture gets dropped
+            // here, which will cancel the session.
+            let _ = call.reply.send(capture);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct DiagramMethodService {
+    codec: DynamicServiceCodec,
+    is_server_streaming: bool,
+    handler: CallHandler,
+}
+
+impl DiagramMethodService {
+    fn new(method: &MethodDescriptor, workflow: usize, calls: UnboundedSender<GrpcCall>) -> Self {
+        Self {
+            // The codec encodes its input and decodes its output, so these are
+            // swapped compared to a client.
+            codec: DynamicServiceCodec {
+                input: method.output(),
+                output: method.input(),
+            },
+            is_server_streaming: method.is_server_streaming(),
+            handler: CallHandler { workflow, calls },
+        }
+    }
+}
+
+impl TowerService<http::Request<axum::body::Body>> for DiagramMethodService {
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(
+        &mut self,
+        _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<axum::body::Body>) -> Self::Future {
+        let mut grpc = Grpc::new(self.codec.clone());
+        let handler = self.handler.clone();
+        if self.is_server_streaming {
+            Box::pin(async move { Ok(grpc.server_streaming(handler, request).await) })
+        } else {
+            Box::pin(async move { Ok(grpc.unary(handler, request).await) })
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CallHandler {
+    workflow: usize,
+    calls: UnboundedSender<GrpcCall>,
+}
+
+impl CallHandler {
+    async fn start(
+        self,
+        request: Request<JsonMessage>,
+    ) -> Result<Capture<JsonMessage, GrpcServerStreams>, Status> {
+        let (reply, capture) = oneshot::channel();
+        self.calls
+            .send(GrpcCall {
+                workflow: self.workflow,
+                request: request.into_inner(),
+                reply,
+            })
+            .map_err(|_| Status::unavailable("the diagram server has been shut down"))?;
+
+        capture
+            .await
+            .map_err(|_| Status::unavailable("the diagram server has been shut down"))
+    }
+}
+
+type ResponseStream = Pin<Box<dyn FutureStream<Item = Result<JsonMessage, Status>> + Send>>;
+
+impl UnaryService<JsonMessage> for CallHandler {
+    type Response = JsonMessage;
+    type Future = BoxFuture<Response<JsonMessage>, Status>;
+
+    fn call(&mut self, request: Request<JsonMessage>) -> Self::Future {
+        let handler = self.clone();
+        Box::pin(async move {
+            let capture = handler.start(request).await?;
+            capture
+                .outcome
+                .await
+                .map(Response::new)
+                .map_err(|cancellation| Status::new(Code::Aborted, format!("{cancellation}")))
+        })
+    }
+}
+
+impl ServerStreamingService<JsonMessage> for CallHandler {
+    type Response = JsonMessage;
+    type ResponseStream = ResponseStream;
+    type Future = BoxFuture<Response<ResponseStream>, Status>;
+
+    fn call(&mut self, request: Request<JsonMessage>) -> Self::Future {
+        let handler = self.clone();
+        Box::pin(async move {
+            let Capture {
+                outcome, streams, ..
+            } = handler.start(request).await?;
+
+            // The stream closes once the session is finished. After that we
+            // check whether the session was cancelled so we can report it to
+            // the client. If the client hangs up, this stream gets dropped
+            // along with the outcome, which cancels the session.
+            let finish = once(outcome).flat_map(|result| match result {
+                Ok(_) => empty().boxed(),
+                Err(cancellation) => {
+                    once(async move { Err(Status::new(Code::Aborted, format!("{cancellation}"))) })
+                        .boxed()
+                }
+            });
+
+            let stream: ResponseStream = UnboundedReceiverStream::new(streams.out)
+                .map(Ok)
+                .chain(finish)
+                .boxed();
+            Ok(Response::new(stream))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::testing::*;
+    use protos::{FibonacciReply, FibonacciRequest, fibonacci_client::FibonacciClient};
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_serve_unary_diagram() {
+        let mut fixture = DiagramTestFixture::new();
+        let rt = Runtime::new().unwrap();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "order",
+            "ops": {
+                "order": {
+                    "type": "transform",
+                    "cel": "request.order",
+                    "next": "multiply3"
+                },
+                "multiply3": {
+                    "type": "node",
+                    "builder": "multiply3",
+                    "next": "reply"
+                },
+                "reply": {
+                    "type": "transform",
+                    "cel": "{\"value\": request}",
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let config = GrpcServerConfig::new(example_descriptors()).with_method(
+            "example_protos.fibonacci.Fibonacci",
+            "FinalNumber",
+            diagram,
+        );
+
+        let handle = serve(&mut fixture, config, &rt);
+        let addr = handle.addr;
+        let mut reply = rt.spawn(async move {
+            let mut client = FibonacciClient::connect(format!("http://{addr}")).await?;
+            let reply = client.final_number(FibonacciRequest { order: 10 }).await?;
+            // This method was not given a diagram.
+            let unimplemented = client
+                .sequence_stream(FibonacciRequest { order: 10 })
+                .await
+                .err()
+                .map(|status| status.code());
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>((reply.into_inner(), unimplemented))
+        });
+
+        let (reply, unimplemented) = run_until_finished(&mut fixture, &rt, &mut reply).unwrap();
+        assert_eq!(reply, FibonacciReply { value: 30 });
+        assert_eq!(unimplemented, Some(Code::Unimplemented));
+        fixture.context.assert_no_errors();
+    }
+
+    #[test]
+    fn test_serve_server_streaming_diagram() {
+        let mut fixture = DiagramTestFixture::new();
+        let rt = Runtime::new().unwrap();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "fork",
+            "ops": {
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["first", "order"]
+                },
+                "first": {
+                    "type": "transform",
+                    "cel": "{\"value\": 0}",
+                    "next": "out"
+                },
+                "order": {
+                    "type": "transform",
+                    "cel": "{\"value\": request.order}",
+                    "next": "fork_order"
+                },
+                "fork_order": {
+                    "type": "fork_clone",
+                    "next": ["out", "finish"]
+                },
+                "out": {
+                    "type": "stream_out",
+                    "name": "out"
+                },
+                "finish": {
+                    "type": "transform",
+                    "cel": "null",
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let config = GrpcServerConfig::new(example_descriptors()).with_method(
+            "example_protos.fibonacci.Fibonacci",
+            "SequenceStream",
+            diagram,
+        );
+
+        let handle = serve(&mut fixture, config, &rt);
+        let addr = handle.addr;
+        let mut values = rt.spawn(async move {
+            let mut client = FibonacciClient::connect(format!("http://{addr}")).await?;
+            let mut stream = client
+                .sequence_stream(FibonacciRequest { order: 10 })
+                .await?
+                .into_inner();
+            let mut values = Vec::new();
+            while let Some(reply) = stream.message().await? {
+                values.push(reply.value);
+            }
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(values)
+        });
+
+        let mut values = run_until_finished(&mut fixture, &rt, &mut values).unwrap();
+        values.sort();
+        assert_eq!(values, [0, 10]);
+        fixture.context.assert_no_errors();
+    }
+
+    #[test]
+    fn test_reject_client_streaming_method() {
+        let mut fixture = DiagramTestFixture::new();
+        let rt = Runtime::new().unwrap();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": { "builtin": "terminate" },
+            "ops": {}
+        }))
+        .unwrap();
+
+        let config = GrpcServerConfig::new(example_descriptors()).with_method(
+            "example_protos.navigation.Navigation",
+            "Guide",
+            diagram,
+        );
+
+        let result = fixture.context.command(|cmds| {
+            cmds.serve_grpc_diagrams(
+                config,
+                "127.0.0.1:0".parse().unwrap(),
+                &fixture.registry,
+                &rt,
+            )
+        });
+        assert!(matches!(result, Err(GrpcServerError::UnsupportedMethod(_))));
+    }
+
+    fn example_descriptors() -> DescriptorPool {
+        let descriptor_set_bytes =
+            include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin"));
+        DescriptorPool::decode(&descriptor_set_bytes[..]).unwrap()
+    }
+
+    fn serve(
+        fixture: &mut DiagramTestFixture,
+        config: GrpcServerConfig,
+        rt: &Runtime,
+    ) -> GrpcServerHandle {
+        fixture
+            .context
+            .command(|cmds| {
+                cmds.serve_grpc_diagrams(
+                    config,
+                    "127.0.0.1:0".parse().unwrap(),
+                    &fixture.registry,
+                    rt,
+                )
+            })
+            .unwrap()
+    }
+
+    fn run_until_finished<T>(
+        fixture: &mut DiagramTestFixture,
+        rt: &Runtime,
+        task: &mut tokio::task::JoinHandle<T>,
+    ) -> T {
+        let start = Instant::now();
+        while !task.is_finished() {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            fixture.context.run(1);
+        }
+        rt.block_on(task).unwrap()
+    }
+
+    mod protos {
+        include!(concat!(env!("OUT_DIR"), "/example_protos.fibonacci.rs"));
+    }
+}
@@ -0,0 +1,506 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_ecs::prelude::{Commands, Component, Entity, Query, World};
+
+use backtrace::Backtrace;
+use thiserror::Error as ThisError;
+use tracing::{debug, info};
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Async, Builder, Cancellation, MiscellaneousFailure, Scope, Service, SpawnWorkflowExt,
+    UnhandledErrors,
+};
+
+use super::{Diagram, DiagramElementRegistry, DiagramError};
+
+/// A problem with loading the diagram file of a hot reloading diagram service.
+#[derive(ThisError, Debug)]
+pub enum DiagramLoadError {
+    #[error("unable to read the diagram file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unable to parse the diagram file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("unable to build the diagram: {0}")]
+    Build(#[from] DiagramError),
+}
+
+/// Returned by [`SpawnHotReloadDiagramExt::spawn_hot_reload_diagram`].
+pub struct HotReloadService<Request, Response> {
+    /// Send requests to this service. Each request is passed along to whichever
+    /// version of the diagram is the most recent one when the request arrives.
+    /// This service stays the same across reloads.
+    pub service: Service<Request, Response>,
+    /// The entity that holds the [`HotReloadDiagram`] component of this service.
+    pub manager: Entity,
+}
+
+pub trait SpawnHotReloadDiagramExt {
+    /// Spawn a service that runs the diagram in the file at `path` and rebuilds
+    /// it whenever the contents of the file change.
+    ///
+    /// New requests are routed to the latest version of the diagram. Sessions
+    /// that are already running on an older version will finish on that
+    /// version, and then the older version will be despawned. If the new
+    /// contents of the file cannot be loaded, the error is reported to
+    /// [`UnhandledErrors`] and the current version stays in use.
+    ///
+    /// The diagram is built with [`Diagram::spawn_workflow`] and must not have
+    /// any streams. Despawning the provider of the returned service will also
+    /// despawn every version of the diagram.
+    fn spawn_hot_reload_diagram<Request, Response>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        registry: Arc<Mutex<DiagramElementRegistry>>,
+    ) -> Result<HotReloadService<Request, Response>, DiagramLoadError>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync;
+}
+
+impl SpawnHotReloadDiagramExt for Commands<'_, '_> {
+    fn spawn_hot_reload_diagram<Request, Response>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        registry: Arc<Mutex<DiagramElementRegistry>>,
+    ) -> Result<HotReloadService<Request, Response>, DiagramLoadError>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+    {
+        let path = path.into();
+        let contents = std::fs::read(&path)?;
+        let build: BuildDiagramFn = Box::new(|contents, registry, commands| {
+            let diagram = Diagram::from_reader(contents)?;
+            let workflow = diagram.spawn_workflow::<Request, Response, ()>(commands, registry)?;
+            Ok(workflow.provider())
+        });
+        let provider = build(
+            &contents,
+            &registry.lock().unwrap_or_else(PoisonError::into_inner),
+            self,
+        )?;
+
+        let manager = self.spawn_empty().id();
+        let service = self.spawn_io_workflow(
+            move |scope: Scope<Request, Response>, builder: &mut Builder| {
+                scope
+                    .start
+                    .chain(builder)
+                    .map(move |input: Async<Request>| forward_request(input, manager))
+                    .cancel_on_err()
+                    .connect(scope.terminate);
+            },
+        );
+
+        self.entity(manager).insert(HotReloadDiagram {
+            path,
+            registry,
+            build,
+            contents,
+            service: service.provider(),
+            current: DiagramVersion::new(provider),
+            retired: Vec::new(),
+            version: 0,
+            poll_interval: Duration::from_secs(1),
+            last_poll: Instant::now(),
+        });
+
+        Ok(HotReloadService { service, manager })
+    }
+}
+
+type BuildDiagramFn = Box<
+    dyn Fn(&[u8], &DiagramElementRegistry, &mut Commands) -> Result<Entity, DiagramLoadError>
+        + Send
+        + Sync,
+>;
+
+/// Keeps track of the versions of a diagram that was spawned with
+/// [`SpawnHotReloadDiagramExt::spawn_hot_reload_diagram`].
+#[derive(Component)]
+pub struct HotReloadDiagram {
+    path: PathBuf,
+    registry: Arc<Mutex<DiagramElementRegistry>>,
+    build: BuildDiagramFn,
+    /// The file contents that were last loaded, whether or not they could be
+    /// built.
+    contents: Vec<u8>,
+    /// The provider of the service that requests are sent to.
+    service: Entity,
+    current: DiagramVersion,
+    /// Older versions that are waiting for their sessions to finish.
+    retired: Vec<DiagramVersion>,
+    version: usize,
+    poll_interval: Duration,
+    last_poll: Instant,
+}
+
+impl HotReloadDiagram {
+    /// The path of the diagram file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many times the diagram has been reloaded.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// The provider of the workflow that new requests are sent to.
+    pub fn current_workflow(&self) -> Entity {
+        self.current.provider
+    }
+
+    /// The providers of older workflows that still have sessions running.
+    pub fn retired_workflows(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.retired.iter().map(|version| version.provider)
+    }
+
+    /// How often the diagram file is checked for changes. The default is once
+    /// per second.
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+}
+
+struct DiagramVersion {
+    provider: Entity,
+    /// Each session that is running on this version holds a clone of this.
+    in_flight: Arc<()>,
+}
+
+impl DiagramVersion {
+    fn new(provider: Entity) -> Self {
+        Self {
+            provider,
+            in_flight: Arc::new(()),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        Arc::strong_count(&self.in_flight) == 1
+    }
+}
+
+#[derive(ThisError, Debug)]
+enum ForwardError {
+    #[error("the hot reloading diagram service has been despawned")]
+    Despawned,
+    #[error(transparent)]
+    Cancelled(#[from] Cancellation),
+}
+
+async fn forward_request<Request, Response>(
+    input: Async<Request>,
+    manager: Entity,
+) -> Result<Response, ForwardError>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+{
+    let Async {
+        request, channel, ..
+    } = input;
+    // Keep the version alive until its session is finished.
+    let (provider, _in_flight) = channel
+        .world(move |world| {
+            world.get::<HotReloadDiagram>(manager).map(|reload| {
+                (
+                    reload.current.provider,
+                    Arc::clone(&reload.current.in_flight),
+                )
+            })
+        })
+        .await
+        .ok_or(ForwardError::Despawned)?;
+
+    let workflow = Service::<Request, Response>::new(provider);
+    Ok(channel.request_outcome(request, workflow).await?)
+}
+
+pub(crate) fn reload_diagram_services(
+    mut managers: Query<(Entity, &mut HotReloadDiagram)>,
+    mut commands: Commands,
+) {
+    let now = Instant::now();
+    for (manager, mut reload) in &mut managers {
+        let reload = &mut *reload;
+        if commands.get_entity(reload.service).is_err() {
+            // The service is gone, so nothing can use the diagram anymore.
+            for version in reload.retired.iter().chain([&reload.current]) {
+                if let Ok(mut workflow) = commands.get_entity(version.provider) {
+                    workflow.despawn();
+                }
+            }
+            commands.entity(manager).despawn();
+            continue;
+        }
+
+        if now.duration_since(reload.last_poll) >= reload.poll_interval {
+            reload.last_poll = now;
+            match std::fs::read(&reload.path) {
+                Ok(contents) if contents != reload.contents => {
+                    let result = (reload.build)(
+                        &contents,
+                        &reload
+                            .registry
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner),
+                        &mut commands,
+                    );
+                    // Remember the contents even if they are broken so we do
+                    // not keep trying to build them.
+                    reload.contents = contents;
+                    match result {
+                        Ok(provider) => {
+                            let previous = std::mem::replace(
+                                &mut reload.current,
+                                DiagramVersion::new(provider),
+                            );
+                            reload.retired.push(previous);
+                            reload.version += 1;
+                            info!(
+                                "reloaded diagram {} (version {})",
+                                reload.path.display(),
+                                reload.version,
+                            );
+                        }
+                        Err(err) => report_error(err, &reload.path, &mut commands),
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    // Editors often replace the file while saving it, so it
+                    // might briefly be missing. Try again on the next poll.
+                    debug!("unable to read diagram {}: {err}", reload.path.display());
+                }
+            }
+        }
+
+        reload.retired.retain(|version| {
+            if !version.is_idle() {
+                return true;
+            }
+
+            if let Ok(mut workflow) = commands.get_entity(version.provider) {
+                workflow.despawn();
+            }
+            false
+        });
+    }
+}
+
+fn report_error(error: DiagramLoadError, path: &Path, commands: &mut Commands) {
+    let backtrace = Backtrace::new();
+    let error = anyhow::Error::from(error).context(format!(
+        "failed to reload diagram {}, the previous version is still in use",
+        path.display()
+    ));
+    commands.queue(move |world: &mut World| {
+        world
+            .get_resource_or_init::<UnhandledErrors>()
+            .miscellaneous
+            .push(MiscellaneousFailure {
+                error: Arc::new(error),
+                backtrace: Some(backtrace),
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonMessage, RequestExt, diagram::testing::*};
+    use bevy_time::TimeUpdateStrategy;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn write_multiply_diagram(path: &Path, delay: f64, factor: i64) {
+        let diagram = json!({
+            "version": "0.1.0",
+            "start": "delay",
+            "ops": {
+                "delay": {
+                    "type": "delay",
+                    "duration": delay,
+                    "next": "multiply",
+                },
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": factor,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        });
+        std::fs::write(path, serde_json::to_vec(&diagram).unwrap()).unwrap();
+    }
+
+    fn spawn_hot_reload(
+        fixture: &mut DiagramTestFixture,
+        path: &Path,
+    ) -> HotReloadService<JsonMessage, JsonMessage> {
+        let registry = Arc::new(Mutex::new(std::mem::take(&mut fixture.registry)));
+        let hot_reload = fixture
+            .context
+            .command(|commands| commands.spawn_hot_reload_diagram(path, registry))
+            .unwrap();
+        fixture
+            .context
+            .app
+            .world_mut()
+            .get_mut::<HotReloadDiagram>(hot_reload.manager)
+            .unwrap()
+            .set_poll_interval(Duration::ZERO);
+        hot_reload
+    }
+
+    #[test]
+    fn test_hot_reload_diagram() {
+        let mut fixture = DiagramTestFixture::new();
+        fixture
+            .context
+            .app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("diagram.json");
+        write_multiply_diagram(&path, 1.0, 3);
+        let HotReloadService { service, manager } = spawn_hot_reload(&mut fixture, &path);
+        let first_workflow = fixture
+            .context
+            .app
+            .world()
+            .get::<HotReloadDiagram>(manager)
+            .unwrap()
+            .current_workflow();
+
+        // This session will still be waiting on the delay when the diagram
+        // gets reloaded.
+        let mut first = fixture
+            .context
+            .command(|commands| commands.request(JsonMessage::from(4), service).outcome());
+        fixture.context.run(2);
+        assert!(first.is_pending());
+
+        write_multiply_diagram(&path, 0.0, 10);
+        fixture.context.run(1);
+        let reload = fixture
+            .context
+            .app
+            .world()
+            .get::<HotReloadDiagram>(manager)
+            .unwrap();
+        assert_eq!(reload.version(), 1);
+        assert_ne!(reload.current_workflow(), first_workflow);
+        assert_eq!(
+            reload.retired_workflows().collect::<Vec<_>>(),
+            [first_workflow]
+        );
+
+        let result: JsonMessage = fixture
+            .context
+            .try_resolve_request(JsonMessage::from(4), service, 100)
+            .unwrap();
+        assert_eq!(result, 40);
+
+        fixture.context.run_with_conditions(&mut first, 100);
+        assert_eq!(first.try_recv().unwrap().unwrap(), 12);
+
+        // The old version is despawned once its session is finished.
+        fixture.context.run(2);
+        let reload = fixture
+            .context
+            .app
+            .world()
+            .get::<HotReloadDiagram>(manager)
+            .unwrap();
+        assert_eq!(reload.retired_workflows().count(), 0);
+        assert!(
+            fixture
+                .context
+                .app
+                .world()
+                .get_entity(first_workflow)
+                .is_err()
+        );
+        assert!(fixture.context.no_unhandled_errors());
+    }
+
+    #[test]
+    fn test_hot_reload_keeps_version_when_reload_fails() {
+        let mut fixture = DiagramTestFixture::new();
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("diagram.json");
+        write_multiply_diagram(&path, 0.0, 3);
+        let HotReloadService { service, manager } = spawn_hot_reload(&mut fixture, &path);
+
+        std::fs::write(&path, "{ not a diagram").unwrap();
+        fixture.context.run(1);
+        assert!(!fixture.context.no_unhandled_errors());
+        assert_eq!(
+            fixture
+                .context
+                .app
+                .world()
+                .get::<HotReloadDiagram>(manager)
+                .unwrap()
+                .version(),
+            0
+        );
+
+        let mut outcome = fixture
+            .context
+            .command(|commands| commands.request(JsonMessage::from(4), service).outcome());
+        fixture.context.run_with_conditions(&mut outcome, 100);
+        assert_eq!(outcome.try_recv().unwrap().unwrap(), 12);
+    }
+
+    #[test]
+    fn test_hot_reload_cleans_up_after_service() {
+        let mut fixture = DiagramTestFixture::new();
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("diagram.json");
+        write_multiply_diagram(&path, 0.0, 3);
+        let HotReloadService { service, manager } = spawn_hot_reload(&mut fixture, &path);
+        let workflow = fixture
+            .context
+            .app
+            .world()
+            .get::<HotReloadDiagram>(manager)
+            .unwrap()
+            .current_workflow();
+
+        fixture.context.app.world_mut().despawn(service.provider());
+        fixture.context.run(1);
+        let world = fixture.context.app.world();
+        assert!(world.get_entity(manager).is_err());
+        assert!(world.get_entity(workflow).is_err());
+    }
+}
@@ -28,10 +28,11 @@ use std::{
 
 use crate::{
     BufferMapLayoutHints, BufferSelection, BuildDiagramOperation, Diagram, DiagramContext,
-    DiagramElementRegistry, DiagramError, DiagramErrorCode, IdentifierRef, IncompatibleLayout,
-    MetadataAccess, NamedOutputRef, NamespaceList, NamespacedOperation, NextOperation, NodeSchema,
-    OperationName, OperationRef, Operations, OutputRef, ScopeSchema, ScriptSchema, SectionError,
-    SectionProvider, SectionSchema, StreamAvailability, StreamPack, WithContext, output_ref,
+    DiagramElementRegistry, DiagramError, DiagramErrorCode, DiagramOperation, IdentifierRef,
+    IncompatibleLayout, JsonMessage, MetadataAccess, NamedOutputRef, NamespaceList,
+    NamespacedOperation, NextOperation, NodeSchema, OperationName, OperationRef, Operations,
+    OutputRef, ScopeSchema, ScriptSchema, SectionError, SectionProvider, SectionSchema,
+    StreamAvailability, StreamPack, WithContext, output_ref,
 };
 
 pub type InferredMessageTypes = HashMap<PortRef, usize>;
@@ -88,6 +89,15 @@ impl InferenceBoundaryConditions {
             streams,
         })
     }
+
+    /// Boundary conditions for running `diagram` with [`JsonMessage`] for its
+    /// request, its response, and each stream that its root scope outputs.
+    pub fn json_diagram(
+        lookup: &dyn MetadataAccess,
+        diagram: &Diagram,
+    ) -> Result<Self, DiagramErrorCode> {
+        Self::json_messages(lookup, diagram.root_stream_names())
+    }
 }
 
 impl Diagram {
@@ -124,6 +134,90 @@ impl Diagram {
         Ok(inferred)
     }
 
+    /// Check the whole diagram and report every error that can be found,
+    /// instead of stopping at the first one like [`Self::infer_message_types`].
+    ///
+    /// Each error carries the port where it was found, when that is known.
+    /// Ports whose message type cannot be determined are reported as
+    /// [`DiagramErrorCode::CannotInferType`]. This only checks what can be
+    /// known without building the workflow. Use [`Self::trial_build`] to also
+    /// find errors from node builders, e.g. a node builder rejecting its config.
+    pub fn validate(
+        &self,
+        lookup: &dyn MetadataAccess,
+        boundary: InferenceBoundaryConditions,
+    ) -> Vec<DiagramError> {
+        let mut errors: Vec<DiagramError> = [
+            self.validate_operation_names(),
+            self.validate_template_usage(),
+        ]
+        .into_iter()
+        .filter_map(|result| result.err().map(Into::into))
+        .collect();
+        // Invalid names or templates can lead to more errors during inference,
+        // but keep going so that every other error gets reported too.
+        let has_invalid_names = !errors.is_empty();
+
+        match self.evaluate_inferences_with(
+            lookup,
+            Some(boundary),
+            &mut ErrorSink::Collect(&mut errors),
+        ) {
+            Ok(inferences) if !has_invalid_names => {
+                let mut unresolved: Vec<_> = inferences
+                    .evaluations
+                    .iter()
+                    .filter(|(_, evaluation)| evaluation.message_type.is_none())
+                    .map(|(port, _)| port)
+                    .filter(|port| !errors.iter().any(|err| err.context.port() == Some(*port)))
+                    .collect();
+                unresolved.sort();
+                for port in unresolved {
+                    errors.push(DiagramError::in_port(
+                        port.clone(),
+                        DiagramErrorCode::CannotInferType(port.clone()),
+                    ));
+                }
+            }
+            // Ports that could not be resolved are not worth reporting when
+            // the names were invalid, since those are likely the cause.
+            Ok(_) => {}
+            Err(err) => errors.push(err),
+        }
+
+        // An operation can be evaluated more than once, which may find the
+        // same error each time.
+        let mut seen = HashSet::new();
+        errors.retain(|err| seen.insert(err.to_string()));
+        errors
+    }
+
+    /// Report every error that would keep this diagram from running as a
+    /// workflow of [`JsonMessage`] requests, responses and streams, the way
+    /// [`Self::spawn_io_workflow`] runs it. This combines [`Self::validate`]
+    /// with [`Self::trial_build`].
+    pub fn validate_json(&self, registry: &DiagramElementRegistry) -> Vec<DiagramError> {
+        let boundary = match InferenceBoundaryConditions::json_diagram(registry, self) {
+            Ok(boundary) => boundary,
+            Err(err) => return vec![err.into()],
+        };
+
+        let mut errors = self.validate(registry, boundary);
+        self.trial_build::<JsonMessage, JsonMessage, ()>(registry, &mut errors);
+        errors
+    }
+
+    /// Names of the streams that the root scope of this diagram outputs.
+    pub fn root_stream_names(&self) -> Vec<String> {
+        self.ops
+            .values()
+            .filter_map(|op| match op.as_ref() {
+                DiagramOperation::StreamOut(stream_out) => Some(stream_out.name.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn evaluate_message_type_inferences(
         &self,
         lookup: &dyn MetadataAccess,
@@ -131,7 +225,85 @@ impl Diagram {
     ) -> Result<Inferences, DiagramError> {
         self.validate_operation_names()?;
         self.validate_template_usage()?;
+        self.evaluate_inferences_with(lookup, Some(boundary), &mut ErrorSink::FailFast)
+    }
+
+    /// Infer message types without fixing the request, response, or stream
+    /// types of the workflow. The terminate operations take the message type
+    /// of the outputs that connect to them. This is used for code generation,
+    /// where the boundary types come out of the diagram instead of going in.
+    pub(crate) fn infer_message_types_with_open_boundary(
+        &self,
+        lookup: &dyn MetadataAccess,
+    ) -> Result<InferredMessageTypes, DiagramError> {
+        self.validate_operation_names()?;
+        self.validate_template_usage()?;
+        let inferences = self.evaluate_inferences_with(lookup, None, &mut ErrorSink::FailFast)?;
+
+        let mut inferred: InferredMessageTypes = inferences
+            .evaluations
+            .iter()
+            .filter_map(|(port, evaluation)| Some((port.clone(), evaluation.message_type?)))
+            .collect();
+
+        let mut terminates: Vec<&OperationRef> = inferences
+            .connections_into
+            .keys()
+            .chain(inferences.redirected_input.keys())
+            .chain(inferences.redirected_input.values())
+            .filter(|op| matches!(op, OperationRef::Terminate(_)))
+            .collect();
+        terminates.sort();
+        terminates.dedup();
+
+        // Keep passing over the terminate operations until none of them
+        // change, because the terminate of a scope may redirect into the
+        // terminate of its parent.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for terminate in &terminates {
+                let port = PortRef::from((*terminate).clone());
+                if inferred.contains_key(&port) {
+                    continue;
+                }
 
+                let mut incoming: Vec<PortRef> = inferences
+                    .connections_into
+                    .get(*terminate)
+                    .into_iter()
+                    .flatten()
+                    .map(|output| output.clone().into())
+                    .chain(
+                        inferences
+                            .redirections_into
+                            .get(*terminate)
+                            .into_iter()
+                            .flatten()
+                            .chain(inferences.redirected_input.get(*terminate))
+                            .map(|input| input.clone().into()),
+                    )
+                    .collect();
+                incoming.sort();
+
+                if let Some(message_type) =
+                    incoming.iter().find_map(|port| inferred.get(port).copied())
+                {
+                    inferred.insert(port, message_type);
+                    changed = true;
+                }
+            }
+        }
+
+        Ok(inferred)
+    }
+
+    fn evaluate_inferences_with(
+        &self,
+        lookup: &dyn MetadataAccess,
+        boundary: Option<InferenceBoundaryConditions>,
+        errors: &mut ErrorSink,
+    ) -> Result<Inferences, DiagramError> {
         let root_on_implicit_error: OperationRef = (&self.on_implicit_error()).into();
 
         let mut inferences = Inferences::default();
@@ -166,19 +338,22 @@ impl Diagram {
                     generated_operations: &mut generated_operations,
                 };
 
-                unfinished
-                    .op
-                    .apply_message_type_constraints(&unfinished.id, &mut ctx)
-                    .in_port(|| {
-                        OperationRef::from(&unfinished.id).in_namespaces(&unfinished.namespaces)
-                    })?;
+                errors.check(
+                    unfinished
+                        .op
+                        .apply_message_type_constraints(&unfinished.id, &mut ctx)
+                        .in_port(|| {
+                            OperationRef::from(&unfinished.id).in_namespaces(&unfinished.namespaces)
+                        }),
+                )?;
             }
 
             unfinished_operations.extend(generated_operations.drain(..));
         }
 
         // Test for circular redirections, which are impossible to solve for and
-        // also logically unsound.
+        // also logically unsound. This is always fatal because evaluating the
+        // remaining constraints would never finish.
         for redirect_from in inferences.redirected_input.keys() {
             let mut next = Some(redirect_from);
             let mut visited = Vec::new();
@@ -221,23 +396,32 @@ impl Diagram {
             queue.push_back(port.clone());
         }
 
-        set_boundary_conditions(&mut inferences, self, lookup, boundary)?;
+        errors.check(set_boundary_conditions(
+            &mut inferences,
+            self,
+            lookup,
+            boundary,
+        ))?;
 
         while let Some(port) = queue.pop_front() {
-            let evaluation = inferences.get_evaluation(&port).in_port(|| port.clone())?;
-            if let Some(message_type) = evaluation
-                .evaluate(&inferences, lookup)
-                .in_port(|| port.clone())?
+            let Some(evaluation) =
+                errors.check(inferences.get_evaluation(&port).in_port(|| port.clone()))?
+            else {
+                continue;
+            };
+            if let Some(Some(message_type)) = errors.check(
+                evaluation
+                    .evaluate(&inferences, lookup)
+                    .in_port(|| port.clone()),
+            )? && Some(message_type) != evaluation.message_type
             {
-                if Some(message_type) != evaluation.message_type {
-                    // A new message type was determined for this port, so update
-                    // it and notify all dependents.
-                    inferences.evaluation(port.clone()).message_type = Some(message_type);
-                    if let Some(deps) = dependents.get(&port) {
-                        for dep in deps {
-                            if !queue.contains(&dep) {
-                                queue.push_back(dep.clone());
-                            }
+                // A new message type was determined for this port, so update
+                // it and notify all dependents.
+                inferences.evaluation(port.clone()).message_type = Some(message_type);
+                if let Some(deps) = dependents.get(&port) {
+                    for dep in deps {
+                        if !queue.contains(&dep) {
+                            queue.push_back(dep.clone());
                         }
                     }
                 }
@@ -248,11 +432,39 @@ impl Diagram {
     }
 }
 
+/// What to do with errors that are found while evaluating inferences.
+enum ErrorSink<'a> {
+    /// Stop at the first error.
+    FailFast,
+    /// Record every error and keep going.
+    Collect(&'a mut Vec<DiagramError>),
+}
+
+impl ErrorSink<'_> {
+    /// Pass along the value of a successful result. For a failed result this
+    /// either returns the error or records it and gives back [`None`].
+    fn check<T, E: Into<DiagramError>>(
+        &mut self,
+        result: Result<T, E>,
+    ) -> Result<Option<T>, DiagramError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => match self {
+                Self::FailFast => Err(err.into()),
+                Self::Collect(errors) => {
+                    errors.push(err.into());
+                    Ok(None)
+                }
+            },
+        }
+    }
+}
+
 fn set_boundary_conditions(
     inferences: &mut Inferences,
     diagram: &Diagram,
     registry: &dyn MetadataAccess,
-    boundary: InferenceBoundaryConditions,
+    boundary: Option<InferenceBoundaryConditions>,
 ) -> Result<(), DiagramErrorCode> {
     let root_on_implicit_error: OperationRef = (&diagram.on_implicit_error()).into();
     let mut generated_operations = Vec::new();
@@ -274,6 +486,12 @@ fn set_boundary_conditions(
     // Add constraints for the start operation
     ctx.connect(OutputRef::start(), ctx.into_operation_ref(&diagram.start));
     let start = ctx.into_port_ref(OutputRef::start());
+    let Some(boundary) = boundary else {
+        // Without boundary conditions, the message types of the start and
+        // terminate operations are decided by the operations they connect to.
+        ctx.inference.evaluation(start);
+        return Ok(());
+    };
     ctx.fixed(start, boundary.request);
 
     // Add constraints for the terminate operation
@@ -511,6 +729,33 @@ impl<'a, 'b> InferenceContext<'a, 'b> {
         Ok(())
     }
 
+    pub fn switch(
+        &mut self,
+        operation_name: &OperationName,
+        cases: &[&NextOperation],
+        default: Option<&NextOperation>,
+    ) -> Result<(), DiagramErrorCode> {
+        let json_message_index = self.metadata.json_message_index()?;
+        let operation = self.into_operation_ref(operation_name);
+        self.fixed(operation.into(), json_message_index);
+
+        for (i, next) in cases.iter().enumerate() {
+            let output = self.into_output_ref(output_ref(operation_name).next_index(i));
+            let target = self.into_operation_ref(*next);
+            self.fixed(output.clone().into(), json_message_index);
+            self.connect(output, target);
+        }
+
+        if let Some(default) = default {
+            let output = self.into_output_ref(output_ref(operation_name).default());
+            let target = self.into_operation_ref(default);
+            self.fixed(output.clone().into(), json_message_index);
+            self.connect(output, target);
+        }
+
+        Ok(())
+    }
+
     pub fn stream_out(&mut self, operation_name: &OperationName, stream_name: &OperationName) {
         let operation = self.into_operation_ref(operation_name);
         let stream = self.into_operation_ref(OperationRef::stream_out(stream_name));
@@ -536,6 +781,39 @@ impl<'a, 'b> InferenceContext<'a, 'b> {
             .constrain(operation.clone(), CloneInput { operation, targets });
     }
 
+    /// Specify that an operation passes along the same message type that it
+    /// receives, e.g. the gate and trim operations.
+    pub fn pass_through(&mut self, operation_name: &OperationName, next: &NextOperation) {
+        let operation = self.into_operation_ref(operation_name);
+        let output = self.into_output_ref(output_ref(operation_name).next());
+        let target = self.into_operation_ref(next);
+
+        self.connect(output.clone(), target.clone());
+        self.inference
+            .constrain(output, ExactMatch(operation.clone().into()));
+        self.inference
+            .constrain(operation.clone(), PassThroughInput { operation, target });
+    }
+
+    /// A timeout passes its input along to `next` like [`Self::pass_through`],
+    /// and sends a trigger to `on_timeout`.
+    pub fn timeout(
+        &mut self,
+        operation_name: &OperationName,
+        next: &NextOperation,
+        on_timeout: &NextOperation,
+    ) -> Result<(), DiagramErrorCode> {
+        self.pass_through(operation_name, next);
+
+        let unit_message_index = self.metadata.unit_message_index()?;
+        let output = self.into_output_ref(output_ref(operation_name).on_timeout());
+        let target = self.into_operation_ref(on_timeout);
+        self.fixed(output.clone().into(), unit_message_index);
+        self.connect(output, target);
+
+        Ok(())
+    }
+
     pub fn result(
         &mut self,
         operation_name: &OperationName,
@@ -755,6 +1033,37 @@ impl<'a, 'b> InferenceContext<'a, 'b> {
         self.inference.constrain(split.clone(), SplitInput(split));
     }
 
+    pub fn spread(&mut self, spread_name: &OperationName, next: &NextOperation) {
+        let spread = self.into_operation_ref(spread_name);
+        let output = self.into_output_ref(output_ref(spread_name).next());
+        let target = self.into_operation_ref(next);
+
+        self.inference
+            .constrain(output.clone(), SpreadOutput(spread.clone()));
+        self.inference
+            .constrain(spread.clone(), SpreadInput(spread));
+
+        self.connect(output, target);
+    }
+
+    pub fn collect(&mut self, collect_name: &OperationName, next: &NextOperation) {
+        let collect = self.into_operation_ref(collect_name);
+        let output = self.into_output_ref(output_ref(collect_name).next());
+        let target = self.into_operation_ref(next);
+
+        self.inference
+            .constrain(output.clone(), CollectOutput(collect.clone()));
+        self.inference.constrain(
+            collect.clone(),
+            CollectInput {
+                operation: collect,
+                target: target.clone(),
+            },
+        );
+
+        self.connect(output, target);
+    }
+
     pub fn script(
         &mut self,
         operation_name: &OperationName,
@@ -905,6 +1214,28 @@ impl<'a> ConstraintContext<'a> {
         &self,
         operation: &OperationRef,
         targets: &[OperationRef],
+    ) -> MessageTypeEvaluation {
+        let Some(selected_input_type) = self.evaluate_pass_through_input(operation, targets)?
+        else {
+            return Ok(None);
+        };
+
+        if !self.metadata.can_clone(selected_input_type)? {
+            return Err(DiagramErrorCode::NotCloneable(
+                self.type_name_for(selected_input_type)?,
+            ));
+        }
+
+        Ok(Some(selected_input_type))
+    }
+
+    /// Choose the input message type of an operation whose outputs pass along
+    /// the same message type that it receives, e.g. fork_clone or the gate
+    /// operations.
+    pub fn evaluate_pass_through_input(
+        &self,
+        operation: &OperationRef,
+        targets: &[OperationRef],
     ) -> MessageTypeEvaluation {
         let incoming_message_types = self.get_message_types_into(operation)?;
         let selected_input_type = if incoming_message_types.is_empty() {
@@ -965,12 +1296,6 @@ impl<'a> ConstraintContext<'a> {
             }
         };
 
-        if !self.metadata.can_clone(selected_input_type)? {
-            return Err(DiagramErrorCode::NotCloneable(
-                self.type_name_for(selected_input_type)?,
-            ));
-        }
-
         Ok(Some(selected_input_type))
     }
 
@@ -1239,6 +1564,132 @@ impl<'a> ConstraintContext<'a> {
         Ok(Some(output_type))
     }
 
+    pub fn evaluate_spread_input(&self, operation: &OperationRef) -> MessageTypeEvaluation {
+        let incoming_message_types = self.get_message_types_into(operation)?;
+        if let [incoming_message_type] = incoming_message_types.as_slice() {
+            let incoming_message_type = *incoming_message_type;
+            if self.metadata.can_spread(incoming_message_type)? {
+                return Ok(Some(incoming_message_type));
+            }
+
+            if !self.metadata.can_seralize(incoming_message_type)? {
+                // The message cannot be spread and cannot be serialized, so
+                // it is not a valid choice for the spread operation.
+                return Err(DiagramErrorCode::NotSpreadable(
+                    self.type_name_for(incoming_message_type)?,
+                ));
+            }
+
+            // The message cannot be spread but it can be serialized, so we
+            // should change it to a JsonMessage.
+            let Ok(json_index) = self.metadata.json_message_index() else {
+                return Err(DiagramErrorCode::NotSpreadable(
+                    self.type_name_for(incoming_message_type)?,
+                ));
+            };
+
+            return Ok(Some(json_index));
+        }
+
+        if incoming_message_types.is_empty() {
+            return Ok(None);
+        }
+
+        let Ok(json_index) = self.metadata.json_message_index() else {
+            return Err(DiagramErrorCode::AmbiguousMessageType(
+                self.type_info_for_slice(&incoming_message_types)?,
+            ));
+        };
+
+        // Check if all incoming messages can be serialized. If so, we can funnel
+        // them into a JsonMessage before spreading.
+        for incoming_message_type in &incoming_message_types {
+            if !self.metadata.can_seralize(*incoming_message_type)? {
+                return Err(DiagramErrorCode::AmbiguousMessageType(
+                    self.type_info_for_slice(&incoming_message_types)?,
+                ));
+            }
+        }
+
+        Ok(Some(json_index))
+    }
+
+    pub fn evaluate_spread_output(&self, operation: &OperationRef) -> MessageTypeEvaluation {
+        let Some(spread_inference) = self.get_inference_of(operation.clone())? else {
+            return Ok(None);
+        };
+
+        let output_type = self.metadata.spread_output_type(*spread_inference)?;
+        Ok(Some(output_type))
+    }
+
+    pub fn evaluate_collect_input(
+        &self,
+        operation: &OperationRef,
+        target: &OperationRef,
+    ) -> MessageTypeEvaluation {
+        let incoming_message_types = self.get_message_types_into(operation)?;
+        if incoming_message_types.is_empty() {
+            // We don't have any upstream hints for the message type, so check
+            // if the target is expecting a collection of some known item type.
+            let Some(target_inference) = self.get_inference_of(target.clone())? else {
+                return Ok(None);
+            };
+
+            return Ok(self
+                .metadata
+                .reverse_lookup()
+                .collect
+                .get(target_inference)
+                .copied());
+        }
+
+        if let [incoming_message_type] = incoming_message_types.as_slice() {
+            let incoming_message_type = *incoming_message_type;
+            if self.metadata.can_collect(incoming_message_type)? {
+                return Ok(Some(incoming_message_type));
+            }
+
+            if !self.metadata.can_seralize(incoming_message_type)? {
+                // The message cannot be collected and cannot be serialized, so
+                // it is not a valid choice for the collect operation.
+                return Err(DiagramErrorCode::NotCollectable(
+                    self.type_name_for(incoming_message_type)?,
+                ));
+            }
+        } else {
+            // Check if all incoming messages can be serialized. If so, we can
+            // funnel them into a JsonMessage before collecting.
+            for incoming_message_type in &incoming_message_types {
+                if !self.metadata.can_seralize(*incoming_message_type)? {
+                    return Err(DiagramErrorCode::AmbiguousMessageType(
+                        self.type_info_for_slice(&incoming_message_types)?,
+                    ));
+                }
+            }
+        }
+
+        // The incoming messages can all be serialized, so we will collect them
+        // as JsonMessages.
+        let json_index = self.metadata.json_message_index()?;
+        if !self.metadata.can_collect(json_index)? {
+            return Err(DiagramErrorCode::NotCollectable(
+                self.type_name_for(json_index)?,
+            ));
+        }
+
+        Ok(Some(json_index))
+    }
+
+    pub fn evaluate_collect_output(&self, operation: &OperationRef) -> MessageTypeEvaluation {
+        let Some(collect_inference) = self.get_inference_of(operation.clone())? else {
+            return Ok(None);
+        };
+
+        let output_type = self.metadata.collect_output_type(*collect_inference)?;
+        Ok(Some(output_type))
+    }
+
     pub fn type_name_for(&self, index: usize) -> Result<Cow<'static, str>, DiagramErrorCode> {
         Ok(Cow::Owned(
             self.metadata.message_type_name(index)?.to_owned(),
@@ -1518,6 +1969,27 @@ impl MessageTypeConstraint for CloneInput {
     }
 }
 
+#[derive(Debug)]
+struct PassThroughInput {
+    operation: OperationRef,
+    target: OperationRef,
+}
+
+impl MessageTypeConstraint for PassThroughInput {
+    fn dependencies(&self, context: &ConstraintContext) -> SmallVec<[PortRef; 8]> {
+        context
+            .connections_into(&self.operation)
+            .into_iter()
+            .map(Into::into)
+            .chain([self.target.clone().into()])
+            .collect()
+    }
+
+    fn evaluate(&self, context: &ConstraintContext) -> MessageTypeEvaluation {
+        context.evaluate_pass_through_input(&self.operation, std::slice::from_ref(&self.target))
+    }
+}
+
 #[derive(Debug)]
 struct ResultInto {
     operation: OperationRef,
@@ -1670,12 +2142,76 @@ impl MessageTypeConstraint for SplitOutput {
     }
 }
 
+#[derive(Debug)]
+struct SpreadInput(OperationRef);
+
+impl MessageTypeConstraint for SpreadInput {
+    fn dependencies(&self, context: &ConstraintContext) -> SmallVec<[PortRef; 8]> {
+        context
+            .connections_into(&self.0)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    fn evaluate(&self, context: &ConstraintContext) -> MessageTypeEvaluation {
+        context.evaluate_spread_input(&self.0)
+    }
+}
+
+#[derive(Debug)]
+struct SpreadOutput(OperationRef);
+
+impl MessageTypeConstraint for SpreadOutput {
+    fn dependencies(&self, _: &ConstraintContext) -> SmallVec<[PortRef; 8]> {
+        smallvec![self.0.clone().into()]
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> MessageTypeEvaluation {
+        ctx.evaluate_spread_output(&self.0)
+    }
+}
+
+#[derive(Debug)]
+struct CollectInput {
+    operation: OperationRef,
+    target: OperationRef,
+}
+
+impl MessageTypeConstraint for CollectInput {
+    fn dependencies(&self, context: &ConstraintContext) -> SmallVec<[PortRef; 8]> {
+        context
+            .connections_into(&self.operation)
+            .into_iter()
+            .map(Into::into)
+            .chain([self.target.clone().into()])
+            .collect()
+    }
+
+    fn evaluate(&self, context: &ConstraintContext) -> MessageTypeEvaluation {
+        context.evaluate_collect_input(&self.operation, &self.target)
+    }
+}
+
+#[derive(Debug)]
+struct CollectOutput(OperationRef);
+
+impl MessageTypeConstraint for CollectOutput {
+    fn dependencies(&self, _: &ConstraintContext) -> SmallVec<[PortRef; 8]> {
+        smallvec![self.0.clone().into()]
+    }
+
+    fn evaluate(&self, ctx: &ConstraintContext) -> MessageTypeEvaluation {
+        ctx.evaluate_collect_output(&self.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::InferenceBoundaryConditions;
     use crate::{
-        MessageRegistrations, NextOperation, OperationRef, OutputRef, PortRef, diagram::testing::*,
-        output_ref, prelude::*,
+        DiagramErrorCode, MessageRegistrations, NextOperation, OperationRef, OutputRef, PortRef,
+        diagram::testing::*, output_ref, prelude::*,
     };
     use serde_json::json;
     use std::collections::HashMap;
@@ -2023,6 +2559,174 @@ mod tests {
         assert!(!inference.contains_key(&unfinished_input));
     }
 
+    #[test]
+    fn validate_reports_every_error() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "first",
+            "ops": {
+                "first": {
+                    "type": "node",
+                    "builder": "missing_builder",
+                    "next": "second"
+                },
+                "second": {
+                    "type": "node",
+                    "builder": "another_missing_builder",
+                    "next": { "builtin": "terminate" }
+                },
+                "unfinished": {
+                    "type": "buffer"
+                }
+            }
+        }))
+        .unwrap();
+
+        let errors = diagram.validate(
+            &fixture.registry,
+            InferenceBoundaryConditions::json_messages(&fixture.registry, []).unwrap(),
+        );
+
+        let first: PortRef = (&NextOperation::Name("first".into())).into();
+        let second: PortRef = (&NextOperation::Name("second".into())).into();
+        let unfinished: PortRef = (&NextOperation::Name("unfinished".into())).into();
+        for port in [&first, &second] {
+            assert!(errors.iter().any(|err| {
+                err.context.port() == Some(port)
+                    && matches!(err.code, DiagramErrorCode::BuilderNotFound(_))
+            }));
+        }
+        assert!(errors.iter().any(|err| {
+            matches!(&err.code, DiagramErrorCode::CannotInferType(port) if *port == unfinished)
+        }));
+    }
+
+    #[test]
+    fn validate_accepts_valid_diagram() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "source",
+            "ops": {
+                "source": {
+                    "type": "node",
+                    "builder": "add",
+                    "next": "target"
+                },
+                "target": {
+                    "type": "node",
+                    "builder": "mul",
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let errors = diagram.validate(
+            &fixture.registry,
+            InferenceBoundaryConditions::json_messages(&fixture.registry, []).unwrap(),
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn validate_keeps_going_after_invalid_names() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "builtin",
+            "ops": {
+                "builtin": {
+                    "type": "node",
+                    "builder": "add",
+                    "next": "second"
+                },
+                "second": {
+                    "type": "node",
+                    "builder": "missing_builder",
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let errors = diagram.validate(
+            &fixture.registry,
+            InferenceBoundaryConditions::json_messages(&fixture.registry, []).unwrap(),
+        );
+
+        assert!(
+            errors
+                .iter()
+                .any(|err| matches!(err.code, DiagramErrorCode::InvalidUseOfReservedName(_)))
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|err| matches!(err.code, DiagramErrorCode::BuilderNotFound(_)))
+        );
+    }
+
+    #[test]
+    fn trial_build_reports_every_builder_error() {
+        let fixture = DiagramTestFixture::new();
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "first",
+            "ops": {
+                "first": {
+                    "type": "node",
+                    "builder": "add_to",
+                    "config": "not a number",
+                    "next": "second"
+                },
+                "second": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": [1, 2],
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let mut errors = diagram.validate(
+            &fixture.registry,
+            InferenceBoundaryConditions::json_messages(&fixture.registry, []).unwrap(),
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+
+        diagram.trial_build::<JsonMessage, JsonMessage, ()>(&fixture.registry, &mut errors);
+        for name in ["first", "second"] {
+            let port: PortRef = (&NextOperation::Name(name.into())).into();
+            assert!(
+                errors.iter().any(|err| {
+                    err.context.port() == Some(&port)
+                        && matches!(err.code, DiagramErrorCode::ConfigError(_))
+                }),
+                "{errors:?}",
+            );
+        }
+
+        let valid = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "add",
+            "ops": {
+                "add": {
+                    "type": "node",
+                    "builder": "add_to",
+                    "config": 5,
+                    "next": { "builtin": "terminate" }
+                }
+            }
+        }))
+        .unwrap();
+        let mut errors = Vec::new();
+        valid.trial_build::<JsonMessage, JsonMessage, ()>(&fixture.registry, &mut errors);
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
     // TODO(@mxgrey): Add tests with sections and scopes to validate type inference
     // inside namespaces.
 }
@@ -87,6 +87,22 @@ impl BuildDiagramOperation for NodeSchema {
         ctx: &mut BuilderContext,
     ) -> Result<BuildStatus, DiagramErrorCode> {
         let node_registration = ctx.registry.get_node_registration(&self.builder)?;
+
+        #[cfg(feature = "trace")]
+        if let Some(replay) = ctx.replay() {
+            let operation = ctx.into_operation_ref(id);
+            if let Some((input, output)) =
+                replay.create_node(operation, node_registration.metadata(), ctx)?
+            {
+                // Stream outputs are not replayed, so whatever they connect to
+                // will simply never receive anything.
+                let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+                ctx.set_input_for_target(id, input, trace)?;
+                ctx.add_output_into_target(&self.next, output);
+                return Ok(BuildStatus::Finished);
+            }
+        }
+
         let mut node = node_registration.create_node(ctx.builder, (*self.config).clone())?;
 
         let trace = TraceInfo::new(self, self.trace_settings.trace)?;
@@ -122,6 +122,14 @@ impl NamedOutputBuilder {
         self.key(OutputKey(smallvec!["remaining".into()]))
     }
 
+    pub fn default(self) -> NamedOutputRef {
+        self.key(["default"])
+    }
+
+    pub fn on_timeout(self) -> NamedOutputRef {
+        self.key(["on_timeout"])
+    }
+
     pub fn section_output(self, output: &dyn Borrow<str>) -> NamedOutputRef {
         self.key(OutputKey(smallvec![
             "connect".into(),
@@ -31,14 +31,15 @@ use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings, js
 use serde::{Deserialize, Serialize, de::DeserializeOwned, ser::SerializeMap};
 
 use super::{
-    BuilderId, DeserializeMessage, DiagramErrorCode, DynForkClone, DynForkResult, DynSplit,
-    DynType, JsonRegistration, OperationName, RegisterJson, RegisterSplit, Script,
-    ScriptEnvironment, Section, SectionInterface, SectionInterfaceDescription, SerializeMessage,
-    SplitSchema, TransformError, TypeInfo,
+    BuilderId, CollectSchema, DeserializeMessage, DiagramErrorCode, DynForkClone, DynForkResult,
+    DynSplit, DynType, JsonRegistration, OperationName, RegisterCollect, RegisterJson,
+    RegisterSplit, RegisterSpread, Script, ScriptEnvironment, Section, SectionInterface,
+    SectionInterfaceDescription, SerializeMessage, SplitSchema, TransformError, TypeInfo,
     buffer_schema::BufferAccessRequest,
     fork_clone_schema::RegisterClone,
     fork_result_schema::{ForkResultRegistration, RegisterForkResult},
     register_json,
+    spread_schema::register_json_spread,
     supported::*,
     unzip_schema::{RegisterUnzip, UnzipRegistration},
 };
@@ -74,7 +75,7 @@ pub struct DiagramElementRegistry {
     pub(super) nodes: HashMap<BuilderId, NodeRegistration>,
     pub(super) sections: HashMap<BuilderId, SectionRegistration>,
     pub(super) scripting: HashMap<BuilderId, ScriptEnvironmentRegistration>,
-    pub(super) messages: MessageRegistry,
+    pub(crate) messages: MessageRegistry,
 }
 
 impl Default for DiagramElementRegistry {
@@ -387,7 +388,9 @@ impl DiagramElementRegistry {
     pub fn register_builtin_messages(&mut self) {
         self.register_message::<JsonMessage>()
             .with_join()
-            .with_split();
+            .with_split()
+            .with_collect();
+        register_json_spread(&mut self.messages);
 
         self.register_message::<ScriptMessage>()
             .with_join()
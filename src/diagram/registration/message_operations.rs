@@ -20,12 +20,14 @@ use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 
 pub use crate::dyn_node::*;
 use crate::{
     AnyBuffer, AsAnyBuffer, BufferAccessMetadata, BufferAccessRegistration, BufferMapLayoutHints,
-    BufferSettings, Builder, JoinRegistration, ListenRegistration, SplitRegistration,
+    BufferSettings, Builder, CollectRegistration, Gate, JoinRegistration, ListenRegistration,
+    SplitRegistration, SpreadRegistration, TrimBranch,
 };
 
 use super::*;
@@ -37,6 +39,10 @@ pub(crate) type SerializeFn<T> = fn(T) -> Result<JsonMessage, String>;
 pub(crate) type ForkCloneFn = fn(&mut Builder) -> Result<DynForkClone, DiagramErrorCode>;
 pub(crate) type CreateBufferFn = fn(BufferSettings, &mut Builder) -> AnyBuffer;
 pub(crate) type CreateTriggerFn = fn(&mut Builder) -> DynNode;
+pub(crate) type CreateGateFn = fn(Gate, Vec<AnyBuffer>, &mut Builder) -> DynNode;
+pub(crate) type CreateTrimFn = fn(Vec<TrimBranch>, &mut Builder) -> DynNode;
+pub(crate) type CreateDelayFn = fn(Duration, &mut Builder) -> DynNode;
+pub(crate) type CreateTimeoutFn = fn(Duration, DynInputSlot, &mut Builder) -> (DynNode, DynOutput);
 pub(crate) type CreateIntoFn =
     Arc<dyn Fn(&mut Builder) -> (DynInputSlot, DynOutput) + 'static + Send + Sync>;
 pub(crate) type CreateTryIntoFn =
@@ -61,12 +67,18 @@ pub struct MessageOperations {
     pub(crate) unzip: Option<UnzipRegistration>,
     pub(crate) fork_result: Option<ForkResultRegistration>,
     pub(crate) split: Option<SplitRegistration>,
+    pub(crate) spread: Option<SpreadRegistration>,
+    pub(crate) collect: Option<CollectRegistration>,
     pub(crate) join: Option<JoinRegistration>,
     pub(crate) buffer_access: Option<BufferAccessRegistration>,
     pub(crate) listen: Option<ListenRegistration>,
     pub(crate) to_string: Option<ToStringFn>,
     pub(crate) create_buffer_impl: CreateBufferFn,
     pub(crate) create_trigger_impl: CreateTriggerFn,
+    pub(crate) create_gate_impl: CreateGateFn,
+    pub(crate) create_trim_impl: CreateTrimFn,
+    pub(crate) create_delay_impl: CreateDelayFn,
+    pub(crate) create_timeout_impl: CreateTimeoutFn,
     pub(crate) into_impls: HashMap<usize, CreateIntoFn>,
     pub(crate) from_impls: HashMap<usize, CreateIntoFn>,
     pub(crate) try_into_impls: HashMap<usize, CreateTryIntoFn>,
@@ -89,6 +101,8 @@ impl MessageOperations {
             unzip: None,
             fork_result: None,
             split: None,
+            spread: None,
+            collect: None,
             join: None,
             buffer_access: None,
             listen: None,
@@ -97,6 +111,20 @@ impl MessageOperations {
                 builder.create_buffer::<T>(settings).as_any_buffer()
             },
             create_trigger_impl: |builder| builder.create_map_block(|_: T| ()).into(),
+            create_gate_impl: |action, buffers, builder| {
+                builder.create_gate_action::<T, _>(action, buffers).into()
+            },
+            create_trim_impl: |branches, builder| builder.create_trim::<T>(branches).into(),
+            create_delay_impl: |duration, builder| builder.create_delay::<T>(duration).into(),
+            create_timeout_impl: |duration, target, builder| {
+                let timeout = builder.create_timeout::<T>(duration, target);
+                let node = DynNode {
+                    input: timeout.input.into(),
+                    output: timeout.output.into(),
+                    streams: Default::default(),
+                };
+                (node, timeout.on_timeout.into())
+            },
             build_scope: BuildScope::new::<T>(),
             into_impls: Default::default(),
             try_into_impls: Default::default(),
@@ -204,6 +232,8 @@ pub struct MessageOperationsMetadata {
     unzip: Option<Vec<usize>>,
     fork_result: Option<[usize; 2]>,
     split: Option<usize>,
+    spread: Option<usize>,
+    collect: Option<usize>,
     join: Option<BufferMapLayoutHints<usize>>,
     buffer_access: Option<BufferAccessMetadata>,
     listen: Option<BufferMapLayoutHints<usize>>,
@@ -231,6 +261,8 @@ impl MessageOperationsMetadata {
             unzip: ops.unzip.as_ref().map(|unzip| unzip.output_types.clone()),
             fork_result: ops.fork_result.as_ref().map(|r| r.output_types),
             split: ops.split.as_ref().map(|op| op.output_type),
+            spread: ops.spread.as_ref().map(|op| op.output_type),
+            collect: ops.collect.as_ref().map(|op| op.output_type),
             join: ops.join.as_ref().map(|op| op.layout.clone()),
             buffer_access: ops.buffer_access.as_ref().map(|op| op.metadata.clone()),
             listen: ops.listen.as_ref().map(|op| op.layout.clone()),
@@ -275,6 +307,14 @@ impl MessageOperationsMetadata {
         &self.split
     }
 
+    pub fn spread_output(&self) -> &Option<usize> {
+        &self.spread
+    }
+
+    pub fn collect_output(&self) -> &Option<usize> {
+        &self.collect
+    }
+
     pub fn join(&self) -> &Option<BufferMapLayoutHints<usize>> {
         &self.join
     }
@@ -15,15 +15,17 @@
  *
 */
 
-use std::{any::Any, borrow::Cow, collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{
+    any::Any, borrow::Cow, collections::HashMap, marker::PhantomData, sync::Arc, time::Duration,
+};
 
 use bevy_ecs::prelude::{Commands, Entity};
 
 pub use crate::dyn_node::*;
 use crate::{
-    Accessor, AnyBuffer, BufferAccessRegistration, BufferMap, BufferSettings, Builder,
+    Accessor, AnyBuffer, BufferAccessRegistration, BufferMap, BufferSettings, Builder, Gate,
     IncrementalScopeBuilder, IncrementalScopeRequest, IncrementalScopeResponse, JoinRegistration,
-    Joined, JsonMessage, ListenRegistration,
+    Joined, JsonMessage, ListenRegistration, TrimBranch,
 };
 
 use serde_with::serde_as;
@@ -112,6 +114,50 @@ where
         self
     }
 
+    /// Mark the message as being spreadable. This is required in order for the
+    /// message to be able to be connected to a "Spread" operation.
+    pub fn with_spread(&mut self) -> &mut Self
+    where
+        Supported<(Message, Supported, Supported)>: RegisterSpread,
+    {
+        self.data.register_spread::<Message, Supported, Supported>();
+        self
+    }
+
+    /// Mark the message as being spreadable but the items from the spread are
+    /// unserializable.
+    pub fn with_spread_minimal(&mut self) -> &mut Self
+    where
+        Supported<(Message, NotSupported, NotSupported)>: RegisterSpread,
+    {
+        self.data
+            .register_spread::<Message, NotSupported, NotSupported>();
+        self
+    }
+
+    /// Mark the message as being collectable. This is required in order for
+    /// the message to be able to be connected to a "Collect" operation, which
+    /// will gather the messages into a [`Vec`].
+    pub fn with_collect(&mut self) -> &mut Self
+    where
+        Supported<(Message, Supported, Supported)>: RegisterCollect,
+    {
+        self.data
+            .register_collect::<Message, Supported, Supported>();
+        self
+    }
+
+    /// Mark the message as being collectable but the collected [`Vec`] is
+    /// unserializable.
+    pub fn with_collect_minimal(&mut self) -> &mut Self
+    where
+        Supported<(Message, NotSupported, NotSupported)>: RegisterCollect,
+    {
+        self.data
+            .register_collect::<Message, NotSupported, NotSupported>();
+        self
+    }
+
     /// Mark the message as being joinable.
     pub fn with_join(&mut self) -> &mut Self
     where
@@ -668,6 +714,57 @@ impl MessageRegistry {
         Supported::<(T, S, C)>::register_split(self);
     }
 
+    pub fn spread(
+        &self,
+        message_info: &TypeInfo,
+        builder: &mut Builder,
+    ) -> Result<DynNode, DiagramErrorCode> {
+        let create = self
+            .get_operations(message_info)?
+            .spread
+            .ok_or(DiagramErrorCode::NotSpreadable(Cow::Borrowed(
+                message_info.type_name,
+            )))?
+            .create;
+
+        Ok(create(builder))
+    }
+
+    /// Register a spread function if not already registered.
+    pub(crate) fn register_spread<T, S, C>(&mut self)
+    where
+        T: Send + Sync + 'static + Any,
+        Supported<(T, S, C)>: RegisterSpread,
+    {
+        Supported::<(T, S, C)>::register_spread(self);
+    }
+
+    pub fn collect(
+        &self,
+        message_info: &TypeInfo,
+        collect_op: &CollectSchema,
+        builder: &mut Builder,
+    ) -> Result<DynNode, DiagramErrorCode> {
+        let create = self
+            .get_operations(message_info)?
+            .collect
+            .ok_or(DiagramErrorCode::NotCollectable(Cow::Borrowed(
+                message_info.type_name,
+            )))?
+            .create;
+
+        Ok(create(collect_op, builder))
+    }
+
+    /// Register a collect function if not already registered.
+    pub(crate) fn register_collect<T, S, C>(&mut self)
+    where
+        T: Send + Sync + 'static + Any,
+        Supported<(T, S, C)>: RegisterCollect,
+    {
+        Supported::<(T, S, C)>::register_collect(self);
+    }
+
     pub fn create_buffer(
         &self,
         message_info: &TypeInfo,
@@ -726,6 +823,56 @@ impl MessageRegistry {
         Ok(f(builder))
     }
 
+    pub fn gate(
+        &self,
+        message_info: &TypeInfo,
+        action: Gate,
+        buffers: &BufferMap,
+        builder: &mut Builder,
+    ) -> Result<DynNode, DiagramErrorCode> {
+        let f = self.get_operations(message_info)?.create_gate_impl;
+        let buffers = buffers.values().copied().collect();
+
+        Ok(f(action, buffers, builder))
+    }
+
+    pub fn trim(
+        &self,
+        message_info: &TypeInfo,
+        branches: Vec<TrimBranch>,
+        builder: &mut Builder,
+    ) -> Result<DynNode, DiagramErrorCode> {
+        let f = self.get_operations(message_info)?.create_trim_impl;
+
+        Ok(f(branches, builder))
+    }
+
+    pub fn delay(
+        &self,
+        message_info: &TypeInfo,
+        duration: Duration,
+        builder: &mut Builder,
+    ) -> Result<DynNode, DiagramErrorCode> {
+        let f = self.get_operations(message_info)?.create_delay_impl;
+
+        Ok(f(duration, builder))
+    }
+
+    /// Create a timeout that watches over `target`. Returns the pass-through
+    /// node that should be connected to the target, and the `on_timeout`
+    /// output.
+    pub fn timeout(
+        &self,
+        message_info: &TypeInfo,
+        duration: Duration,
+        target: DynInputSlot,
+        builder: &mut Builder,
+    ) -> Result<(DynNode, DynOutput), DiagramErrorCode> {
+        let f = self.get_operations(message_info)?.create_timeout_impl;
+
+        Ok(f(duration, target, builder))
+    }
+
     pub fn join(
         &self,
         joinable: &TypeInfo,
@@ -864,6 +1011,12 @@ pub struct ReverseMessageLookup {
     #[schemars(with = "Vec<(usize, Vec<usize>)>")]
     pub(crate) split: HashMap<usize, Vec<usize>>,
 
+    /// Map from the message type that comes out of a collect operation to the
+    /// message type of the items that were collected.
+    #[serde_as(as = "Vec<(_, _)>")]
+    #[schemars(with = "Vec<(usize, usize)>")]
+    pub(crate) collect: HashMap<usize, usize>,
+
     /// The index where the [`JsonMessage`] type is registered.
     #[serde_as(as = "_")]
     #[schemars(with = "Option<usize>")]
@@ -872,6 +1025,11 @@ pub struct ReverseMessageLookup {
     #[serde_as(as = "_")]
     #[schemars(with = "Option<usize>")]
     pub(crate) script_message: Option<usize>,
+
+    /// The index where the unit `()` type is registered.
+    #[serde_as(as = "_")]
+    #[schemars(with = "Option<usize>")]
+    pub(crate) unit_message: Option<usize>,
 }
 
 impl MessageRegistrations {
@@ -993,6 +1151,10 @@ impl MessageRegistrations {
             self.reverse_lookup.script_message = Some(index);
         }
 
+        if message_info == TypeInfo::of::<()>() {
+            self.reverse_lookup.unit_message = Some(index);
+        }
+
         self.indices.insert(message_info, index);
         self.messages.push(registration);
 
@@ -20,7 +20,8 @@ use std::{any::Any, borrow::Cow, cell::RefCell, collections::HashMap, marker::Ph
 pub use crate::dyn_node::*;
 use crate::{
     Accessor, Builder, BuilderId, DiagramElementRegistry, DiagramErrorCode, DynType, Joined,
-    JsonMessage, MessageRegistrationBuilder, RegisterSplit, Text, diagram::supported::*,
+    JsonMessage, MessageRegistrationBuilder, RegisterCollect, RegisterSplit, RegisterSpread, Text,
+    diagram::supported::*,
 };
 
 use super::{BufferAccessRequest, ConfigExample, RegisterForkResult, RegisterUnzip};
@@ -36,7 +37,8 @@ pub struct NodeRegistration {
 }
 
 impl NodeRegistration {
-    pub(crate) fn create_node(
+    /// Create an instance of the registered node with the given config.
+    pub fn create_node(
         &self,
         builder: &mut Builder,
         config: JsonMessage,
@@ -243,6 +245,46 @@ where
         self
     }
 
+    /// Mark the node as having a spreadable response. This is required in order
+    /// for the node to be able to be connected to a "Spread" operation.
+    pub fn with_spread(&mut self) -> &mut Self
+    where
+        Supported<(Response, Supported, Supported)>: RegisterSpread,
+    {
+        MessageRegistrationBuilder::new(&mut self.registry.messages).with_spread();
+        self
+    }
+
+    /// Mark the node as having a spreadable response but the items from the
+    /// spread are unserializable.
+    pub fn with_spread_unserializable(&mut self) -> &mut Self
+    where
+        Supported<(Response, NotSupported, NotSupported)>: RegisterSpread,
+    {
+        MessageRegistrationBuilder::new(&mut self.registry.messages).with_spread_minimal();
+        self
+    }
+
+    /// Mark the node as having a collectable response. This is required in
+    /// order for the node to be able to be connected to a "Collect" operation.
+    pub fn with_collect(&mut self) -> &mut Self
+    where
+        Supported<(Response, Supported, Supported)>: RegisterCollect,
+    {
+        MessageRegistrationBuilder::new(&mut self.registry.messages).with_collect();
+        self
+    }
+
+    /// Mark the node as having a collectable response but the collected list
+    /// is unserializable.
+    pub fn with_collect_unserializable(&mut self) -> &mut Self
+    where
+        Supported<(Response, NotSupported, NotSupported)>: RegisterCollect,
+    {
+        MessageRegistrationBuilder::new(&mut self.registry.messages).with_collect_minimal();
+        self
+    }
+
     /// Mark the node as having a joinable request.
     pub fn with_join(&mut self) -> &mut Self
     where
@@ -120,6 +120,8 @@ pub trait MetadataAccess {
 
     fn script_message_index(&self) -> Result<usize, DiagramErrorCode>;
 
+    fn unit_message_index(&self) -> Result<usize, DiagramErrorCode>;
+
     fn node_metadata(&self, builder: &str) -> Result<&NodeMetadata, DiagramErrorCode>;
 
     fn section_metadata(&self, builder: &str) -> Result<&SectionMetadata, DiagramErrorCode>;
@@ -170,6 +172,14 @@ pub trait MetadataAccess {
 
     fn can_split(&self, message_index: usize) -> Result<bool, DiagramErrorCode>;
 
+    fn spread_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode>;
+
+    fn can_spread(&self, message_index: usize) -> Result<bool, DiagramErrorCode>;
+
+    fn collect_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode>;
+
+    fn can_collect(&self, message_index: usize) -> Result<bool, DiagramErrorCode>;
+
     fn reverse_lookup(&self) -> &ReverseMessageLookup;
 }
 
@@ -198,6 +208,18 @@ impl MetadataAccess for DiagramElementRegistry {
             })
     }
 
+    fn unit_message_index(&self) -> Result<usize, DiagramErrorCode> {
+        self.messages
+            .registration
+            .reverse_lookup
+            .unit_message
+            .ok_or_else(|| {
+                DiagramErrorCode::UnregisteredTypes(vec![Cow::Borrowed(
+                    TypeInfo::of::<()>().type_name,
+                )])
+            })
+    }
+
     fn node_metadata(&self, builder: &str) -> Result<&NodeMetadata, DiagramErrorCode> {
         Ok(self.get_node_registration(builder)?.metadata())
     }
@@ -359,6 +381,40 @@ impl MetadataAccess for DiagramElementRegistry {
             .is_some())
     }
 
+    fn spread_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode> {
+        let Some(spread) = &self.get_message_operations_by_index(message_index)?.spread else {
+            return Err(DiagramErrorCode::NotSpreadable(Cow::Owned(
+                self.message_type_name(message_index)?.to_owned(),
+            )));
+        };
+
+        Ok(spread.output_type)
+    }
+
+    fn can_spread(&self, message_index: usize) -> Result<bool, DiagramErrorCode> {
+        Ok(self
+            .get_message_operations_by_index(message_index)?
+            .spread
+            .is_some())
+    }
+
+    fn collect_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode> {
+        let Some(collect) = &self.get_message_operations_by_index(message_index)?.collect else {
+            return Err(DiagramErrorCode::NotCollectable(Cow::Owned(
+                self.message_type_name(message_index)?.to_owned(),
+            )));
+        };
+
+        Ok(collect.output_type)
+    }
+
+    fn can_collect(&self, message_index: usize) -> Result<bool, DiagramErrorCode> {
+        Ok(self
+            .get_message_operations_by_index(message_index)?
+            .collect
+            .is_some())
+    }
+
     fn reverse_lookup(&self) -> &ReverseMessageLookup {
         &self.messages.registration.reverse_lookup
     }
@@ -381,6 +437,12 @@ impl MetadataAccess for DiagramElementMetadata {
         })
     }
 
+    fn unit_message_index(&self) -> Result<usize, DiagramErrorCode> {
+        self.reverse_message_lookup.unit_message.ok_or_else(|| {
+            DiagramErrorCode::UnregisteredTypes(vec![Cow::Borrowed(TypeInfo::of::<()>().type_name)])
+        })
+    }
+
     fn node_metadata(&self, builder: &str) -> Result<&NodeMetadata, DiagramErrorCode> {
         self.nodes
             .get(builder)
@@ -539,6 +601,40 @@ impl MetadataAccess for DiagramElementMetadata {
             .is_some())
     }
 
+    fn spread_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode> {
+        let Some(spread) = self.message_operations_for(message_index)?.spread_output() else {
+            return Err(DiagramErrorCode::NotSpreadable(
+                self.message_type_name(message_index)?.to_owned().into(),
+            ));
+        };
+
+        Ok(*spread)
+    }
+
+    fn can_spread(&self, message_index: usize) -> Result<bool, DiagramErrorCode> {
+        Ok(self
+            .message_operations_for(message_index)?
+            .spread_output()
+            .is_some())
+    }
+
+    fn collect_output_type(&self, message_index: usize) -> Result<usize, DiagramErrorCode> {
+        let Some(collect) = self.message_operations_for(message_index)?.collect_output() else {
+            return Err(DiagramErrorCode::NotCollectable(
+                self.message_type_name(message_index)?.to_owned().into(),
+            ));
+        };
+
+        Ok(*collect)
+    }
+
+    fn can_collect(&self, message_index: usize) -> Result<bool, DiagramErrorCode> {
+        Ok(self
+            .message_operations_for(message_index)?
+            .collect_output()
+            .is_some())
+    }
+
     fn reverse_lookup(&self) -> &ReverseMessageLookup {
         &self.reverse_message_lookup
     }
@@ -0,0 +1,764 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use crate::IdentifierRef;
+
+use super::{
+    BufferSelection, Diagram, DiagramOperation, NextOperation, OperationName, Operations,
+    SectionProvider,
+};
+
+impl Diagram {
+    /// Render the diagram as a [Graphviz DOT](https://graphviz.org/doc/info/lang.html)
+    /// graph, e.g. to embed a picture of the workflow in documentation.
+    ///
+    /// Scope operations and section templates are drawn as clusters that
+    /// contain their own operations. Connections from buffers into the
+    /// operations that use them are drawn as dashed lines.
+    pub fn to_dot(&self) -> String {
+        DiagramGraph::new(self).to_dot()
+    }
+
+    /// Render the diagram as a [Mermaid](https://mermaid.js.org/syntax/flowchart.html)
+    /// flowchart. This contains the same information as [`Self::to_dot`].
+    pub fn to_mermaid(&self) -> String {
+        DiagramGraph::new(self).to_mermaid()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum NodeShape {
+    Operation,
+    Buffer,
+    Section,
+    /// Places where messages enter or leave a scope
+    Terminal,
+    /// An operation that is referenced but does not exist
+    Missing,
+}
+
+struct GraphNode {
+    id: String,
+    label: String,
+    shape: NodeShape,
+}
+
+struct Cluster {
+    id: String,
+    label: String,
+    nodes: Vec<GraphNode>,
+    clusters: Vec<Cluster>,
+}
+
+impl Cluster {
+    fn new(id: String, label: String) -> Self {
+        Self {
+            id,
+            label,
+            nodes: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+}
+
+struct GraphEdge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    buffer: bool,
+}
+
+/// The operations of a diagram arranged as a graph that can be rendered in
+/// different formats.
+struct DiagramGraph {
+    root: Cluster,
+    edges: Vec<GraphEdge>,
+}
+
+impl DiagramGraph {
+    fn new(diagram: &Diagram) -> Self {
+        let mut builder = GraphBuilder::default();
+        let mut root = Cluster::new(String::new(), String::new());
+        let scope = GraphScope {
+            prefix: String::new(),
+            ops: &diagram.ops,
+            outputs: &[],
+        };
+        let mut pending = PendingNodes::default();
+
+        let start = builder.node("builtin:start");
+        root.nodes.push(GraphNode {
+            id: start.clone(),
+            label: "start".to_owned(),
+            shape: NodeShape::Terminal,
+        });
+        let target = builder.target(&scope, &diagram.start, &mut pending);
+        builder.connect(&start, target, None, false);
+        builder.add_operations(&scope, &mut root, &mut pending);
+
+        let mut templates: Vec<_> = diagram.templates.iter().collect();
+        templates.sort_by_key(|(name, _)| *name);
+        for (name, template) in templates {
+            let prefix = format!("template:{name}/");
+            let mut cluster = Cluster::new(builder.cluster(), format!("template: {name}"));
+            let scope = GraphScope {
+                prefix: prefix.clone(),
+                ops: &template.ops,
+                outputs: &template.outputs,
+            };
+            let mut inner_pending = PendingNodes::default();
+            for (remapping, kind, buffer) in [
+                (&template.inputs, "input", false),
+                (&template.buffers, "buffer", true),
+            ] {
+                let mut exposed = Vec::new();
+                let _ = remapping.redirect(|name, next| {
+                    exposed.push((name.clone(), next.clone()));
+                    Ok(())
+                });
+                exposed.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (name, next) in exposed {
+                    let id = builder.node(&format!("{prefix}{kind}:{name}"));
+                    cluster.nodes.push(GraphNode {
+                        id: id.clone(),
+                        label: format!("{kind}: {name}"),
+                        shape: NodeShape::Terminal,
+                    });
+                    let target = builder.target(&scope, &next, &mut inner_pending);
+                    builder.connect(&id, target, None, buffer);
+                }
+            }
+            builder.add_operations(&scope, &mut cluster, &mut inner_pending);
+            inner_pending.declare(&mut cluster);
+            root.clusters.push(cluster);
+        }
+
+        pending.declare(&mut root);
+        Self {
+            root,
+            edges: builder.edges,
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph diagram {\n    node [shape=box];\n");
+        write_dot_cluster(&mut out, &self.root, 1);
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label=\"{}\"", dot_escape(label)));
+            }
+            if edge.buffer {
+                attributes.push("style=dashed".to_owned());
+            }
+
+            let _ = write!(out, "    {} -> {}", edge.from, edge.to);
+            if !attributes.is_empty() {
+                let _ = write!(out, " [{}]", attributes.join(", "));
+            }
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        write_mermaid_cluster(&mut out, &self.root, 1);
+        for edge in &self.edges {
+            let arrow = if edge.buffer { "-.->" } else { "-->" };
+            let _ = match &edge.label {
+                Some(label) => writeln!(
+                    out,
+                    "    {} {arrow}|\"{}\"| {}",
+                    edge.from,
+                    mermaid_escape(label),
+                    edge.to
+                ),
+                None => writeln!(out, "    {} {arrow} {}", edge.from, edge.to),
+            };
+        }
+        out
+    }
+}
+
+fn write_dot_cluster(out: &mut String, cluster: &Cluster, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for node in &cluster.nodes {
+        let attributes = match node.shape {
+            NodeShape::Operation => "",
+            NodeShape::Buffer => ", shape=cylinder",
+            NodeShape::Section => ", shape=component",
+            NodeShape::Terminal => ", shape=oval",
+            NodeShape::Missing => ", style=dashed",
+        };
+        let _ = writeln!(
+            out,
+            "{indent}{} [label=\"{}\"{attributes}];",
+            node.id,
+            dot_escape(&node.label)
+        );
+    }
+
+    for child in &cluster.clusters {
+        let _ = writeln!(out, "{indent}subgraph {} {{", child.id);
+        let _ = writeln!(out, "{indent}    label=\"{}\";", dot_escape(&child.label));
+        write_dot_cluster(out, child, depth + 1);
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+fn write_mermaid_cluster(out: &mut String, cluster: &Cluster, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for node in &cluster.nodes {
+        let label = mermaid_escape(&node.label);
+        let _ = match node.shape {
+            NodeShape::Operation => writeln!(out, "{indent}{}[\"{label}\"]", node.id),
+            NodeShape::Buffer => writeln!(out, "{indent}{}[(\"{label}\")]", node.id),
+            NodeShape::Section => writeln!(out, "{indent}{}[[\"{label}\"]]", node.id),
+            NodeShape::Terminal => writeln!(out, "{indent}{}([\"{label}\"])", node.id),
+            NodeShape::Missing => writeln!(out, "{indent}{}>\"{label}\"]", node.id),
+        };
+    }
+
+    for child in &cluster.clusters {
+        let _ = writeln!(
+            out,
+            "{indent}subgraph {} [\"{}\"]",
+            child.id,
+            mermaid_escape(&child.label)
+        );
+        write_mermaid_cluster(out, child, depth + 1);
+        let _ = writeln!(out, "{indent}end");
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br>")
+}
+
+/// The operations of one scope of the graph: the root of the diagram, a
+/// scope operation, or a section template.
+struct GraphScope<'a> {
+    /// Put in front of operation names to make them unique across the graph.
+    prefix: String,
+    ops: &'a Operations,
+    /// Names that lead out of a section template.
+    outputs: &'a [OperationName],
+}
+
+/// Nodes that only get declared once something connects to them, keyed by
+/// their path within the graph.
+#[derive(Default)]
+struct PendingNodes(BTreeMap<String, GraphNode>);
+
+impl PendingNodes {
+    fn declare(self, cluster: &mut Cluster) {
+        cluster.nodes.extend(self.0.into_values());
+    }
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    /// Short ids that are valid in every output format, keyed by the path of
+    /// the node.
+    ids: HashMap<String, String>,
+    clusters: usize,
+    edges: Vec<GraphEdge>,
+}
+
+impl GraphBuilder {
+    fn node(&mut self, path: &str) -> String {
+        let next = self.ids.len();
+        self.ids
+            .entry(path.to_owned())
+            .or_insert_with(|| format!("n{next}"))
+            .clone()
+    }
+
+    fn cluster(&mut self) -> String {
+        self.clusters += 1;
+        format!("cluster_{}", self.clusters)
+    }
+
+    fn add_operations(
+        &mut self,
+        scope: &GraphScope,
+        cluster: &mut Cluster,
+        pending: &mut PendingNodes,
+    ) {
+        let mut ops: Vec<_> = scope.ops.iter().collect();
+        ops.sort_by_key(|(name, _)| *name);
+        for (name, op) in ops {
+            let path = format!("{}{name}", scope.prefix);
+            let id = self.node(&path);
+            let (kind, shape): (Cow<str>, _) = match op.as_ref() {
+                DiagramOperation::Node(node) => {
+                    (node.builder.as_ref().into(), NodeShape::Operation)
+                }
+                DiagramOperation::Section(section) => match &section.provider {
+                    SectionProvider::Builder(builder) => {
+                        (format!("section: {builder}").into(), NodeShape::Section)
+                    }
+                    SectionProvider::Template(template) => {
+                        (format!("template: {template}").into(), NodeShape::Section)
+                    }
+                },
+                DiagramOperation::StreamOut(stream_out) => (
+                    format!("stream_out: {}", stream_out.name).into(),
+                    NodeShape::Terminal,
+                ),
+                DiagramOperation::Buffer(_) => ("buffer".into(), NodeShape::Buffer),
+                DiagramOperation::Scope(_) => {
+                    self.add_scope(scope, name, &path, &id, op, cluster, pending);
+                    continue;
+                }
+                other => (other.to_string().into(), NodeShape::Operation),
+            };
+            cluster.nodes.push(GraphNode {
+                id: id.clone(),
+                label: format!("{name}\n{kind}"),
+                shape,
+            });
+
+            for (next, label) in outgoing(op) {
+                let target = self.target(scope, next, pending);
+                self.connect(&id, target, label, false);
+            }
+
+            if let Some(buffers) = buffer_selection(op) {
+                for (key, buffer) in buffers.iter() {
+                    let label = match key {
+                        IdentifierRef::Name(name) => Some(name.into_owned()),
+                        IdentifierRef::Index(_) => None,
+                    };
+                    let (from, input) = self.target(scope, buffer, pending);
+                    let label = combine_labels(input, label);
+                    self.edges.push(GraphEdge {
+                        from,
+                        to: id.clone(),
+                        label,
+                        buffer: true,
+                    });
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_scope(
+        &mut self,
+        parent: &GraphScope,
+        name: &OperationName,
+        path: &str,
+        id: &str,
+        op: &DiagramOperation,
+        cluster: &mut Cluster,
+        pending: &mut PendingNodes,
+    ) {
+        let DiagramOperation::Scope(scope_op) = op else {
+            return;
+        };
+
+        let mut child = Cluster::new(self.cluster(), format!("{name}\nscope"));
+        // Connections into the scope operation arrive at its start.
+        child.nodes.push(GraphNode {
+            id: id.to_owned(),
+            label: "start".to_owned(),
+            shape: NodeShape::Terminal,
+        });
+
+        let inner = GraphScope {
+            prefix: format!("{path}/"),
+            ops: &scope_op.ops,
+            outputs: &[],
+        };
+        let mut inner_pending = PendingNodes::default();
+        let target = self.target(&inner, &scope_op.start, &mut inner_pending);
+        self.connect(id, target, None, false);
+        self.add_operations(&inner, &mut child, &mut inner_pending);
+
+        let (terminate, _) = self.target(&inner, &NextOperation::terminate(), &mut inner_pending);
+        let target = self.target(parent, &scope_op.next, pending);
+        self.connect(&terminate, target, None, false);
+
+        let mut streams: Vec<_> = scope_op.stream_out.iter().collect();
+        streams.sort_by_key(|(name, _)| *name);
+        for (stream, next) in streams {
+            let mut sources: Vec<_> = scope_op
+                .ops
+                .iter()
+                .filter(|(_, inner_op)| {
+                    matches!(
+                        inner_op.as_ref(),
+                        DiagramOperation::StreamOut(stream_out) if stream_out.name == *stream
+                    )
+                })
+                .map(|(inner_name, _)| inner_name)
+                .collect();
+            sources.sort();
+            for source in sources {
+                let from = self.node(&format!("{}{source}", inner.prefix));
+                let target = self.target(parent, next, pending);
+                self.connect(&from, target, Some(stream.to_string()), false);
+            }
+        }
+
+        inner_pending.declare(&mut child);
+        cluster.clusters.push(child);
+    }
+
+    /// Get the id of the node that `next` leads to within `scope`, along with
+    /// the name of the input if `next` leads into a section.
+    fn target(
+        &mut self,
+        scope: &GraphScope,
+        next: &NextOperation,
+        pending: &mut PendingNodes,
+    ) -> (String, Option<String>) {
+        match next {
+            NextOperation::Name(name) => {
+                if scope.ops.contains_key(name) {
+                    return (self.node(&format!("{}{name}", scope.prefix)), None);
+                }
+
+                if scope.outputs.contains(name) {
+                    let id = self.pending(
+                        format!("{}output:{name}", scope.prefix),
+                        format!("output: {name}"),
+                        NodeShape::Terminal,
+                        pending,
+                    );
+                    return (id, None);
+                }
+
+                let id = self.pending(
+                    format!("{}{name}", scope.prefix),
+                    name.to_string(),
+                    NodeShape::Missing,
+                    pending,
+                );
+                (id, None)
+            }
+            NextOperation::Builtin { builtin } => {
+                let id = self.pending(
+                    format!("{}builtin:{builtin}", scope.prefix),
+                    builtin.to_string(),
+                    NodeShape::Terminal,
+                    pending,
+                );
+                (id, None)
+            }
+            NextOperation::Namespace(namespaced) => {
+                if scope.ops.contains_key(&namespaced.namespace) {
+                    let id = self.node(&format!("{}{}", scope.prefix, namespaced.namespace));
+                    return (id, Some(namespaced.operation.to_string()));
+                }
+
+                let id = self.pending(
+                    format!("{}{next}", scope.prefix),
+                    next.to_string(),
+                    NodeShape::Missing,
+                    pending,
+                );
+                (id, None)
+            }
+        }
+    }
+
+    fn pending(
+        &mut self,
+        path: String,
+        label: String,
+        shape: NodeShape,
+        pending: &mut PendingNodes,
+    ) -> String {
+        let id = self.node(&path);
+        pending.0.entry(path).or_insert_with(|| GraphNode {
+            id: id.clone(),
+            label,
+            shape,
+        });
+        id
+    }
+
+    fn connect(
+        &mut self,
+        from: &str,
+        (to, input): (String, Option<String>),
+        label: Option<String>,
+        buffer: bool,
+    ) {
+        self.edges.push(GraphEdge {
+            from: from.to_owned(),
+            to,
+            label: combine_labels(label, input),
+            buffer,
+        });
+    }
+}
+
+fn combine_labels(first: Option<String>, second: Option<String>) -> Option<String> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(format!("{first} → {second}")),
+        (first, second) => first.or(second),
+    }
+}
+
+/// Where the outputs of an operation are sent, with a label for each output
+/// that needs one. Scopes are handled separately.
+fn outgoing(op: &DiagramOperation) -> Vec<(&NextOperation, Option<String>)> {
+    fn sorted_map(
+        map: &HashMap<OperationName, NextOperation>,
+    ) -> impl Iterator<Item = (&NextOperation, Option<String>)> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by_key(|(name, _)| *name);
+        entries
+            .into_iter()
+            .map(|(name, next)| (next, Some(name.to_string())))
+    }
+
+    let mut outputs = Vec::new();
+    match op {
+        DiagramOperation::Node(node) => {
+            outputs.push((&node.next, None));
+            outputs.extend(sorted_map(&node.stream_out));
+        }
+        DiagramOperation::Section(section) => {
+            outputs.extend(sorted_map(&section.connect));
+        }
+        DiagramOperation::Scope(_)
+        | DiagramOperation::StreamOut(_)
+        | DiagramOperation::Buffer(_) => {}
+        DiagramOperation::ForkClone(fork_clone) => {
+            outputs.extend(fork_clone.next.iter().map(|next| (next, None)));
+        }
+        DiagramOperation::Unzip(unzip) => {
+            outputs.extend(
+                unzip
+                    .next
+                    .iter()
+                    .enumerate()
+                    .map(|(i, next)| (next, Some(i.to_string()))),
+            );
+        }
+        DiagramOperation::ForkResult(fork_result) => {
+            outputs.push((&fork_result.ok, Some("ok".to_owned())));
+            outputs.push((&fork_result.err, Some("err".to_owned())));
+        }
+        DiagramOperation::Split(split) => {
+            outputs.extend(
+                split
+                    .sequential
+                    .iter()
+                    .enumerate()
+                    .map(|(i, next)| (next, Some(i.to_string()))),
+            );
+            outputs.extend(sorted_map(&split.keyed));
+            if let Some(remaining) = &split.remaining {
+                outputs.push((remaining, Some("remaining".to_owned())));
+            }
+        }
+        DiagramOperation::Spread(spread) => outputs.push((&spread.next, None)),
+        DiagramOperation::Collect(collect) => outputs.push((&collect.next, None)),
+        DiagramOperation::Join(join) => outputs.push((&join.next, None)),
+        DiagramOperation::GateOpen(gate) => outputs.push((&gate.next, None)),
+        DiagramOperation::GateClose(gate) => outputs.push((&gate.next, None)),
+        DiagramOperation::Trim(trim) => outputs.push((&trim.next, None)),
+        DiagramOperation::Delay(delay) => outputs.push((&delay.next, None)),
+        DiagramOperation::Timeout(timeout) => {
+            outputs.push((&timeout.next, None));
+            outputs.push((&timeout.on_timeout, Some("timeout".to_owned())));
+        }
+        DiagramOperation::Transform(transform) => {
+            outputs.push((&transform.next, None));
+            if let Some(on_error) = &transform.on_error {
+                outputs.push((on_error, Some("error".to_owned())));
+            }
+        }
+        DiagramOperation::Switch(switch) => {
+            outputs.extend(
+                switch
+                    .cases
+                    .iter()
+                    .map(|case| (&case.next, Some(case.when.clone()))),
+            );
+            if let Some(default) = &switch.default {
+                outputs.push((default, Some("default".to_owned())));
+            }
+            if let Some(on_error) = &switch.on_error {
+                outputs.push((on_error, Some("error".to_owned())));
+            }
+        }
+        DiagramOperation::BufferAccess(access) => outputs.push((&access.next, None)),
+        DiagramOperation::Listen(listen) => outputs.push((&listen.next, None)),
+        DiagramOperation::Script(script) => {
+            outputs.push((&script.next, None));
+            if let Some(on_error) = &script.on_error {
+                outputs.push((on_error, Some("error".to_owned())));
+            }
+            outputs.extend(sorted_map(&script.stream_out));
+        }
+    }
+
+    outputs
+}
+
+fn buffer_selection(op: &DiagramOperation) -> Option<&BufferSelection> {
+    match op {
+        DiagramOperation::Join(join) => Some(&join.buffers),
+        DiagramOperation::GateOpen(gate) => Some(&gate.buffers),
+        DiagramOperation::GateClose(gate) => Some(&gate.buffers),
+        DiagramOperation::BufferAccess(access) => Some(&access.buffers),
+        DiagramOperation::Listen(listen) => Some(&listen.buffers),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_simple_diagram() {
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "add",
+            "ops": {
+                "add": {
+                    "type": "node",
+                    "builder": "add",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            diagram.to_dot(),
+            "digraph diagram {\n    \
+                node [shape=box];\n    \
+                n0 [label=\"start\", shape=oval];\n    \
+                n1 [label=\"add\\nadd\"];\n    \
+                n2 [label=\"terminate\", shape=oval];\n    \
+                n0 -> n1;\n    \
+                n1 -> n2;\n\
+            }\n",
+        );
+
+        assert_eq!(
+            diagram.to_mermaid(),
+            "flowchart TD\n    \
+                n0([\"start\"])\n    \
+                n1[\"add<br>add\"]\n    \
+                n2([\"terminate\"])\n    \
+                n0 --> n1\n    \
+                n1 --> n2\n",
+        );
+    }
+
+    #[test]
+    fn test_render_scopes_templates_and_buffers() {
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "templates": {
+                "doubler": {
+                    "inputs": ["double"],
+                    "outputs": ["out"],
+                    "ops": {
+                        "double": {
+                            "type": "node",
+                            "builder": "multiply_by",
+                            "config": 2,
+                            "next": "out",
+                        },
+                    },
+                },
+            },
+            "start": "fork",
+            "ops": {
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["buffer_a", "scope"],
+                },
+                "scope": {
+                    "type": "scope",
+                    "start": "inner",
+                    "ops": {
+                        "inner": {
+                            "type": "node",
+                            "builder": "add",
+                            "next": { "builtin": "terminate" },
+                        },
+                    },
+                    "next": { "section": "double" },
+                },
+                "section": {
+                    "type": "section",
+                    "template": "doubler",
+                    "connect": {
+                        "out": "buffer_b",
+                    },
+                },
+                "buffer_a": { "type": "buffer" },
+                "buffer_b": { "type": "buffer" },
+                "join": {
+                    "type": "join",
+                    "buffers": { "a": "buffer_a", "b": "buffer_b" },
+                    "next": "missing_op",
+                },
+            },
+        }))
+        .unwrap();
+
+        let dot = diagram.to_dot();
+        assert!(dot.contains("label=\"scope\\nscope\";"));
+        assert!(dot.contains("label=\"template: doubler\";"));
+        assert!(dot.contains("[label=\"input: double\", shape=oval]"));
+        assert!(dot.contains("[label=\"output: out\", shape=oval]"));
+        assert!(dot.contains("[label=\"buffer_a\\nbuffer\", shape=cylinder]"));
+        assert!(dot.contains("[label=\"section\\ntemplate: doubler\", shape=component]"));
+        assert!(dot.contains("[label=\"missing_op\", style=dashed]"));
+        assert!(dot.contains("[label=\"a\", style=dashed]"));
+        assert!(dot.contains("[label=\"out\"]"));
+        assert!(dot.contains("[label=\"double\"]"));
+        assert_eq!(dot, diagram.to_dot());
+
+        let mermaid = diagram.to_mermaid();
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("[\"scope<br>scope\"]"));
+        assert!(mermaid.contains("[(\"buffer_b<br>buffer\")]"));
+        assert!(mermaid.contains("[[\"section<br>template: doubler\"]]"));
+        assert!(mermaid.contains("-.->|\"b\"|"));
+        assert!(mermaid.contains("-->|\"double\"|"));
+        assert_eq!(mermaid.matches("subgraph").count(), 2);
+        assert_eq!(mermaid.matches("\n    end\n").count(), 2);
+    }
+}
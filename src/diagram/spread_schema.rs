@@ -0,0 +1,305 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Builder, JsonMessage};
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, DynNode,
+    InferenceContext, MessageRegistry, NextOperation, OperationName, Operations, RegisterClone,
+    SerializeMessage, Templates, TraceInfo, TraceSettings, supported::*,
+};
+
+/// If the input message is a list-like object, send each of its elements to
+/// `next` as a separate message. Each element starts a new thread within the
+/// workflow, and the elements are sent out in the order that the list iterates
+/// over them.
+///
+/// Unlike `split`, every element goes to the same target, which makes this
+/// a natural fit for processing each element in parallel and then gathering
+/// the results back into a list with `collect`.
+///
+/// For a [`JsonMessage`] input, the elements of an array or the values of an
+/// object will be spread. Any other JSON value is sent out as a single
+/// message.
+///
+/// If the input message is empty, no message will be sent and the thread
+/// will be disposed.
+///
+/// # Examples
+///
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "spread",
+///     "ops": {
+///         "spread": {
+///             "type": "spread",
+///             "next": "visit_waypoint"
+///         },
+///         "visit_waypoint": {
+///             "type": "node",
+///             "builder": "visit",
+///             "next": "results"
+///         },
+///         "results": {
+///             "type": "collect",
+///             "next": { "builtin": "terminate" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SpreadSchema {
+    pub next: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl BuildDiagramOperation for SpreadSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        let inferred_type = ctx.inferred_message_type(id)?;
+
+        let spread = ctx.registry.messages.spread(&inferred_type, ctx.builder)?;
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, spread.input, trace)?;
+        ctx.add_output_into_target(&self.next, spread.output);
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.spread(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+pub type SpreadFn = fn(&mut Builder) -> DynNode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadRegistration {
+    pub create: SpreadFn,
+    pub output_type: usize,
+}
+
+pub trait RegisterSpread {
+    fn create_spread(builder: &mut Builder) -> DynNode;
+
+    fn register_spread(messages: &mut MessageRegistry);
+}
+
+impl<T, Serializer, Cloneable> RegisterSpread for Supported<(T, Serializer, Cloneable)>
+where
+    T: Send + Sync + 'static + IntoIterator,
+    T::Item: Send + Sync + 'static,
+    Serializer: SerializeMessage<T::Item>,
+    Cloneable: RegisterClone<T::Item>,
+{
+    fn create_spread(builder: &mut Builder) -> DynNode {
+        builder.create_spread::<T>().into()
+    }
+
+    fn register_spread(messages: &mut MessageRegistry) {
+        let output_type = messages.registration.get_index_or_insert::<T::Item>();
+
+        let ops = &mut messages.registration.get_or_insert_operations::<T>();
+        ops.spread = Some(SpreadRegistration {
+            create: Self::create_spread,
+            output_type,
+        });
+
+        messages.register_serialize::<T::Item, Serializer>();
+        messages.register_clone::<T::Item, Cloneable>();
+    }
+}
+
+/// [`JsonMessage`] does not implement [`IntoIterator`], so its spread operation
+/// gets registered separately.
+pub(super) fn register_json_spread(messages: &mut MessageRegistry) {
+    let output_type = messages.registration.get_index_or_insert::<JsonMessage>();
+
+    let ops = &mut messages
+        .registration
+        .get_or_insert_operations::<JsonMessage>();
+    ops.spread = Some(SpreadRegistration {
+        create: |builder| {
+            let node = builder.create_map_block(json_elements);
+            let output = builder.chain(node.output).spread().output();
+
+            DynNode {
+                input: node.input.into(),
+                output: output.into(),
+                streams: Default::default(),
+            }
+        },
+        output_type,
+    });
+}
+
+fn json_elements(value: JsonMessage) -> Vec<JsonMessage> {
+    match value {
+        Value::Array(array) => array,
+        Value::Object(map) => map.into_iter().map(|(_, value)| value).collect(),
+        singular => vec![singular],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_log::test;
+
+    use crate::{
+        Builder, Diagram, JsonMessage, NodeBuilderOptions, diagram::testing::DiagramTestFixture,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_spread_then_collect() {
+        let mut fixture = DiagramTestFixture::new();
+
+        fn make_list(n: i64) -> Vec<i64> {
+            (1..=n).collect()
+        }
+
+        fixture
+            .registry
+            .register_node_builder(
+                NodeBuilderOptions::new("make_list"),
+                |builder: &mut Builder, _config: ()| builder.create_map_block(make_list),
+            )
+            .with_spread();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "make_list",
+            "ops": {
+                "make_list": {
+                    "type": "node",
+                    "builder": "make_list",
+                    "next": "spread",
+                },
+                "spread": {
+                    "type": "spread",
+                    "next": "multiply",
+                },
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 10,
+                    "next": "collect",
+                },
+                "collect": {
+                    "type": "collect",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, JsonMessage::from(3))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!([10, 20, 30]));
+    }
+
+    #[test]
+    fn test_spread_json() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "spread",
+            "ops": {
+                "spread": {
+                    "type": "spread",
+                    "next": "collect",
+                },
+                "collect": {
+                    "type": "collect",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, json!(["a", "b", "c"]))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!(["a", "b", "c"]));
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, json!({ "x": 1, "y": 2 }))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!([1, 2]));
+
+        let result: JsonMessage = fixture.spawn_and_run(&diagram, json!(5)).unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, json!([5]));
+    }
+
+    #[test]
+    fn test_spread_not_spreadable() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "op1",
+            "ops": {
+                "op1": {
+                    "type": "node",
+                    "builder": "opaque_response",
+                    "next": "spread",
+                },
+                "spread": {
+                    "type": "spread",
+                    "next": { "builtin": "dispose" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(err.code, DiagramErrorCode::NotSpreadable(_)),
+            "{:?}",
+            err
+        );
+    }
+}
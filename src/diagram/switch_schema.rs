@@ -0,0 +1,315 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use cel_interpreter::{Context, Program, Value};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ForkResultOutput, InferenceContext, JsonMessage, Output, TransformError};
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, NextOperation,
+    OperationName, Operations, Templates, TraceInfo, TraceSettings,
+};
+
+/// If the request is serializable, route it to one of several operations by
+/// testing it against [CEL](https://cel.dev/) predicates. Each predicate has
+/// access to a "request" variable which contains the input message, and must
+/// evaluate to a boolean.
+///
+/// * `cases` - Tested in order. The message is sent unchanged to the `next`
+///   of the first case whose `when` predicate evaluates to true. Predicates
+///   after the first match are not evaluated.
+/// * `default` - Where to send the message if none of the predicates are
+///   true. If this is not specified, the message will be disposed.
+///
+/// # Examples
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "route",
+///     "ops": {
+///         "route": {
+///             "type": "switch",
+///             "cases": [
+///                 { "when": "request.battery < 0.2", "next": "charge" },
+///                 { "when": "request.task == \"deliver\"", "next": "deliver" }
+///             ],
+///             "default": "idle"
+///         },
+///         "charge": {
+///             "type": "node",
+///             "builder": "charge",
+///             "next": { "builtin": "terminate" }
+///         },
+///         "deliver": {
+///             "type": "node",
+///             "builder": "deliver",
+///             "next": { "builtin": "terminate" }
+///         },
+///         "idle": {
+///             "type": "node",
+///             "builder": "idle",
+///             "next": { "builtin": "terminate" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SwitchSchema {
+    pub cases: Vec<SwitchCase>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<NextOperation>,
+    /// Specify what happens if an error occurs while evaluating a predicate,
+    /// including when a predicate does not evaluate to a boolean. If you
+    /// specify a target for on_error, then an error message will be sent to
+    /// that target. You can set this to `{ "builtin": "dispose" }` to simply
+    /// ignore errors.
+    ///
+    /// If left unspecified, a failure will be treated like an implicit operation
+    /// failure and behave according to the `on_implicit_error` for this operation's
+    /// scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_error: Option<NextOperation>,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+/// One case of a [`SwitchSchema`].
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SwitchCase {
+    /// A CEL predicate that decides whether the message goes to `next`.
+    pub when: String,
+    pub next: NextOperation,
+}
+
+impl BuildDiagramOperation for SwitchSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        let error_target = self
+            .on_error
+            .as_ref()
+            .map(|on_error| ctx.into_operation_ref(on_error))
+            .unwrap_or(
+                // If no error target was explicitly given then treat this as an
+                // implicit error.
+                ctx.get_implicit_error_target(),
+            );
+
+        // Each case is tested by its own node. Messages that do not match a
+        // case are passed along to the node of the next case.
+        let mut input = None;
+        let mut unmatched: Option<Output<JsonMessage>> = None;
+        for (index, case) in self.cases.iter().enumerate() {
+            let program = Program::compile(&case.when)?;
+            let node = ctx.builder.create_map_block(
+                move |req: JsonMessage| -> Result<Result<JsonMessage, JsonMessage>, TransformError> {
+                    if evaluate_predicate(&program, index, &req)? {
+                        Ok(Ok(req))
+                    } else {
+                        Ok(Err(req))
+                    }
+                },
+            );
+
+            match unmatched {
+                Some(previous) => ctx.builder.connect(previous, node.input),
+                None => input = Some(node.input),
+            }
+
+            let (fork_error, ForkResultOutput { ok, err }) = ctx.builder.create_fork_result();
+            ctx.builder.connect(node.output, fork_error);
+            ctx.add_output_into_target(error_target.clone(), err.into());
+
+            let (fork_match, ForkResultOutput { ok: matched, err }) =
+                ctx.builder.create_fork_result();
+            ctx.builder.connect(ok, fork_match);
+            ctx.add_output_into_target(&case.next, matched.into());
+            unmatched = Some(err);
+        }
+
+        let (input, unmatched) = match (input, unmatched) {
+            (Some(input), Some(unmatched)) => (input, unmatched),
+            _ => {
+                // With no cases, every message goes to the default.
+                let node = ctx.builder.create_map_block(|req: JsonMessage| req);
+                (node.input, node.output)
+            }
+        };
+
+        if let Some(default) = &self.default {
+            ctx.add_output_into_target(default, unmatched.into());
+        }
+
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, input.into(), trace)?;
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        let cases: Vec<_> = self.cases.iter().map(|case| &case.next).collect();
+        ctx.switch(id, &cases, self.default.as_ref())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+fn evaluate_predicate(
+    program: &Program,
+    case: usize,
+    request: &JsonMessage,
+) -> Result<bool, TransformError> {
+    let mut context = Context::default();
+    context.add_variable("request", request)?;
+    match program.execute(&context)? {
+        Value::Bool(value) => Ok(value),
+        other => Err(TransformError::NotABoolean {
+            case,
+            value: format!("{other:?}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_log::test;
+
+    use crate::{
+        Cancellation, CancellationCause, Diagram, JsonMessage, diagram::testing::DiagramTestFixture,
+    };
+
+    fn switch_diagram() -> Diagram {
+        Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "switch",
+            "ops": {
+                "switch": {
+                    "type": "switch",
+                    "cases": [
+                        { "when": "int(request) < 0", "next": "negative" },
+                        { "when": "int(request) < 10", "next": "small" },
+                    ],
+                    "default": "large",
+                },
+                "negative": {
+                    "type": "transform",
+                    "cel": "\"negative\"",
+                    "next": { "builtin": "terminate" },
+                },
+                "small": {
+                    "type": "transform",
+                    "cel": "\"small\"",
+                    "next": { "builtin": "terminate" },
+                },
+                "large": {
+                    "type": "transform",
+                    "cel": "\"large\"",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_switch_cases() {
+        let mut fixture = DiagramTestFixture::new();
+        let diagram = switch_diagram();
+
+        for (request, expected) in [(-3, "negative"), (4, "small"), (25, "large")] {
+            let result: JsonMessage = fixture
+                .spawn_and_run(&diagram, JsonMessage::from(request))
+                .unwrap();
+            assert!(fixture.context.no_unhandled_errors());
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_switch_without_default() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "switch",
+            "ops": {
+                "switch": {
+                    "type": "switch",
+                    "cases": [
+                        { "when": "request.ready", "next": { "builtin": "terminate" } },
+                    ],
+                },
+            },
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, json!({ "ready": true }))
+            .unwrap();
+        assert_eq!(result["ready"], true);
+
+        // A message that matches no case is disposed, so the workflow cannot
+        // terminate.
+        let err = fixture
+            .spawn_and_run::<_, JsonMessage>(&diagram, json!({ "ready": false }))
+            .unwrap_err();
+        assert!(matches!(
+            *err.downcast_ref::<Cancellation>().unwrap().cause,
+            CancellationCause::Unreachable(_)
+        ));
+    }
+
+    #[test]
+    fn test_switch_predicate_not_boolean() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "switch",
+            "ops": {
+                "switch": {
+                    "type": "switch",
+                    "cases": [
+                        { "when": "int(request) + 1", "next": { "builtin": "terminate" } },
+                    ],
+                    "on_error": { "builtin": "cancel" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture
+            .spawn_and_run::<_, JsonMessage>(&diagram, JsonMessage::from(4))
+            .unwrap_err();
+        assert!(err.to_string().contains("boolean"), "{err}");
+    }
+}
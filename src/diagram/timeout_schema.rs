@@ -0,0 +1,247 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, InferenceContext,
+    NextOperation, OperationName, Operations, Templates, TraceInfo, TraceSettings,
+    duration_from_secs,
+};
+
+/// Pass the incoming message along to `next` and start a timer. If `next` is
+/// still active when the timer runs out, its activity will be cancelled and a
+/// trigger `()` will be sent to `on_timeout`.
+///
+/// * `duration` - How long `next` may stay active, in seconds.
+///
+/// Only the activity of the `next` operation itself is watched and cancelled.
+/// Anything it has already passed along downstream will keep running. If
+/// `next` has finished by the time the timer runs out, nothing happens.
+///
+/// Like `delay`, the timer follows the virtual clock of the executor.
+///
+/// # Examples
+///
+/// Give up on a navigation request if it takes longer than two minutes.
+///
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "limit",
+///     "ops": {
+///         "limit": {
+///             "type": "timeout",
+///             "duration": 120,
+///             "next": "navigate",
+///             "on_timeout": "report_failure"
+///         },
+///         "navigate": {
+///             "type": "node",
+///             "builder": "navigate",
+///             "next": { "builtin": "terminate" }
+///         },
+///         "report_failure": {
+///             "type": "node",
+///             "builder": "report_failure",
+///             "next": { "builtin": "cancel" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TimeoutSchema {
+    /// How long the target may stay active, in seconds.
+    pub duration: f64,
+    /// The operation that is being watched. This must be an operation with an
+    /// input slot, such as a node.
+    pub next: NextOperation,
+    /// Where to send a trigger if the timer runs out.
+    pub on_timeout: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+impl BuildDiagramOperation for TimeoutSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        let duration = duration_from_secs(self.duration)?;
+        match &self.next {
+            NextOperation::Name(name) => {
+                ctx.operations.get_op(name)?;
+            }
+            NextOperation::Builtin { .. } => {
+                return Err(DiagramErrorCode::InvalidTimeoutTarget(self.next.clone()));
+            }
+            NextOperation::Namespace(_) => {}
+        }
+
+        let Some(target) = ctx.get_input_slot(&self.next) else {
+            // If the target never gets an input slot then it cannot be
+            // watched by a timeout.
+            return Ok(BuildStatus::defer(DiagramErrorCode::InvalidTimeoutTarget(
+                self.next.clone(),
+            )));
+        };
+
+        let inferred_type = ctx.inferred_message_type(id)?;
+        let (timeout, on_timeout) =
+            ctx.registry
+                .messages
+                .timeout(&inferred_type, duration, target, ctx.builder)?;
+
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, timeout.input, trace)?;
+        ctx.add_output_into_target(&self.next, timeout.output);
+        ctx.add_output_into_target(&self.on_timeout, on_timeout);
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.timeout(id, &self.next, &self.on_timeout)
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_time::TimeUpdateStrategy;
+    use serde_json::json;
+    use std::time::Duration;
+    use test_log::test;
+
+    use crate::{
+        Builder, Diagram, JsonMessage, NodeBuilderOptions, diagram::testing::DiagramTestFixture,
+    };
+
+    use super::*;
+
+    fn fixture_with_virtual_clock() -> DiagramTestFixture {
+        let mut fixture = DiagramTestFixture::new();
+        fixture
+            .context
+            .app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("wait"),
+            |builder: &mut Builder, seconds: f64| {
+                builder.create_delay::<i64>(Duration::from_secs_f64(seconds))
+            },
+        );
+
+        fixture
+    }
+
+    fn timeout_diagram(work_seconds: f64) -> Diagram {
+        Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "timeout",
+            "ops": {
+                "timeout": {
+                    "type": "timeout",
+                    "duration": 1.0,
+                    "next": "work",
+                    "on_timeout": "timed_out",
+                },
+                "work": {
+                    "type": "node",
+                    "builder": "wait",
+                    "config": work_seconds,
+                    "next": "multiply",
+                },
+                "multiply": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 10,
+                    "next": { "builtin": "terminate" },
+                },
+                "timed_out": {
+                    "type": "transform",
+                    "cel": "-1",
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_timeout_expires() {
+        let mut fixture = fixture_with_virtual_clock();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&timeout_diagram(60.0), JsonMessage::from(4), 100)
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, -1);
+    }
+
+    #[test]
+    fn test_timeout_not_reached() {
+        let mut fixture = fixture_with_virtual_clock();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&timeout_diagram(0.5), JsonMessage::from(4), 100)
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, 40);
+    }
+
+    #[test]
+    fn test_timeout_builtin_is_invalid() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "timeout",
+            "ops": {
+                "timeout": {
+                    "type": "timeout",
+                    "duration": 1.0,
+                    "next": { "builtin": "terminate" },
+                    "on_timeout": { "builtin": "dispose" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(err.code, DiagramErrorCode::InvalidTimeoutTarget(_)),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -22,7 +22,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error as ThisError;
 
-use crate::{ForkResultOutput, InferenceContext, JsonMessage};
+use crate::{Builder, ForkResultOutput, InferenceContext, JsonMessage, Node};
 
 use super::{
     BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, NextOperation,
@@ -42,6 +42,9 @@ pub enum TransformError {
 
     #[error("Failed to convert the CEL program response to JSON: {0}")]
     ConvertToJson(String),
+
+    #[error("The predicate of switch case [{case}] evaluated to [{value}] instead of a boolean")]
+    NotABoolean { case: usize, value: String },
 }
 
 impl From<ParseError> for DiagramErrorCode {
@@ -117,17 +120,7 @@ impl BuildDiagramOperation for TransformSchema {
         id: &OperationName,
         ctx: &mut BuilderContext,
     ) -> Result<BuildStatus, DiagramErrorCode> {
-        let program = Program::compile(&self.cel)?;
-        let node = ctx.builder.create_map_block(
-            move |req: JsonMessage| -> Result<JsonMessage, TransformError> {
-                let mut context = Context::default();
-                context.add_variable("request", req)?;
-                Ok(program
-                    .execute(&context)?
-                    .json()
-                    .map_err(|err| TransformError::ConvertToJson(err.to_string()))?)
-            },
-        );
+        let node = create_cel_transform(ctx.builder, &self.cel)?;
 
         let error_target = self
             .on_error
@@ -162,6 +155,26 @@ impl BuildDiagramOperation for TransformSchema {
     }
 }
 
+/// Create a node that transforms its request by running it through the
+/// [CEL](https://cel.dev/) program of `cel`, the same way that the transform
+/// operation of a diagram does.
+pub fn create_cel_transform(
+    builder: &mut Builder,
+    cel: &str,
+) -> Result<Node<JsonMessage, Result<JsonMessage, TransformError>>, DiagramErrorCode> {
+    let program = Program::compile(cel)?;
+    Ok(builder.create_map_block(
+        move |req: JsonMessage| -> Result<JsonMessage, TransformError> {
+            let mut context = Context::default();
+            context.add_variable("request", req)?;
+            Ok(program
+                .execute(&context)?
+                .json()
+                .map_err(|err| TransformError::ConvertToJson(err.to_string()))?)
+        },
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -0,0 +1,406 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{TrimBranch, TrimPoint};
+
+use super::{
+    BuildDiagramOperation, BuildStatus, BuilderContext, DiagramErrorCode, InferenceContext,
+    NextOperation, OperationName, Operations, Templates, TraceInfo, TraceSettings,
+};
+
+/// Cancel all activity along one or more branches of the workflow, then pass
+/// the incoming message along to `next` unchanged. The message is only passed
+/// along after the trimming has finished.
+///
+/// Each branch refers to operations in the diagram by name:
+///
+/// * `single_point` - Cancel only the activity of a single operation.
+/// * `downstream` - Cancel the activity of an operation and everything that
+///   is downstream of it.
+/// * `between` - Cancel every operation along any path from the `from` point
+///   to the `to` point.
+/// * `span` - Cancel every operation along any path from the `from` point to
+///   any of the `to` points.
+///
+/// Points are included in the trim by default. To exclude a point, use
+/// `{ "operation": <name>, "inclusive": false }` instead of only the name.
+///
+/// A point refers to the input of an operation, so any implicit conversion
+/// (e.g. deserialization) that a message is going through on its way into the
+/// point will not be trimmed.
+///
+/// # Examples
+///
+/// Preempt the current motion whenever a new goal arrives.
+///
+/// ```
+/// # crossflow::Diagram::from_json_str(r#"
+/// {
+///     "version": "0.1.0",
+///     "start": "preempt",
+///     "ops": {
+///         "preempt": {
+///             "type": "trim",
+///             "branches": [
+///                 { "downstream": "plan_motion" }
+///             ],
+///             "next": "plan_motion"
+///         },
+///         "plan_motion": {
+///             "type": "node",
+///             "builder": "plan_motion",
+///             "next": "follow_path"
+///         },
+///         "follow_path": {
+///             "type": "node",
+///             "builder": "follow_path",
+///             "next": { "builtin": "terminate" }
+///         }
+///     }
+/// }
+/// # "#)?;
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TrimSchema {
+    /// The branches of the workflow whose activity will be cancelled.
+    pub branches: Vec<TrimBranchSchema>,
+    pub next: NextOperation,
+    #[serde(flatten)]
+    pub trace_settings: TraceSettings,
+}
+
+/// Describe a branch of the workflow to trim. See [`TrimBranch`].
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimBranchSchema {
+    /// Trim only a single operation.
+    SinglePoint(NextOperation),
+    /// Trim everything downstream of a point.
+    Downstream(TrimPointSchema),
+    /// Trim the operations that fill the span between two points.
+    Between {
+        from: TrimPointSchema,
+        to: TrimPointSchema,
+    },
+    /// Trim every operation along some path between the `from` point and any
+    /// of the `to` points.
+    Span {
+        from: TrimPointSchema,
+        to: Vec<TrimPointSchema>,
+    },
+}
+
+/// Refer to an operation where a trim begins or ends.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged, rename_all = "snake_case")]
+pub enum TrimPointSchema {
+    /// Choose whether the operation is included in the trim.
+    Point {
+        operation: NextOperation,
+        inclusive: bool,
+    },
+    /// The operation will be included in the trim.
+    Inclusive(NextOperation),
+}
+
+impl TrimPointSchema {
+    fn operation(&self) -> &NextOperation {
+        match self {
+            Self::Point { operation, .. } => operation,
+            Self::Inclusive(operation) => operation,
+        }
+    }
+
+    fn is_inclusive(&self) -> bool {
+        match self {
+            Self::Point { inclusive, .. } => *inclusive,
+            Self::Inclusive(_) => true,
+        }
+    }
+}
+
+impl TrimBranchSchema {
+    /// Get every operation that this branch refers to.
+    fn operations(&self) -> Vec<&NextOperation> {
+        match self {
+            Self::SinglePoint(operation) => vec![operation],
+            Self::Downstream(from) => vec![from.operation()],
+            Self::Between { from, to } => vec![from.operation(), to.operation()],
+            Self::Span { from, to } => std::iter::once(from)
+                .chain(to)
+                .map(TrimPointSchema::operation)
+                .collect(),
+        }
+    }
+
+    /// Convert this into a [`TrimBranch`]. If one of the operations in the
+    /// branch does not have an input slot yet, that operation will be returned
+    /// as an error.
+    fn to_trim_branch<'a>(&'a self, ctx: &BuilderContext) -> Result<TrimBranch, &'a NextOperation> {
+        let point = |point: &'a TrimPointSchema| {
+            let operation = point.operation();
+            ctx.get_input_slot(operation)
+                .map(|input| TrimPoint::from_dyn_input(&input, point.is_inclusive()))
+                .ok_or(operation)
+        };
+
+        let branch = match self {
+            Self::SinglePoint(operation) => {
+                let input = ctx.get_input_slot(operation).ok_or(operation)?;
+                let point = TrimPoint::from_dyn_input(&input, true);
+                // A span that begins and ends at the same inclusive point only
+                // trims that point.
+                TrimBranch::between(point, point)
+            }
+            Self::Downstream(from) => TrimBranch::downstream(point(from)?),
+            Self::Between { from, to } => TrimBranch::between(point(from)?, point(to)?),
+            Self::Span { from, to } => {
+                let to = to.iter().map(point).collect::<Result<Vec<_>, _>>()?;
+                TrimBranch::span(point(from)?, to)
+            }
+        };
+
+        Ok(branch)
+    }
+}
+
+impl BuildDiagramOperation for TrimSchema {
+    fn build_diagram_operation(
+        &self,
+        id: &OperationName,
+        ctx: &mut BuilderContext,
+    ) -> Result<BuildStatus, DiagramErrorCode> {
+        for operation in self.branches.iter().flat_map(TrimBranchSchema::operations) {
+            match operation {
+                NextOperation::Name(name) => {
+                    ctx.operations.get_op(name)?;
+                }
+                NextOperation::Builtin { .. } => {
+                    return Err(DiagramErrorCode::InvalidTrimPoint(operation.clone()));
+                }
+                NextOperation::Namespace(_) => {}
+            }
+        }
+
+        let mut branches = Vec::new();
+        for branch in &self.branches {
+            match branch.to_trim_branch(ctx) {
+                Ok(branch) => branches.push(branch),
+                Err(unbuilt) => {
+                    // If the operation never gets an input slot then it cannot
+                    // be used as a trim point.
+                    return Ok(BuildStatus::defer(DiagramErrorCode::InvalidTrimPoint(
+                        unbuilt.clone(),
+                    )));
+                }
+            }
+        }
+
+        let inferred_type = ctx.inferred_message_type(id)?;
+        let trim = ctx
+            .registry
+            .messages
+            .trim(&inferred_type, branches, ctx.builder)?;
+
+        let trace = TraceInfo::new(self, self.trace_settings.trace)?;
+        ctx.set_input_for_target(id, trim.input, trace)?;
+        ctx.add_output_into_target(&self.next, trim.output);
+        Ok(BuildStatus::Finished)
+    }
+
+    fn apply_message_type_constraints(
+        &self,
+        id: &OperationName,
+        ctx: &mut InferenceContext,
+    ) -> Result<(), DiagramErrorCode> {
+        ctx.pass_through(id, &self.next);
+        Ok(())
+    }
+
+    fn child_operations(&self, _: &Templates) -> Result<Option<Operations>, DiagramErrorCode> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_log::test;
+
+    use crate::{
+        Builder, Diagram, DiagramOperation, JsonMessage, NodeBuilderOptions,
+        diagram::testing::DiagramTestFixture,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_trim_downstream() {
+        let mut fixture = DiagramTestFixture::new();
+
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("double_async"),
+            |builder: &mut Builder, _config: ()| {
+                builder.create_map_async(|value: i64| async move { 2 * value })
+            },
+        );
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "entry",
+            "ops": {
+                "entry": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 1,
+                    "next": "fork",
+                },
+                "fork": {
+                    "type": "fork_clone",
+                    "next": ["noop", "trim"],
+                },
+                "noop": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 1,
+                    "next": "double_a",
+                },
+                "double_a": {
+                    "type": "node",
+                    "builder": "double_async",
+                    "next": { "builtin": "terminate" },
+                },
+                "trim": {
+                    "type": "trim",
+                    "branches": [
+                        { "downstream": "noop" },
+                    ],
+                    "next": "double_b",
+                },
+                "double_b": {
+                    "type": "node",
+                    "builder": "multiply_by",
+                    "config": 2,
+                    "next": "double_a",
+                },
+            },
+        }))
+        .unwrap();
+
+        // The activity that went through noop gets trimmed, so only the
+        // message that went through both doublers reaches the terminal.
+        let result: JsonMessage = fixture
+            .spawn_and_run(&diagram, JsonMessage::from(2))
+            .unwrap();
+        assert!(fixture.context.no_unhandled_errors());
+        assert_eq!(result, 8);
+    }
+
+    #[test]
+    fn test_trim_point_formats() {
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "trim",
+            "ops": {
+                "trim": {
+                    "type": "trim",
+                    "branches": [
+                        { "single_point": "a" },
+                        { "between": { "from": "a", "to": { "operation": "b", "inclusive": false } } },
+                        { "span": { "from": "a", "to": ["b", { "section": "c" }] } },
+                    ],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let DiagramOperation::Trim(trim) = diagram.ops.get("trim").unwrap().as_ref() else {
+            panic!("wrong operation type");
+        };
+
+        let TrimBranchSchema::Between { to, .. } = &trim.branches[1] else {
+            panic!("wrong branch type");
+        };
+        assert!(!to.is_inclusive());
+
+        let TrimBranchSchema::Span { to, .. } = &trim.branches[2] else {
+            panic!("wrong branch type");
+        };
+        assert!(to.iter().all(TrimPointSchema::is_inclusive));
+        assert!(matches!(to[1].operation(), NextOperation::Namespace(_)));
+    }
+
+    #[test]
+    fn test_trim_unknown_operation() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "trim",
+            "ops": {
+                "trim": {
+                    "type": "trim",
+                    "branches": [
+                        { "downstream": "missing" },
+                    ],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(err.code, DiagramErrorCode::OperationNotFound(_)),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_trim_builtin_is_invalid() {
+        let mut fixture = DiagramTestFixture::new();
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "trim",
+            "ops": {
+                "trim": {
+                    "type": "trim",
+                    "branches": [
+                        { "single_point": { "builtin": "dispose" } },
+                    ],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let err = fixture.spawn_json_io_workflow(&diagram).unwrap_err();
+        assert!(
+            matches!(err.code, DiagramErrorCode::InvalidTrimPoint(_)),
+            "{:?}",
+            err
+        );
+    }
+}
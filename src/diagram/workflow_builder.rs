@@ -22,13 +22,13 @@ use std::{
 };
 
 use crate::{
-    AnyBuffer, BufferMap, Builder, BuilderScopeContext, IdentifierRef, JsonMessage, PortRef, Scope,
-    ScriptMessage, StreamPack, diagram::script_environment_registration::ArcScriptEnvironment,
-    dyn_node::DynStreamInputPack,
+    AnyBuffer, BufferMap, Builder, BuilderScopeContext, IdentifierRef, InspectableJsonBuffer,
+    JsonBuffer, JsonMessage, PortRef, Scope, ScriptMessage, StreamPack,
+    diagram::script_environment_registration::ArcScriptEnvironment, dyn_node::DynStreamInputPack,
 };
 
 #[cfg(feature = "trace")]
-use crate::{OperationInfo, Trace};
+use crate::{DiagramReplay, OperationInfo, Trace};
 
 use super::{
     BufferSelection, BuilderId, Diagram, DiagramContext, DiagramElementRegistry, DiagramError,
@@ -50,6 +50,8 @@ struct DiagramConstruction {
     outputs_into_target: HashMap<OperationRef, Vec<DynOutput>>,
     /// A map of what buffers exist in the diagram
     buffers: HashMap<OperationRef, BufferRef>,
+    /// The input slots that have been set for operations
+    input_slots: HashMap<OperationRef, DynInputSlot>,
     /// Operations that were spawned by another operation.
     generated_operations: Vec<UnfinishedOperation>,
     /// Scripting environments that have been built
@@ -93,9 +95,25 @@ pub struct BuilderContext<'a, 'c, 'w, 's, 'b> {
     construction: &'c mut DiagramConstruction,
     pub builder: &'c mut Builder<'w, 's, 'b>,
     diagram_context: DiagramContext<'a>,
+    #[cfg_attr(not(feature = "trace"), allow(dead_code))]
+    options: &'a WorkflowBuildOptions,
+}
+
+/// Optional behaviors that change how the operations of a diagram get built.
+#[derive(Default, Clone)]
+pub(crate) struct WorkflowBuildOptions {
+    /// Nodes that should send out recorded messages instead of running.
+    #[cfg(feature = "trace")]
+    pub(crate) replay: Option<DiagramReplay>,
 }
 
 impl<'a, 'c, 'w, 's, 'b> BuilderContext<'a, 'c, 'w, 's, 'b> {
+    /// Get the replay that this workflow is being built for, if any.
+    #[cfg(feature = "trace")]
+    pub(crate) fn replay(&self) -> Option<&'a DiagramReplay> {
+        self.options.replay.as_ref()
+    }
+
     /// Get the message type that has been inferred for a certain input/output
     /// port. This will only include ports that your operation added constraints
     /// for.
@@ -157,6 +175,9 @@ impl<'a, 'c, 'w, 's, 'b> BuilderContext<'a, 'c, 'w, 's, 'b> {
     ) -> Result<(), DiagramErrorCode> {
         let operation = self.into_operation_ref(operation);
         let connect = standard_input_connection(input, &self.registry)?;
+        self.construction
+            .input_slots
+            .insert(operation.clone(), input);
 
         #[cfg(feature = "trace")]
         {
@@ -185,6 +206,14 @@ impl<'a, 'c, 'w, 's, 'b> BuilderContext<'a, 'c, 'w, 's, 'b> {
         self.impl_connect_into_target(operation, connect)
     }
 
+    /// Get the input slot that was set for an operation using
+    /// [`Self::set_input_for_target`]. This will be [`None`] if the operation
+    /// has not been built yet or if it does not use a standard input slot.
+    pub fn get_input_slot(&self, operation: impl Into<OperationRef>) -> Option<DynInputSlot> {
+        let operation = self.into_operation_ref(operation);
+        self.construction.input_slots.get(&operation).copied()
+    }
+
     /// Set the implementation for how outputs connect into this target. This is
     /// a more general method than [`Self::set_input_for_target`].
     ///
@@ -239,6 +268,15 @@ impl<'a, 'c, 'w, 's, 'b> BuilderContext<'a, 'c, 'w, 's, 'b> {
         buffer: AnyBuffer,
         trace: TraceInfo,
     ) -> Result<(), DiagramErrorCode> {
+        if let Some(json_buffer) = buffer.downcast_buffer::<JsonBuffer>() {
+            // Let the buffer be found by its entity, e.g. to inspect its
+            // contents while debugging.
+            self.builder
+                .commands()
+                .entity(buffer.id())
+                .insert(InspectableJsonBuffer(json_buffer));
+        }
+
         let input: DynInputSlot = buffer.into();
         self.set_input_for_target(operation.clone(), input, trace)?;
 
@@ -611,11 +649,12 @@ pub trait ConnectIntoTarget {
     }
 }
 
-pub(super) fn create_workflow<Request, Response, Streams>(
+pub(crate) fn create_workflow<Request, Response, Streams>(
     scope: Scope<Request, Response, Streams>,
     builder: &mut Builder,
     registry: &DiagramElementRegistry,
     diagram: &Diagram,
+    options: &WorkflowBuildOptions,
 ) -> Result<(), DiagramError>
 where
     Request: 'static + Send + Sync,
@@ -642,6 +681,7 @@ where
             builder,
             message_type_inference,
             registry,
+            options,
             diagram_context: DiagramContext {
                 operations: diagram.ops.clone(),
                 templates: &diagram.templates,
@@ -689,6 +729,7 @@ where
                 builder: &mut builder,
                 message_type_inference,
                 registry,
+                options,
                 diagram_context: DiagramContext {
                     operations: unfinished.sibling_ops.clone(),
                     templates: &diagram.templates,
@@ -745,6 +786,7 @@ where
                     builder: &mut builder,
                     message_type_inference,
                     registry,
+                    options,
                     diagram_context: DiagramContext {
                         operations: diagram.ops.clone(),
                         templates: &diagram.templates,
@@ -43,6 +43,9 @@ pub use register_zenoh_publisher::*;
 mod register_zenoh_querier;
 pub use register_zenoh_querier::*;
 
+mod register_zenoh_queryable;
+pub use register_zenoh_queryable::*;
+
 mod register_zenoh_subscription;
 pub use register_zenoh_subscription::*;
 
@@ -54,11 +57,19 @@ impl DiagramElementRegistry {
     /// - `zenoh_publisher` - advertise a publisher when the workflow is created,
     ///   and then publish each message that gets passed into the node
     /// - `zenoh_querier` - query
+    /// - `zenoh_queryable` - begin answering queries for a key when the node is
+    ///   triggered, and stream out each incoming [`ZenohQuery`] until cancelled.
+    ///   Downstream nodes answer the queries with [`ZenohQuery::reply`] or
+    ///   [`ZenohQuery::reply_err`].
+    /// - `zenoh_reply` / `zenoh_reply_err` - answer a query from a
+    ///   `zenoh_queryable` node with a message or an error. The input is a
+    ///   [`ZenohReply`], usually made by joining the query with the message.
     pub fn enable_zenoh(&mut self, zenoh_session_config: ::zenoh::Config) {
         let ensure_session = EnsureZenohSession::new(zenoh_session_config);
         self.register_zenoh_subscription(ensure_session.clone());
         self.register_zenoh_publisher(ensure_session.clone());
-        self.register_zenoh_querier(ensure_session);
+        self.register_zenoh_querier(ensure_session.clone());
+        self.register_zenoh_queryable(ensure_session);
 
         // Make sure this is registered since it gets used by canceller streams
         self.opt_out()
@@ -741,4 +752,122 @@ mod tests {
         assert_eq!(result["y"].as_f64().unwrap(), 4.0);
         assert_eq!(result["yaw"].as_f64().unwrap(), 0.0);
     }
+
+    #[test]
+    fn test_zenoh_queryable() {
+        let mut fixture = DiagramTestFixture::new();
+        fixture.registry.enable_zenoh(::zenoh::Config::default());
+        fixture.registry.register_message::<[f64; 2]>();
+
+        const KEY: &str = "test_zenoh_queryable_add";
+
+        // The diagram answers its own query, so it acts as both the service
+        // and the client. Queries get answered with the sum of two numbers, or
+        // with an error if the payload does not contain two numbers.
+        //
+        // The querier waits for a matching queryable before it sends its
+        // query, so the query cannot go out before the queryable is declared.
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "initialize",
+            "ops": {
+                "initialize": {
+                    "type": "fork_clone",
+                    "next": ["trigger_queryable", "query"]
+                },
+                "trigger_queryable": {
+                    "type": "transform",
+                    "cel": "null",
+                    "next": "queryable"
+                },
+                "queryable": {
+                    "type": "node",
+                    "builder": "zenoh_queryable",
+                    "config": {
+                        "key": KEY,
+                        "decoder": "json",
+                        "encoder": "json"
+                    },
+                    "stream_out": { "out": "fork_query" },
+                    "next": { "builtin": "dispose" }
+                },
+                "fork_query": {
+                    "type": "fork_clone",
+                    "next": ["incoming", "check"]
+                },
+                "incoming": { "type": "buffer" },
+                "check": {
+                    "type": "switch",
+                    "cases": [
+                        { "when": "size(request.payload) == 2", "next": "add" }
+                    ],
+                    "default": "reject"
+                },
+                "add": {
+                    "type": "transform",
+                    "cel": "request.payload[0] + request.payload[1]",
+                    "next": "sum"
+                },
+                "reject": {
+                    "type": "transform",
+                    "cel": "\"expected two numbers\"",
+                    "next": "invalid"
+                },
+                "sum": { "type": "buffer" },
+                "invalid": { "type": "buffer" },
+                "join_sum": {
+                    "type": "join",
+                    "buffers": {
+                        "query": "incoming",
+                        "message": "sum"
+                    },
+                    "next": "reply"
+                },
+                "join_invalid": {
+                    "type": "join",
+                    "buffers": {
+                        "query": "incoming",
+                        "message": "invalid"
+                    },
+                    "next": "reply_err"
+                },
+                "reply": {
+                    "type": "node",
+                    "builder": "zenoh_reply",
+                    "next": { "builtin": "dispose" }
+                },
+                "reply_err": {
+                    "type": "node",
+                    "builder": "zenoh_reply_err",
+                    "next": { "builtin": "dispose" }
+                },
+                "query": {
+                    "type": "node",
+                    "builder": "zenoh_querier",
+                    "config": {
+                        "key": KEY,
+                        "encoder": "json",
+                        "decoder": "json",
+                        "wait_for_matching": "always"
+                    },
+                    "stream_out": {
+                        "out": { "builtin": "terminate" },
+                        "out_error": { "builtin": "terminate" }
+                    },
+                    "next": { "builtin": "dispose" }
+                }
+            }
+        }))
+        .unwrap();
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, [2.0, 3.0], Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(result.as_f64().unwrap(), 5.0);
+
+        let result: JsonMessage = fixture
+            .spawn_and_run_with_conditions(&diagram, json!("not numbers"), Duration::from_secs(10))
+            .unwrap();
+        assert!(result.as_str().unwrap().contains("query returned an error"));
+    }
 }
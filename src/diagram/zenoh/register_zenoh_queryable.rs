@@ -0,0 +1,324 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use super::*;
+
+use ::zenoh::query::Query;
+use bevy_ecs::prelude::{Res, World};
+use futures_lite::future::race;
+use thiserror::Error as ThisError;
+use tokio::sync::mpsc::unbounded_channel;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ZenohQueryableConfig {
+    /// The key expression that this queryable will answer queries for.
+    pub key: Arc<str>,
+    /// The encoding of the payloads of incoming queries.
+    pub decoder: ZenohEncodingConfig,
+    /// How replies will be encoded.
+    pub encoder: ZenohEncodingConfig,
+    /// Declare that this queryable can answer every query for its whole key
+    /// expression. Queriers that target all complete queryables will only
+    /// reach queryables that set this.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub complete: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub locality: ZenohLocalityConfig,
+}
+
+#[derive(StreamPack)]
+pub struct ZenohQueryableStreams {
+    /// Queries that arrive at the queryable
+    pub out: ZenohQuery,
+    /// Error messages that are produced if an error occurs while receiving or
+    /// decoding a query
+    pub out_error: String,
+    /// A way to stop answering queries
+    pub canceller: UnboundedSender<JsonMessage>,
+}
+
+/// A query that was received by a `zenoh_queryable` node. Use [`Self::reply`]
+/// or [`Self::reply_err`] to answer it, or pass it into a `zenoh_reply` or
+/// `zenoh_reply_err` node as part of a [`ZenohReply`]. The querier is told that
+/// there will be no more replies once every clone of this query has been dropped.
+///
+/// Serializing a query only gives its key, parameters, and payload, so it
+/// cannot be deserialized back into a query that can be answered.
+#[derive(Clone, Serialize, JsonSchema)]
+pub struct ZenohQuery {
+    /// The key expression that the query was sent to.
+    pub key: String,
+    /// Key/value parameters that were sent with the query.
+    pub parameters: HashMap<String, String>,
+    /// The decoded payload of the query, if it had one.
+    pub payload: Option<JsonMessage>,
+    #[serde(skip)]
+    query: Query,
+    #[serde(skip)]
+    encoder: Codec,
+}
+
+/// The input of the `zenoh_reply` and `zenoh_reply_err` nodes. Use a join
+/// operation to put together the query with the message that answers it.
+#[derive(Clone, Joined)]
+pub struct ZenohReply {
+    /// The query to answer.
+    pub query: ZenohQuery,
+    /// The message to reply with.
+    pub message: JsonMessage,
+}
+
+impl ZenohQuery {
+    /// Send a reply to the querier. The message will be encoded using the
+    /// encoder of the queryable node.
+    pub async fn reply(&self, message: &JsonMessage) -> Result<(), ZenohQueryableError> {
+        let payload = self
+            .encoder
+            .encode(message)
+            .map_err(ZenohQueryableError::EncodingError)?;
+
+        self.query
+            .reply(self.query.key_expr().clone(), payload)
+            .encoding(self.encoder.encoding())
+            .await
+            .map_err(ArcError::new)?;
+
+        Ok(())
+    }
+
+    /// Send an error reply to the querier. Error messages are always encoded
+    /// as JSON.
+    pub async fn reply_err(&self, message: &JsonMessage) -> Result<(), ZenohQueryableError> {
+        let payload = Codec::Json
+            .encode(message)
+            .map_err(ZenohQueryableError::EncodingError)?;
+
+        self.query
+            .reply_err(payload)
+            .encoding(Codec::Json.encoding())
+            .await
+            .map_err(ArcError::new)?;
+
+        Ok(())
+    }
+}
+
+#[derive(ThisError, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ZenohQueryableError {
+    #[error("the queryable was already active")]
+    AlreadyActive,
+    #[error("the active buffer tracker was despawned")]
+    BufferDespawned,
+    #[error("the zenoh session was removed from its resource")]
+    SessionRemoved,
+    #[error("error while encoding message: {}", .0)]
+    EncodingError(String),
+    #[error("{}", .0)]
+    ZenohError(#[from] ArcError),
+}
+
+impl DiagramElementRegistry {
+    pub(super) fn register_zenoh_queryable(&mut self, ensure_session: EnsureZenohSession) {
+        self.opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder_fallible(
+                NodeBuilderOptions::new("zenoh_queryable")
+                    .with_default_display_text("Zenoh Queryable"),
+                move |builder, config: ZenohQueryableConfig| {
+                    builder.commands().queue(ensure_session.clone());
+
+                    let active_buffer = builder.create_buffer::<()>(BufferSettings::default());
+                    let access = builder.create_buffer_access::<(), _>(active_buffer);
+
+                    let decoder: Codec = (&config.decoder).try_into()?;
+                    let encoder: Codec = (&config.encoder).try_into()?;
+
+                    let callback =
+                        move |input: Async<((), BufferKey<()>), ZenohQueryableStreams>,
+                              mut active: BufferAccessMut<()>,
+                              session: Res<ZenohSession>| {
+                            let request_id = input.id;
+                            // First make sure an instance of this node isn't already active
+                            let already_active = active
+                                .get_mut(request_id, &input.request.1)
+                                .map_err(|_| ZenohQueryableError::BufferDespawned)
+                                .and_then(|mut active_buffer| {
+                                    if active_buffer.is_empty() {
+                                        active_buffer.push(());
+                                        Ok(())
+                                    } else {
+                                        Err(ZenohQueryableError::AlreadyActive)
+                                    }
+                                });
+
+                            let session = session.outcome.clone();
+
+                            let (sender, mut receiver) = unbounded_channel();
+                            input.streams.canceller.send(sender);
+
+                            let config = config.clone();
+                            let decoder = decoder.clone();
+                            let encoder = encoder.clone();
+                            async move {
+                                // Return right away if the queryable is already active
+                                already_active?;
+
+                                let cancel = receiver.recv();
+
+                                let active_key = input.request.1;
+
+                                // Keep the queryable inside this block so it
+                                // gets undeclared before the active buffer is
+                                // cleared, whether it finished or got cancelled.
+                                let r = async move {
+                                    let answering = async move {
+                                        let session = session
+                                            .await
+                                            .map_err(|_| ZenohQueryableError::SessionRemoved)?
+                                            .map_err(ZenohQueryableError::ZenohError)?;
+
+                                        let queryable = session
+                                            .declare_queryable(config.key.as_ref())
+                                            .complete(config.complete)
+                                            .allowed_origin(config.locality.into())
+                                            .await
+                                            .map_err(ArcError::new)?;
+
+                                        loop {
+                                            // An error here means the queryable
+                                            // was closed, so no more queries
+                                            // can arrive.
+                                            let query = queryable
+                                                .recv_async()
+                                                .await
+                                                .map_err(ArcError::new)?;
+
+                                            let payload = match query
+                                                .payload()
+                                                .map(|payload| decoder.decode_payload(payload))
+                                                .transpose()
+                                            {
+                                                Ok(payload) => payload,
+                                                Err(msg) => {
+                                                    // Let the querier know that
+                                                    // its query was not usable.
+                                                    let _ = query
+                                                        .reply_err(msg.clone())
+                                                        .encoding(Encoding::TEXT_PLAIN)
+                                                        .await;
+                                                    input.streams.out_error.send(msg);
+                                                    continue;
+                                                }
+                                            };
+
+                                            input.streams.out.send(ZenohQuery {
+                                                key: query.key_expr().to_string(),
+                                                parameters: query
+                                                    .parameters()
+                                                    .iter()
+                                                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                                                    .collect(),
+                                                payload,
+                                                query,
+                                                encoder: encoder.clone(),
+                                            });
+                                        }
+                                    };
+
+                                    race(answering, receive_cancel::<ZenohQueryableError>(cancel))
+                                        .await
+                                }
+                                .await;
+
+                                // Clear the buffer to indicate that the queryable
+                                // could be restarted.
+                                let _ = input
+                                    .channel
+                                    .commands(move |commands| {
+                                        commands.queue(move |world: &mut World| {
+                                            let _ = world.buffer_mut(
+                                                request_id,
+                                                &active_key,
+                                                |mut active_buffer| {
+                                                    active_buffer.drain(..);
+                                                },
+                                            );
+                                        });
+                                    })
+                                    .await;
+
+                                // Return the result from earlier
+                                r
+                            }
+                        };
+
+                    let node = builder.create_node(callback.into_callback());
+                    builder.connect(access.output, node.input);
+
+                    Ok(Node::<_, _, ZenohQueryableStreams> {
+                        input: access.input,
+                        output: node.output,
+                        streams: node.streams,
+                    })
+                },
+            )
+            .with_common_request()
+            .with_common_response()
+            .with_result();
+
+        // Let queries be read by operations that work on JSON, such as transform.
+        self.opt_out()
+            .no_deserializing()
+            .register_message::<ZenohQuery>();
+
+        self.opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder(
+                NodeBuilderOptions::new("zenoh_reply").with_default_display_text("Zenoh Reply"),
+                |builder, _config: ()| {
+                    builder.create_map(|input: Async<ZenohReply>| async move {
+                        let ZenohReply { query, message } = input.request;
+                        query.reply(&message).await
+                    })
+                },
+            )
+            .with_join()
+            .with_common_response()
+            .with_result();
+
+        self.opt_out()
+            .no_serializing()
+            .no_deserializing()
+            .register_node_builder(
+                NodeBuilderOptions::new("zenoh_reply_err")
+                    .with_default_display_text("Zenoh Reply Error"),
+                |builder, _config: ()| {
+                    builder.create_map(|input: Async<ZenohReply>| async move {
+                        let ZenohReply { query, message } = input.request;
+                        query.reply_err(&message).await
+                    })
+                },
+            )
+            .with_join()
+            .with_common_response()
+            .with_result();
+    }
+}
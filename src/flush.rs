@@ -32,7 +32,7 @@ use crate::{
     ReachableRequest, SeriesLifecycleChange, SeriesLifecycleChannel, ServiceHook, ServiceLifecycle,
     ServiceLifecycleChannel, UnhandledErrors, UnusedTarget, WakeQueue, awaken_task,
     dispose_for_despawned_service, drop_series_target, execute_operation,
-    validate_scope_reachability,
+    validate_scope_reachability, wake_sleep_timers,
 };
 
 #[cfg(feature = "single_threaded_async")]
@@ -84,7 +84,7 @@ impl FlushParameters {
 }
 
 pub fn flush_execution() -> ScheduleConfigs<ScheduleSystem> {
-    flush_execution_impl.into_configs()
+    (wake_sleep_timers, flush_execution_impl).chain()
 }
 
 fn flush_execution_impl(
@@ -106,6 +106,15 @@ fn flush_execution_impl(
             world.resource_scope::<DebugRoster, _>(|world, mut debug_roster| {
                 debug_roster.release_unpaused(world, &mut roster);
             });
+
+            // Pauses and unpauses that were requested directly through the
+            // resource would otherwise not be announced until the session
+            // takes its next input.
+            world.resource_scope::<Debug, _>(|world, mut debug| {
+                debug
+                    .bypass_change_detection()
+                    .notify_session_changes(world);
+            });
         }
 
         // Queue any operations that needed to be deferred
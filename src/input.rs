@@ -39,6 +39,9 @@ use crate::{
     Debug, DebugRoster, MessageSent, Trace, TraceToggle, TracedEvent, UniversalTraceToggle,
 };
 
+#[cfg(feature = "diagnostics")]
+use crate::OperationMetrics;
+
 pub type Seq = u32;
 
 /// This contains data that has been provided as input into an operation, along
@@ -420,6 +423,17 @@ impl ManageInput for World {
         if let Some(mut storage) = self.get_mut::<InputStorage<T>>(target) {
             let _target_seq = storage.push(session, data);
 
+            #[cfg(feature = "diagnostics")]
+            {
+                OperationMetrics::input_arrived(
+                    self,
+                    target,
+                    session,
+                    _target_seq,
+                    route.outputs.iter().map(|output| output.source),
+                );
+            }
+
             #[cfg(feature = "trace")]
             {
                 if perform_trace {
@@ -492,13 +506,19 @@ impl ManageInput for World {
         {
             let mut storage = self.get_mut::<InputStorage<T>>(source).or_broken()?;
             let input = storage.reverse_queue.pop();
+
+            #[cfg(feature = "diagnostics")]
+            if let Some(input) = &input {
+                OperationMetrics::input_taken(self, source, input.session, input.seq);
+            }
+
             return Ok(input);
         }
 
         #[cfg(feature = "trace")]
         {
             self.get_resource_or_init::<Debug>();
-            self.resource_scope::<Debug, _>(|world, mut debug| {
+            let input = self.resource_scope::<Debug, _>(|world, mut debug| {
                 if !debug.is_active() {
                     // Revert to the usual implementation of popping the next
                     let mut storage = world.get_mut::<InputStorage<T>>(source).or_broken()?;
@@ -511,19 +531,24 @@ impl ManageInput for World {
                         let rev_next = storage.reverse_queue.iter().rev().position(|input| {
                             let session = input.session;
                             let seq = input.seq;
+                            let id = RequestId {
+                                session,
+                                source,
+                                seq,
+                            };
 
                             // Evaluate whether we have hit a breakpoint. This will
-                            // pause the current session if we have.
-                            debug.evaluate_break(session, source, world);
+                            // pause the current session if we have. An input that
+                            // has already been stepped past its breakpoint should
+                            // not trigger it again.
+                            if !debug_roster.is_stepping(id) {
+                                debug.evaluate_break(session, source, world);
+                            }
 
                             let mut is_paused = debug.is_paused(session, world);
                             if is_paused {
                                 // We need to track this request inside the debug roster
-                                if debug_roster.is_allowed(RequestId {
-                                    session,
-                                    source,
-                                    seq,
-                                }) {
+                                if debug_roster.is_allowed(id) {
                                     // If this input has been given permission to
                                     // to be taken, change is_paused to false so
                                     // it will be passed along.
@@ -546,7 +571,14 @@ impl ManageInput for World {
                         }
                     })
                 }
-            })
+            })?;
+
+            #[cfg(feature = "diagnostics")]
+            if let Some(input) = &input {
+                OperationMetrics::input_taken(self, source, input.session, input.seq);
+            }
+
+            Ok(input)
         }
     }
 
@@ -613,6 +645,11 @@ impl ManageInput for World {
                 .reverse_queue
                 .retain(|Input { session: s, .. }| *s != session);
         }
+
+        #[cfg(feature = "diagnostics")]
+        {
+            OperationMetrics::inputs_cleared(self, source, session);
+        }
     }
 
     fn increment_input_seq<T: 'static + Send + Sync>(
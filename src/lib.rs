@@ -98,6 +98,11 @@ pub mod debug;
 #[cfg(feature = "trace")]
 pub use debug::*;
 
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::*;
+
 #[cfg(feature = "diagram")]
 pub mod diagram;
 #[cfg(feature = "diagram")]
@@ -194,6 +199,9 @@ pub const fn trace_supported() -> bool {
     false
 }
 
+pub mod timer;
+pub use timer::*;
+
 pub mod trim;
 pub use trim::*;
 
@@ -456,9 +464,19 @@ impl Plugin for CrossflowPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, flush_execution());
 
+        #[cfg(feature = "diagram")]
+        {
+            app.add_systems(Update, reload_diagram_services);
+        }
+
+        #[cfg(feature = "grpc_server")]
+        {
+            app.add_systems(Update, serve_grpc_calls);
+        }
+
         #[cfg(feature = "trace")]
         {
-            app.add_event::<TracedEvent>();
+            app.add_event::<TracedEvent>().init_resource::<TraceRunId>();
         }
     }
 }
@@ -467,6 +485,10 @@ impl Plugin for CrossflowPlugin {
 /// more plugins that allow create a sufficient but minimal app for executing
 /// workflows.
 ///
+/// Delay and timeout operations need the [`Time`](bevy_time::Time) resource,
+/// which this plugin does not add. If your app does not already have it, add
+/// [`TimePlugin`](bevy_time::TimePlugin) alongside this plugin.
+///
 /// Use [`CrossflowPlugin`] if you want to use crossflow as a library within an
 /// existing app. Use [`CrossflowExecutorApp`] if you want to set up an app from
 /// scratch whose main purpose is to execute workflows.
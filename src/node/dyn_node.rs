@@ -74,6 +74,20 @@ impl DynInputSlot {
         &self.type_info
     }
 
+    pub fn into_input<T>(self) -> Result<InputSlot<T>, TypeMismatch>
+    where
+        T: Send + Sync + 'static + Any,
+    {
+        if self.type_info != TypeInfo::of::<T>() {
+            Err(TypeMismatch {
+                source_type: TypeInfo::of::<T>(),
+                target_type: self.type_info,
+            })
+        } else {
+            Ok(InputSlot::<T>::new(self.scope, self.source))
+        }
+    }
+
     #[cfg(feature = "diagram")]
     pub(crate) fn new(scope: Entity, source: Entity, type_info: TypeInfo) -> Self {
         Self {
@@ -253,6 +267,18 @@ impl DynStreamOutputPack {
         self.named.remove(name)
     }
 
+    /// Same as [`Self::take_named`] but gives an error that lists the
+    /// available streams if the name is not available.
+    #[cfg(feature = "diagram")]
+    pub fn take_stream(&mut self, name: &str) -> Result<DynOutput, crate::DiagramErrorCode> {
+        self.take_named(name).ok_or_else(|| {
+            crate::DiagramErrorCode::MissingStream(crate::MissingStream {
+                missing_name: name.into(),
+                available_names: self.available_names().map(|n| n.clone().into()).collect(),
+            })
+        })
+    }
+
     /// Add an anonymous stream output to this pack.
     pub fn add_anonymous(&mut self, output: impl Into<DynOutput>) {
         let output: DynOutput = output.into();
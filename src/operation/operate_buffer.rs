@@ -101,7 +101,17 @@ where
                     buffer.force_push(data);
                 },
             )
-            .or_broken()
+            .or_broken()?;
+
+        #[cfg(feature = "diagnostics")]
+        {
+            if let Some(storage) = world.get::<BufferStorage<T>>(source) {
+                let queued = storage.count(session);
+                crate::OperationMetrics::buffer_queued(world, source, queued);
+            }
+        }
+
+        Ok(())
     }
 
     fn cleanup(mut clean: OperationCleanup) -> OperationResult {
@@ -78,6 +78,9 @@ pub(crate) struct OperateTask<Response: 'static + Send + Sync, Streams: StreamPa
     disposal: Option<Disposal>,
     being_cleaned: Option<Cleanup>,
     finished_normally: bool,
+    /// When the task was created, used to measure its duration
+    #[cfg(feature = "diagnostics")]
+    started: std::time::Instant,
     _ignore: std::marker::PhantomData<fn(Streams)>,
 }
 
@@ -103,6 +106,8 @@ impl<Response: 'static + Send + Sync, Streams: StreamPack> OperateTask<Response,
             disposal: None,
             being_cleaned: None,
             finished_normally: false,
+            #[cfg(feature = "diagnostics")]
+            started: std::time::Instant::now(),
             _ignore: Default::default(),
         }
     }
@@ -234,6 +239,8 @@ where
         let node = operation.node();
         let request_id = operation.request_id;
         let being_cleaned = operation.being_cleaned;
+        #[cfg(feature = "diagnostics")]
+        let started = operation.started;
         // We take out unblock here just in case the entity gets despawned and/or
         // the OperateTask component gets dropped before we reach the end of the
         // function.
@@ -254,6 +261,11 @@ where
                 // Task has finished. We will defer its input until after the
                 // ChannelQueue has been processed so that any streams from this
                 // task will be delivered before the final output.
+                #[cfg(feature = "diagnostics")]
+                {
+                    crate::OperationMetrics::task_finished(world, node, started.elapsed());
+                }
+
                 let port = output_port::next();
                 let route = request_id.to_message_route(&port, target);
                 let r = world.defer_input(route, result, roster);
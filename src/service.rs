@@ -216,7 +216,7 @@ impl<Request, Response, Streams> Service<Request, Response, Streams> {
     /// - Commands::spawn_*_service
     /// - Commands::spawn_workflow
     /// - ServiceDiscovery::iter()
-    fn new(entity: Entity) -> Self {
+    pub(crate) fn new(entity: Entity) -> Self {
         Self {
             provider: entity,
             _ignore: Default::default(),
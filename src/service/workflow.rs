@@ -79,6 +79,11 @@ impl WorkflowStorage {
     pub(crate) fn new(scope: Entity) -> Self {
         Self { scope }
     }
+
+    #[cfg(feature = "trace")]
+    pub(crate) fn scope(&self) -> Entity {
+        self.scope
+    }
 }
 
 pub(crate) struct WorkflowService<Request, Response, Streams> {
@@ -0,0 +1,203 @@
+/*
+ * Copyright (C) 2026 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_ecs::prelude::{Commands, Component, Entity, Query, Res, World};
+use bevy_time::Time;
+
+use backtrace::Backtrace;
+
+use tokio::sync::oneshot;
+
+use thiserror::Error as ThisError;
+
+use std::{sync::Arc, time::Duration};
+
+use crate::{InputSlot, MiscellaneousFailure, Output, Reply, UnhandledErrors};
+
+/// Timers in crossflow are driven by the [`Time`] resource of the world, which
+/// is normally provided by [`TimePlugin`](bevy_time::TimePlugin). This error
+/// means that resource is missing.
+#[derive(ThisError, Debug, Clone, Copy)]
+#[error(
+    "timers need a Time resource in the world, e.g. by adding bevy_time::TimePlugin to the app"
+)]
+pub struct TimeUnavailable;
+
+/// The ports of a timeout created by [`Builder::create_timeout`].
+///
+/// [`Builder::create_timeout`]: crate::Builder::create_timeout
+pub struct Timeout<T> {
+    /// Messages passed into this slot will start the timer.
+    pub input: InputSlot<T>,
+    /// Messages that are passed into [`Self::input`] will immediately come out
+    /// of this output. Connect it to the target that is being watched.
+    pub output: Output<T>,
+    /// This will be triggered if the target was still active when the timer
+    /// expired. The activity of the target will be trimmed before this
+    /// trigger is sent.
+    pub on_timeout: Output<()>,
+}
+
+/// A timer waiting for the [`Time`] resource to reach its deadline.
+#[derive(Component)]
+pub(crate) struct SleepTimer {
+    deadline: Duration,
+    sender: Option<oneshot::Sender<()>>,
+}
+
+/// Start a timer that will finish once `duration` has elapsed according to
+/// the [`Time`] resource of the world.
+pub(crate) fn start_timer(
+    world: &mut World,
+    duration: Duration,
+) -> Result<Reply<()>, TimeUnavailable> {
+    let now = world
+        .get_resource::<Time>()
+        .ok_or(TimeUnavailable)?
+        .elapsed();
+    let (sender, receiver) = oneshot::channel();
+    world.spawn(SleepTimer {
+        deadline: now + duration,
+        sender: Some(sender),
+    });
+
+    Ok(Reply::new(receiver))
+}
+
+/// Start a timer on behalf of an operation. If the timer cannot be started,
+/// the problem will be reported to [`UnhandledErrors`] and [`None`] will be
+/// returned.
+pub(crate) fn start_operation_timer(world: &mut World, duration: Duration) -> Option<Reply<()>> {
+    match start_timer(world, duration) {
+        Ok(timer) => Some(timer),
+        Err(err) => {
+            world
+                .get_resource_or_init::<UnhandledErrors>()
+                .miscellaneous
+                .push(MiscellaneousFailure {
+                    error: Arc::new(err.into()),
+                    backtrace: Some(Backtrace::new()),
+                });
+            None
+        }
+    }
+}
+
+pub(crate) fn wake_sleep_timers(
+    time: Option<Res<Time>>,
+    mut timers: Query<(Entity, &mut SleepTimer)>,
+    mut commands: Commands,
+) {
+    let Some(time) = time else {
+        return;
+    };
+
+    let now = time.elapsed();
+    for (entity, mut timer) in &mut timers {
+        if timer.deadline <= now {
+            if let Some(sender) = timer.sender.take() {
+                let _ = sender.send(());
+            }
+            commands.entity(entity).despawn();
+        } else if timer.sender.as_ref().is_none_or(|s| s.is_closed()) {
+            // Whoever was waiting on this timer is gone, e.g. because its
+            // session was cancelled.
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::*, testing::*};
+    use bevy_time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_delay_uses_virtual_time() {
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            let delay = builder.create_delay::<i32>(Duration::from_secs(3));
+            builder.connect(scope.start, delay.input);
+            builder.connect(delay.output, scope.terminate);
+        });
+
+        let mut outcome = context.command(|commands| commands.request(5, workflow).outcome());
+
+        // Each update advances the virtual clock by a fixed amount, no matter
+        // how much time has passed on the wall clock.
+        context.run_with_conditions(&mut outcome, 20);
+        assert!(outcome.is_pending());
+
+        context.run_with_conditions(&mut outcome, 20);
+        assert_eq!(outcome.try_recv().unwrap().unwrap(), 5);
+        assert!(context.no_unhandled_errors());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            let slow = builder.create_delay::<i32>(Duration::from_secs(60));
+            let timeout = builder.create_timeout::<i32>(Duration::from_secs(1), slow.input);
+            builder.connect(scope.start, timeout.input);
+            builder.connect(timeout.output, slow.input);
+            builder.connect(slow.output, scope.terminate);
+
+            builder
+                .chain(timeout.on_timeout)
+                .map_block(|_| -1)
+                .connect(scope.terminate);
+        });
+
+        let r = context.try_resolve_request(5, workflow, 30).unwrap();
+        assert_eq!(r, -1);
+
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            let fast = builder.create_delay::<i32>(Duration::from_millis(200));
+            let timeout = builder.create_timeout::<i32>(Duration::from_secs(1), fast.input);
+            builder.connect(scope.start, timeout.input);
+            builder.connect(timeout.output, fast.input);
+
+            // The workflow is still running when the timer expires, but the
+            // target has already finished, so the timeout should not fire.
+            let downstream = builder.create_delay::<i32>(Duration::from_secs(2));
+            builder.connect(fast.output, downstream.input);
+            builder.connect(downstream.output, scope.terminate);
+
+            builder
+                .chain(timeout.on_timeout)
+                .map_block(|_| -1)
+                .connect(scope.terminate);
+        });
+
+        let r = context.try_resolve_request(5, workflow, 40).unwrap();
+        assert_eq!(r, 5);
+    }
+}
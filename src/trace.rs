@@ -38,6 +38,18 @@ use std::{
 };
 use thiserror::Error as ThisError;
 
+mod jsonl;
+pub use jsonl::*;
+
+mod otlp;
+pub use otlp::*;
+
+mod record;
+pub use record::*;
+
+mod replay;
+pub use replay::*;
+
 /// The trace toggle settings of this resource override any other trace settings.
 /// This is typically used to turn on debugging in cases where tracing is not
 /// normally used.
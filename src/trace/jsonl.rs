@@ -0,0 +1,337 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::prelude::{Commands, Res, ResMut, Resource, Trigger, World};
+
+use backtrace::Backtrace;
+
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{MiscellaneousFailure, TraceRecord, TraceRunId, TracedEvent, UnhandledErrors};
+
+/// Stream every [`TracedEvent`] into a file as [JSON Lines](https://jsonlines.org/),
+/// where each line is one [`TraceRecord`].
+///
+/// This plugin only records the events. Operations still need to have tracing
+/// turned on, e.g. with the `default_trace` setting of a diagram or with
+/// [`UniversalTraceToggle`](crate::UniversalTraceToggle).
+///
+/// When a maximum file size is set, the file will be rotated once it would
+/// exceed that size: `trace.jsonl` gets renamed to `trace.jsonl.1`, the
+/// previous `trace.jsonl.1` gets renamed to `trace.jsonl.2`, and so on up to
+/// the maximum number of rotated files. Anything older is deleted.
+///
+/// Problems with writing the file are reported to [`UnhandledErrors`].
+#[derive(Debug, Clone)]
+pub struct JsonlTracePlugin {
+    path: PathBuf,
+    max_file_size: Option<u64>,
+    max_rotated_files: usize,
+}
+
+impl JsonlTracePlugin {
+    /// Write the trace to the file at `path`. If the file already exists, new
+    /// events will be appended to it.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_file_size: None,
+            max_rotated_files: 5,
+        }
+    }
+
+    /// Rotate the file before it grows larger than `bytes`. By default the file
+    /// is never rotated.
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Keep at most `count` rotated files. The default is 5.
+    pub fn with_max_rotated_files(mut self, count: usize) -> Self {
+        self.max_rotated_files = count;
+        self
+    }
+}
+
+impl Plugin for JsonlTracePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(JsonlTraceWriter {
+            settings: self.clone(),
+            file: None,
+            size: 0,
+        })
+        .init_resource::<TraceRunId>()
+        .add_observer(write_jsonl_trace)
+        .add_systems(Last, flush_jsonl_trace);
+    }
+}
+
+#[derive(Resource)]
+struct JsonlTraceWriter {
+    settings: JsonlTracePlugin,
+    file: Option<BufWriter<File>>,
+    /// How many bytes are in the current file
+    size: u64,
+}
+
+impl JsonlTraceWriter {
+    fn write(&mut self, line: &[u8]) -> std::io::Result<()> {
+        // Open the file before checking its size so that a file left over from
+        // an earlier run is accounted for.
+        if self.file.is_none() {
+            self.file = Some(self.open()?);
+        }
+
+        if let Some(max_file_size) = self.settings.max_file_size
+            && self.size > 0
+            && self.size + line.len() as u64 > max_file_size
+        {
+            self.rotate()?;
+            self.file = Some(self.open()?);
+        }
+
+        if let Some(file) = &mut self.file {
+            file.write_all(line)?;
+        }
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn open(&mut self) -> std::io::Result<BufWriter<File>> {
+        let path = &self.settings.path;
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.size = file.metadata()?.len();
+        Ok(BufWriter::new(file))
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+
+        let path = &self.settings.path;
+        let max = self.settings.max_rotated_files;
+        if max == 0 {
+            std::fs::remove_file(path)?;
+        } else {
+            for i in (1..max).rev() {
+                let from = rotated_path(path, i);
+                if from.exists() {
+                    std::fs::rename(from, rotated_path(path, i + 1))?;
+                }
+            }
+            std::fs::rename(path, rotated_path(path, 1))?;
+        }
+
+        self.size = 0;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(format!(".{index}"));
+    path.into()
+}
+
+fn write_jsonl_trace(
+    trigger: Trigger<TracedEvent>,
+    mut writer: ResMut<JsonlTraceWriter>,
+    run_id: Res<TraceRunId>,
+    mut commands: Commands,
+) {
+    let record = TraceRecord::new(trigger.event(), *run_id);
+    let result = serde_json::to_vec(&record)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut line| {
+            line.push(b'\n');
+            writer.write(&line).map_err(anyhow::Error::from)
+        });
+
+    if let Err(err) = result {
+        // Try to reopen the file the next time around.
+        writer.file = None;
+        report_error(err, &mut commands);
+    }
+}
+
+fn flush_jsonl_trace(mut writer: ResMut<JsonlTraceWriter>, mut commands: Commands) {
+    if let Err(err) = writer.flush() {
+        writer.file = None;
+        report_error(err.into(), &mut commands);
+    }
+}
+
+fn report_error(error: anyhow::Error, commands: &mut Commands) {
+    let backtrace = Backtrace::new();
+    commands.queue(move |world: &mut World| {
+        world
+            .get_resource_or_init::<UnhandledErrors>()
+            .miscellaneous
+            .push(MiscellaneousFailure {
+                error: Arc::new(error.context("failed to write the JSONL trace file")),
+                backtrace: Some(backtrace),
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        JsonlTracePlugin, TraceEventRecord, TraceRecord, TraceRunId, UniversalTraceToggle,
+        testing::*,
+    };
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn read_records(path: &Path) -> Vec<TraceRecord> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn run_traced_workflow(context: &mut TestingContext) {
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            builder
+                .chain(scope.start)
+                .map_block(|value: i64| value + 1)
+                .connect(scope.terminate);
+        });
+
+        let r = context.try_resolve_request(1_i64, workflow, ()).unwrap();
+        assert_eq!(r, 2);
+        // Make sure the flush system gets a chance to run
+        context.run(1);
+    }
+
+    #[test]
+    fn test_jsonl_trace_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(UniversalTraceToggle::with_messages())
+            .add_plugins(JsonlTracePlugin::new(&path));
+
+        run_traced_workflow(&mut context);
+        assert!(context.no_unhandled_errors());
+
+        let records = read_records(&path);
+        assert!(
+            records
+                .iter()
+                .any(|r| matches!(&r.event, TraceEventRecord::MessageSent(_)))
+        );
+        let run_id = *context.app.world().resource::<TraceRunId>();
+        assert!(records.iter().all(|r| r.run_id == run_id));
+        assert!(
+            records
+                .iter()
+                .any(|r| matches!(&r.event, TraceEventRecord::SessionEvent(_)))
+        );
+    }
+
+    #[test]
+    fn test_jsonl_trace_rotation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(UniversalTraceToggle::with_messages())
+            .add_plugins(
+                JsonlTracePlugin::new(&path)
+                    .with_max_file_size(256)
+                    .with_max_rotated_files(2),
+            );
+
+        for _ in 0..5 {
+            run_traced_workflow(&mut context);
+        }
+        assert!(context.no_unhandled_errors());
+
+        let mut files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["trace.jsonl", "trace.jsonl.1", "trace.jsonl.2"]);
+
+        // Every rotated file must still contain complete records.
+        for file in &files {
+            read_records(&dir.path().join(file));
+        }
+    }
+
+    #[test]
+    fn test_jsonl_trace_rotates_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let leftover = "x".repeat(300);
+        std::fs::write(&path, &leftover).unwrap();
+
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(UniversalTraceToggle::with_messages())
+            .add_plugins(
+                JsonlTracePlugin::new(&path)
+                    .with_max_file_size(256)
+                    .with_max_rotated_files(100),
+            );
+
+        run_traced_workflow(&mut context);
+        assert!(context.no_unhandled_errors());
+
+        // The leftover file was already over the limit, so it must be rotated
+        // out untouched before anything new gets appended to it.
+        let mut found_leftover = false;
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let file = entry.unwrap().path();
+            if std::fs::read_to_string(&file).unwrap() == leftover {
+                found_leftover = true;
+            } else {
+                read_records(&file);
+            }
+        }
+        assert!(found_leftover);
+    }
+}
@@ -0,0 +1,564 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::prelude::{Commands, Res, ResMut, Resource, Trigger, World};
+
+use backtrace::Backtrace;
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    MiscellaneousFailure, SessionChangeRecord, TraceEntityId, TraceEventRecord, TraceRecord,
+    TraceRunId, TracedEvent, UnhandledErrors,
+};
+
+/// Converts [`TraceRecord`]s into spans that follow the
+/// [OTLP JSON encoding](https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding).
+///
+/// Each session becomes a span that starts when the session is spawned and
+/// ends when it is despawned. The parent of each span is the session that
+/// spawned it, according to the session stack of the event. All spans that
+/// descend from the same root session share one trace ID, which is derived
+/// from the [`TraceRunId`] of the records and the root session, so the same run
+/// gets the same trace IDs no matter which collector exports it. Each message
+/// sent into an operation of a session becomes an event of that session's span.
+#[derive(Debug, Default)]
+pub struct OtlpSpanCollector {
+    open: HashMap<TraceEntityId, OpenSpan>,
+    finished: Vec<OtlpSpan>,
+}
+
+#[derive(Debug)]
+struct OpenSpan {
+    session_stack: Vec<TraceEntityId>,
+    span: OtlpSpan,
+}
+
+impl OtlpSpanCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the spans based on a newly recorded event.
+    pub fn record(&mut self, record: &TraceRecord) {
+        let time = record.unix_time_ns.to_string();
+        match &record.event {
+            TraceEventRecord::SessionEvent(event) => {
+                let Some(session) = event.session() else {
+                    return;
+                };
+
+                match &event.change {
+                    SessionChangeRecord::Spawned { scope } => {
+                        let name = scope
+                            .as_ref()
+                            .map(|scope| {
+                                scope
+                                    .operation
+                                    .id
+                                    .as_ref()
+                                    .map(|id| id.to_string())
+                                    .unwrap_or_else(|| scope.operation.operation_type.clone())
+                            })
+                            .unwrap_or_else(|| "session".to_owned());
+
+                        let parent_span_id = event
+                            .session_stack
+                            .len()
+                            .checked_sub(2)
+                            .map(|i| span_id(event.session_stack[i]))
+                            .unwrap_or_default();
+
+                        let span = OtlpSpan {
+                            trace_id: trace_id(record.run_id, &event.session_stack),
+                            span_id: span_id(session),
+                            parent_span_id,
+                            name,
+                            kind: OtlpSpan::KIND_INTERNAL,
+                            start_time_unix_nano: time.clone(),
+                            end_time_unix_nano: time,
+                            attributes: vec![OtlpKeyValue::int(
+                                "crossflow.session",
+                                session.0 as i64,
+                            )],
+                            events: Vec::new(),
+                            status: OtlpStatus::default(),
+                        };
+
+                        self.open.insert(
+                            session,
+                            OpenSpan {
+                                session_stack: event.session_stack.clone(),
+                                span,
+                            },
+                        );
+                    }
+                    SessionChangeRecord::Cancelled { cancellation, .. } => {
+                        if let Some(open) = self.open.get_mut(&session) {
+                            open.span.status = OtlpStatus {
+                                message: cancellation.clone(),
+                                code: OtlpStatus::CODE_ERROR,
+                            };
+                        }
+                    }
+                    SessionChangeRecord::Despawned => {
+                        self.finish(session, &time);
+                    }
+                    _ => {}
+                }
+            }
+            TraceEventRecord::MessageSent(message) => {
+                let Some(session) = message.input.session_stack.last() else {
+                    return;
+                };
+
+                let Some(open) = self.open.get_mut(session) else {
+                    return;
+                };
+
+                let operation = &message.input.operation;
+                let mut attributes = vec![OtlpKeyValue::string(
+                    "crossflow.operation_type",
+                    operation.operation_type.clone(),
+                )];
+                if let Some(message_type) = &operation.message_type {
+                    attributes.push(OtlpKeyValue::string(
+                        "crossflow.message_type",
+                        message_type.clone(),
+                    ));
+                }
+
+                open.span.events.push(OtlpSpanEvent {
+                    time_unix_nano: time,
+                    name: operation
+                        .id
+                        .as_ref()
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|| operation.operation_type.clone()),
+                    attributes,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Take all the spans that have finished since the last time this was
+    /// called.
+    pub fn take_finished(&mut self) -> Vec<OtlpSpan> {
+        std::mem::take(&mut self.finished)
+    }
+
+    fn finish(&mut self, session: TraceEntityId, time: &str) {
+        // Descendent sessions get despawned along with their parent, so their
+        // spans end here too.
+        let ended: Vec<_> = self
+            .open
+            .iter()
+            .filter(|(_, open)| open.session_stack.contains(&session))
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in ended {
+            if let Some(mut open) = self.open.remove(&id) {
+                open.span.end_time_unix_nano = time.to_owned();
+                self.finished.push(open.span);
+            }
+        }
+    }
+}
+
+/// Entity IDs get reused between runs of an app, so the run ID is mixed in to
+/// keep the traces of different runs apart.
+fn trace_id(run_id: TraceRunId, session_stack: &[TraceEntityId]) -> String {
+    let root = session_stack.first().map(|s| s.0).unwrap_or_default();
+    format!("{:032x}", run_id.0.as_u128() ^ u128::from(root))
+}
+
+fn span_id(session: TraceEntityId) -> String {
+    format!("{:016x}", session.0)
+}
+
+/// The top-level message of an OTLP trace export, equivalent to
+/// `ExportTraceServiceRequest`. This is what gets posted to the `/v1/traces`
+/// endpoint of a collector.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpTraceRequest {
+    pub resource_spans: Vec<OtlpResourceSpans>,
+}
+
+impl OtlpTraceRequest {
+    /// Bundle spans into a request on behalf of a service.
+    pub fn new(service_name: &str, spans: Vec<OtlpSpan>) -> Self {
+        Self {
+            resource_spans: vec![OtlpResourceSpans {
+                resource: OtlpResource {
+                    attributes: vec![OtlpKeyValue::string("service.name", service_name)],
+                },
+                scope_spans: vec![OtlpScopeSpans {
+                    scope: OtlpInstrumentationScope {
+                        name: "crossflow".to_owned(),
+                        version: env!("CARGO_PKG_VERSION").to_owned(),
+                    },
+                    spans,
+                }],
+            }],
+        }
+    }
+
+    /// Iterate over every span in the request.
+    pub fn spans(&self) -> impl Iterator<Item = &OtlpSpan> {
+        self.resource_spans
+            .iter()
+            .flat_map(|r| &r.scope_spans)
+            .flat_map(|s| &s.spans)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpResourceSpans {
+    pub resource: OtlpResource,
+    pub scope_spans: Vec<OtlpScopeSpans>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpResource {
+    pub attributes: Vec<OtlpKeyValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpScopeSpans {
+    pub scope: OtlpInstrumentationScope,
+    pub spans: Vec<OtlpSpan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpInstrumentationScope {
+    pub name: String,
+    pub version: String,
+}
+
+/// A span in the OTLP JSON encoding. IDs are hex-encoded and 64-bit
+/// timestamps are strings, as required by the encoding.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    /// Empty for spans that have no parent.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub parent_span_id: String,
+    pub name: String,
+    pub kind: u32,
+    pub start_time_unix_nano: String,
+    pub end_time_unix_nano: String,
+    #[serde(default)]
+    pub attributes: Vec<OtlpKeyValue>,
+    #[serde(default)]
+    pub events: Vec<OtlpSpanEvent>,
+    #[serde(default)]
+    pub status: OtlpStatus,
+}
+
+impl OtlpSpan {
+    pub const KIND_INTERNAL: u32 = 1;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpSpanEvent {
+    pub time_unix_nano: String,
+    pub name: String,
+    #[serde(default)]
+    pub attributes: Vec<OtlpKeyValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpStatus {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+    #[serde(default)]
+    pub code: u32,
+}
+
+impl OtlpStatus {
+    pub const CODE_UNSET: u32 = 0;
+    pub const CODE_ERROR: u32 = 2;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OtlpKeyValue {
+    pub key: String,
+    pub value: OtlpAnyValue,
+}
+
+impl OtlpKeyValue {
+    pub fn string(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: OtlpAnyValue::StringValue(value.into()),
+        }
+    }
+
+    pub fn int(key: impl Into<String>, value: i64) -> Self {
+        Self {
+            key: key.into(),
+            value: OtlpAnyValue::IntValue(value.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OtlpAnyValue {
+    StringValue(String),
+    /// 64-bit integers are encoded as strings.
+    IntValue(String),
+}
+
+/// Implement this to send spans to a collector, e.g. by posting them to its
+/// `/v1/traces` endpoint.
+pub trait OtlpSpanExporter: 'static + Send + Sync {
+    fn export(&mut self, request: OtlpTraceRequest) -> Result<(), anyhow::Error>;
+}
+
+/// Append each export request as one line of JSON to a file. This is the
+/// format read by the `otlpjsonfile` receiver of the OpenTelemetry collector.
+#[derive(Debug, Clone)]
+pub struct OtlpJsonFileExporter {
+    path: PathBuf,
+}
+
+impl OtlpJsonFileExporter {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OtlpSpanExporter for OtlpJsonFileExporter {
+    fn export(&mut self, request: OtlpTraceRequest) -> Result<(), anyhow::Error> {
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Collect spans from every [`TracedEvent`] with an [`OtlpSpanCollector`] and
+/// hand finished spans to an [`OtlpSpanExporter`] once per update.
+///
+/// Session events are always traced, but message events only appear for
+/// operations that have tracing turned on.
+pub struct OtlpSpanPlugin {
+    service_name: String,
+    exporter: Mutex<Option<Box<dyn OtlpSpanExporter>>>,
+}
+
+impl OtlpSpanPlugin {
+    pub fn new(service_name: impl Into<String>, exporter: impl OtlpSpanExporter) -> Self {
+        Self {
+            service_name: service_name.into(),
+            exporter: Mutex::new(Some(Box::new(exporter))),
+        }
+    }
+}
+
+impl Plugin for OtlpSpanPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(exporter) = self.exporter.lock().unwrap().take() else {
+            return;
+        };
+
+        app.insert_resource(OtlpSpanExport {
+            service_name: self.service_name.clone(),
+            collector: OtlpSpanCollector::new(),
+            exporter,
+        })
+        .init_resource::<TraceRunId>()
+        .add_observer(collect_otlp_spans)
+        .add_systems(Last, export_otlp_spans);
+    }
+}
+
+#[derive(Resource)]
+struct OtlpSpanExport {
+    service_name: String,
+    collector: OtlpSpanCollector,
+    exporter: Box<dyn OtlpSpanExporter>,
+}
+
+fn collect_otlp_spans(
+    trigger: Trigger<TracedEvent>,
+    mut export: ResMut<OtlpSpanExport>,
+    run_id: Res<TraceRunId>,
+) {
+    export
+        .collector
+        .record(&TraceRecord::new(trigger.event(), *run_id));
+}
+
+fn export_otlp_spans(mut export: ResMut<OtlpSpanExport>, mut commands: Commands) {
+    let spans = export.collector.take_finished();
+    if spans.is_empty() {
+        return;
+    }
+
+    let request = OtlpTraceRequest::new(&export.service_name, spans);
+    if let Err(err) = export.exporter.export(request) {
+        let backtrace = Backtrace::new();
+        commands.queue(move |world: &mut World| {
+            world
+                .get_resource_or_init::<UnhandledErrors>()
+                .miscellaneous
+                .push(MiscellaneousFailure {
+                    error: Arc::new(err.context("failed to export OTLP spans")),
+                    backtrace: Some(backtrace),
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        OtlpSpanExporter, OtlpSpanPlugin, OtlpStatus, OtlpTraceRequest, TraceRunId,
+        UniversalTraceToggle, testing::*,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Stands in for a collector by keeping every request it receives.
+    #[derive(Clone, Default)]
+    struct CollectorStandIn(Arc<Mutex<Vec<OtlpTraceRequest>>>);
+
+    impl OtlpSpanExporter for CollectorStandIn {
+        fn export(&mut self, request: OtlpTraceRequest) -> Result<(), anyhow::Error> {
+            // Make sure the request survives the trip through JSON
+            let json = serde_json::to_string(&request)?;
+            self.0.lock().unwrap().push(serde_json::from_str(&json)?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_otlp_session_spans() {
+        let collector = CollectorStandIn::default();
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(UniversalTraceToggle::on())
+            .add_plugins(OtlpSpanPlugin::new("test", collector.clone()));
+
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            builder
+                .chain(scope.start)
+                .map_block(|value: i64| value * 2)
+                .connect(scope.terminate);
+        });
+
+        let r = context.try_resolve_request(3_i64, workflow, ()).unwrap();
+        assert_eq!(r, 6);
+        context.run(2);
+        assert!(context.no_unhandled_errors());
+
+        let requests = collector.0.lock().unwrap();
+        let spans: Vec<_> = requests.iter().flat_map(|r| r.spans()).collect();
+
+        // The request session and the scoped session of the workflow
+        assert_eq!(spans.len(), 2);
+        let root = spans
+            .iter()
+            .find(|span| span.parent_span_id.is_empty())
+            .unwrap();
+        let child = spans
+            .iter()
+            .find(|span| span.parent_span_id == root.span_id)
+            .unwrap();
+        assert_eq!(root.trace_id, child.trace_id);
+        assert!(!child.events.is_empty());
+        assert!(
+            root.start_time_unix_nano.parse::<u64>().unwrap()
+                <= child.start_time_unix_nano.parse::<u64>().unwrap()
+        );
+        assert!(
+            child.end_time_unix_nano.parse::<u64>().unwrap()
+                >= child.start_time_unix_nano.parse::<u64>().unwrap()
+        );
+        assert_eq!(child.status.code, OtlpStatus::CODE_UNSET);
+
+        // Trace IDs come from the run, so they can be reproduced from the
+        // records of the run.
+        let run_id = *context.app.world().resource::<TraceRunId>();
+        assert_eq!(
+            u128::from_str_radix(&root.trace_id, 16).unwrap() ^ run_id.0.as_u128(),
+            u128::from_str_radix(&root.span_id, 16).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_otlp_cancelled_session_span() {
+        let collector = CollectorStandIn::default();
+        let mut context = TestingContext::minimal_plugins();
+        context
+            .app
+            .insert_resource(UniversalTraceToggle::on())
+            .add_plugins(OtlpSpanPlugin::new("test", collector.clone()));
+
+        let workflow = context.spawn_io_workflow(|scope, builder| {
+            builder
+                .chain(scope.start)
+                .map_block(produce_err::<i64>)
+                .cancel_on_err()
+                .connect(scope.terminate);
+        });
+
+        let r = context.try_resolve_request(3_i64, workflow, ());
+        assert!(r.is_err());
+        context.run(2);
+        assert!(context.no_unhandled_errors());
+
+        let requests = collector.0.lock().unwrap();
+        let spans: Vec<_> = requests.iter().flat_map(|r| r.spans()).collect();
+        let root = spans
+            .iter()
+            .find(|span| span.parent_span_id.is_empty())
+            .unwrap();
+        let child = spans
+            .iter()
+            .find(|span| span.parent_span_id == root.span_id)
+            .unwrap();
+        assert_eq!(child.status.code, OtlpStatus::CODE_ERROR);
+        assert!(child.status.message.contains("unacceptable Err value"));
+    }
+}
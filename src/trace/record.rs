@@ -0,0 +1,445 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_ecs::prelude::{Entity, Resource};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::{
+    BufferAccessRecord, BufferEvent, IdentifierRef, JsonMessage, MessageSent, OperationInfo,
+    OperationRef, OutputDisposed, OutputRef, PauseCause, Seq, SessionChange, SessionEvent,
+    TraceBuffer, TraceSource, TraceTarget, TracedEvent, TracedEventKind, TracedMessage,
+};
+
+/// A serializable snapshot of a [`TracedEvent`].
+///
+/// [`TracedEvent`] refers to live [`Entity`] values and shared data that can
+/// only be used inside the app that produced it. This record owns all of its
+/// data so it can be written to a file or sent over a network, then read back
+/// later for offline analysis.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct TraceRecord {
+    /// The run of the app that produced the event. Every [`TraceEntityId`] in
+    /// the event is only meaningful together with this.
+    pub run_id: TraceRunId,
+    /// Wall clock time of the event as nanoseconds since the UNIX epoch.
+    pub unix_time_ns: u64,
+    pub event: TraceEventRecord,
+}
+
+impl TraceRecord {
+    /// Make a record of an event that was produced during the run `run_id`.
+    /// Use the [`TraceRunId`] resource of the app that produced the event.
+    pub fn new(event: &TracedEvent, run_id: TraceRunId) -> Self {
+        let unix_time_ns = event
+            .time
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Self {
+            run_id,
+            unix_time_ns,
+            event: (&event.event).into(),
+        }
+    }
+
+    /// Get the wall clock time of the event.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + std::time::Duration::from_nanos(self.unix_time_ns)
+    }
+}
+
+/// Serializable form of [`TracedEventKind`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEventRecord {
+    MessageSent(MessageSentRecord),
+    BufferEvent(BufferEventRecord),
+    SessionEvent(SessionEventRecord),
+    OutputDisposed(OutputDisposedRecord),
+    Broken(BrokenRecord),
+}
+
+impl From<&TracedEventKind> for TraceEventRecord {
+    fn from(value: &TracedEventKind) -> Self {
+        match value {
+            TracedEventKind::MessageSent(event) => Self::MessageSent(event.into()),
+            TracedEventKind::BufferEvent(event) => Self::BufferEvent(event.into()),
+            TracedEventKind::SessionEvent(event) => Self::SessionEvent(event.into()),
+            TracedEventKind::OutputDisposed(event) => Self::OutputDisposed(event.into()),
+            TracedEventKind::Broken(broken) => Self::Broken(BrokenRecord {
+                node: broken.node.into(),
+                backtrace: broken.backtrace.as_ref().map(|b| format!("{b:?}")),
+            }),
+        }
+    }
+}
+
+/// A randomly generated identifier for one run of an app. This is inserted as a
+/// resource by [`CrossflowPlugin`](crate::CrossflowPlugin) and stamped onto
+/// every [`TraceRecord`], so records that were archived from different runs or
+/// different machines can be told apart.
+#[derive(Resource, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct TraceRunId(#[schemars(with = "String")] pub Uuid);
+
+impl Default for TraceRunId {
+    fn default() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// Identifies an entity, e.g. a session or an operation, within one run of the
+/// app that produced the trace. Despawned entities are never identified by the
+/// same value again during that run, even if their index gets reused, but other
+/// runs will reuse the same values. Use the [`TraceRunId`] of the record
+/// together with this to identify an entity across runs.
+#[derive(
+    Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(transparent)]
+pub struct TraceEntityId(pub u64);
+
+impl TraceEntityId {
+    /// Get the entity that this refers to, if this was produced by the
+    /// current app.
+    pub fn entity(self) -> Option<Entity> {
+        Entity::try_from_bits(self.0).ok()
+    }
+}
+
+impl From<Entity> for TraceEntityId {
+    fn from(value: Entity) -> Self {
+        Self(value.to_bits())
+    }
+}
+
+fn session_stack_record(stack: &[Entity]) -> Vec<TraceEntityId> {
+    stack.iter().copied().map(Into::into).collect()
+}
+
+/// Serializable form of [`TracedMessage`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageRecord {
+    /// The message was serialized successfully.
+    Value(JsonMessage),
+    /// The message should have been traced, but it could not be serialized.
+    Error(String),
+}
+
+fn message_record(message: &TracedMessage) -> Option<MessageRecord> {
+    message.as_ref().map(|message| match message {
+        Ok(value) => MessageRecord::Value(value.clone()),
+        Err(err) => MessageRecord::Error(err.to_string()),
+    })
+}
+
+/// Serializable form of [`OperationInfo`] plus the identity of the operation.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct OperationRecord {
+    pub entity: TraceEntityId,
+    pub operation_type: String,
+    /// The unique identifier of the operation within its workflow, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<OperationRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+}
+
+impl OperationRecord {
+    fn new(entity: Entity, operation_type: &str, info: Option<&OperationInfo>) -> Self {
+        Self {
+            entity: entity.into(),
+            operation_type: operation_type.to_owned(),
+            id: info.and_then(|info| info.id().clone()),
+            message_type: info.and_then(|info| info.message_type().as_ref().map(|t| t.to_string())),
+        }
+    }
+}
+
+/// Serializable form of [`TraceSource`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct TraceSourceRecord {
+    pub session_stack: Vec<TraceEntityId>,
+    pub operation: OperationRecord,
+    pub seq: Seq,
+    pub port: Vec<IdentifierRef<'static>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<OutputRef>,
+}
+
+impl From<&TraceSource> for TraceSourceRecord {
+    fn from(value: &TraceSource) -> Self {
+        Self {
+            session_stack: session_stack_record(&value.session_stack),
+            operation: OperationRecord::new(
+                value.source,
+                &value.operation_type,
+                value.info.as_deref(),
+            ),
+            seq: value.seq,
+            port: value.port.to_vec(),
+            labels: value
+                .labels
+                .as_ref()
+                .map(|l| l.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Serializable form of [`TraceTarget`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct TraceTargetRecord {
+    pub session_stack: Vec<TraceEntityId>,
+    pub operation: OperationRecord,
+    pub seq: Seq,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<OperationRef>,
+}
+
+impl From<&TraceTarget> for TraceTargetRecord {
+    fn from(value: &TraceTarget) -> Self {
+        Self {
+            session_stack: session_stack_record(&value.session_stack),
+            operation: OperationRecord::new(
+                value.target,
+                &value.operation_type,
+                value.info.as_deref(),
+            ),
+            seq: value.seq,
+            labels: value
+                .labels
+                .as_ref()
+                .map(|l| l.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Serializable form of [`MessageSent`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct MessageSentRecord {
+    pub output: Vec<TraceSourceRecord>,
+    pub input: TraceTargetRecord,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<MessageRecord>,
+}
+
+impl From<&MessageSent> for MessageSentRecord {
+    fn from(value: &MessageSent) -> Self {
+        Self {
+            output: value.output.iter().map(Into::into).collect(),
+            input: (&value.input).into(),
+            message: message_record(&value.message),
+        }
+    }
+}
+
+/// Serializable form of [`OutputDisposed`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct OutputDisposedRecord {
+    pub trigger: TraceSourceRecord,
+    pub disposed_operation: TraceEntityId,
+    pub disposed_in_session: Vec<TraceEntityId>,
+    /// Description of why the output was disposed.
+    pub disposal: String,
+}
+
+impl From<&OutputDisposed> for OutputDisposedRecord {
+    fn from(value: &OutputDisposed) -> Self {
+        Self {
+            trigger: (&value.trigger).into(),
+            disposed_operation: value.disposed_operation.into(),
+            disposed_in_session: session_stack_record(&value.disposed_in_session),
+            disposal: value.disposal.to_string(),
+        }
+    }
+}
+
+/// Serializable form of [`TraceBuffer`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct TraceBufferRecord {
+    pub session_stack: Vec<TraceEntityId>,
+    pub id: TraceEntityId,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<OperationRef>,
+}
+
+impl From<&TraceBuffer> for TraceBufferRecord {
+    fn from(value: &TraceBuffer) -> Self {
+        Self {
+            session_stack: session_stack_record(&value.session_stack),
+            id: value.id.into(),
+            labels: value
+                .labels
+                .as_ref()
+                .map(|l| l.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Serializable form of [`BufferEvent`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct BufferEventRecord {
+    pub accessor: TraceTargetRecord,
+    pub buffer: TraceBufferRecord,
+    pub access: BufferAccessKindRecord,
+}
+
+impl From<&BufferEvent> for BufferEventRecord {
+    fn from(value: &BufferEvent) -> Self {
+        Self {
+            accessor: (&value.accessor).into(),
+            buffer: (&value.buffer).into(),
+            access: (&value.access).into(),
+        }
+    }
+}
+
+/// Serializable form of [`BufferAccessRecord`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "access", rename_all = "snake_case")]
+pub enum BufferAccessKindRecord {
+    Viewed,
+    Modified {
+        seq: Seq,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        original: Option<MessageRecord>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        modified: Option<MessageRecord>,
+    },
+    Pushed {
+        seq: Seq,
+        position: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<MessageRecord>,
+    },
+    Removed {
+        seq: Seq,
+    },
+}
+
+impl From<&BufferAccessRecord> for BufferAccessKindRecord {
+    fn from(value: &BufferAccessRecord) -> Self {
+        match value {
+            BufferAccessRecord::Viewed => Self::Viewed,
+            BufferAccessRecord::Modified(modified) => Self::Modified {
+                seq: modified.seq,
+                original: message_record(&modified.original),
+                modified: message_record(&modified.modified),
+            },
+            BufferAccessRecord::Pushed(pushed) => Self::Pushed {
+                seq: pushed.seq,
+                position: pushed.position,
+                message: message_record(&pushed.message),
+            },
+            BufferAccessRecord::Removed(removed) => Self::Removed { seq: removed.seq },
+        }
+    }
+}
+
+/// Serializable form of [`SessionEvent`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct SessionEventRecord {
+    pub session_stack: Vec<TraceEntityId>,
+    pub change: SessionChangeRecord,
+}
+
+impl SessionEventRecord {
+    /// The session that this event is about.
+    pub fn session(&self) -> Option<TraceEntityId> {
+        self.session_stack.last().copied()
+    }
+}
+
+impl From<&SessionEvent> for SessionEventRecord {
+    fn from(value: &SessionEvent) -> Self {
+        Self {
+            session_stack: session_stack_record(&value.session_stack),
+            change: (&value.change).into(),
+        }
+    }
+}
+
+/// Serializable form of [`SessionChange`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum SessionChangeRecord {
+    Spawned {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<TraceTargetRecord>,
+    },
+    Terminated {
+        source: TraceSourceRecord,
+    },
+    Cancelled {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<TraceSourceRecord>,
+        /// Description of the cancellation cause.
+        cancellation: String,
+    },
+    BeginCleanup,
+    Despawned,
+    Paused {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        breakpoint: Option<TraceEntityId>,
+    },
+    Unpaused,
+}
+
+impl From<&SessionChange> for SessionChangeRecord {
+    fn from(value: &SessionChange) -> Self {
+        match value {
+            SessionChange::Spawned { scope } => Self::Spawned {
+                scope: scope.as_ref().map(Into::into),
+            },
+            SessionChange::Terminated { source } => Self::Terminated {
+                source: source.into(),
+            },
+            SessionChange::Cancelled {
+                source,
+                cancellation,
+            } => Self::Cancelled {
+                source: source.as_ref().map(Into::into),
+                cancellation: cancellation.to_string(),
+            },
+            SessionChange::BeginCleanup => Self::BeginCleanup,
+            SessionChange::Despawned => Self::Despawned,
+            SessionChange::Paused(cause) => Self::Paused {
+                breakpoint: match cause {
+                    PauseCause::UserRequest => None,
+                    PauseCause::Breakpoint(breakpoint) => Some((*breakpoint).into()),
+                },
+            },
+            SessionChange::Unpaused => Self::Unpaused,
+        }
+    }
+}
+
+/// Serializable form of [`Broken`](crate::Broken).
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct BrokenRecord {
+    pub node: TraceEntityId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+}
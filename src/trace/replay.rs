@@ -0,0 +1,685 @@
+/*
+ * Copyright (C) 2025 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy_ecs::prelude::Commands;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::BufRead,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    BuilderContext, Diagram, DiagramElementRegistry, DiagramError, DiagramErrorCode, DynInputSlot,
+    DynOutput, JsonMessage, MessageRecord, NodeMetadata, OperationRef, Seq, Service, StreamPack,
+    TraceEntityId, TraceEventRecord, TraceRecord, TraceRunId, TypeInfo,
+    diagram::WorkflowBuildOptions, output_port,
+};
+
+/// Re-run a diagram with some of its nodes replaced by the messages that they
+/// produced in a recorded trace.
+///
+/// The trace must have been recorded with [`TraceToggle::Messages`](crate::TraceToggle::Messages)
+/// turned on for the replaced nodes and for whatever operations receive their
+/// outputs, otherwise there will be no message payloads to replay.
+///
+/// Instead of running, each replaced node compares every input it receives
+/// against the inputs that were recorded for it and then sends out the output
+/// that was recorded for the matching input. Any mismatch is reported as a
+/// [`ReplayDivergence`]. Stream outputs of replaced nodes are not replayed.
+///
+/// This is a cheap handle to shared state, so you can keep a clone of it to
+/// check [`Self::divergences`] after the workflow has run.
+#[derive(Clone, Default)]
+pub struct DiagramReplay {
+    inner: Arc<Mutex<ReplayState>>,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    /// Every node call found in the trace, regardless of whether it is replaced.
+    recorded: HashMap<OperationRef, VecDeque<RecordedCall>>,
+    replaced: HashSet<OperationRef>,
+    divergences: Vec<ReplayDivergence>,
+}
+
+#[derive(Debug, Default)]
+struct RecordedCall {
+    /// The input message, if its payload was recorded.
+    input: Option<JsonMessage>,
+    /// The output that the call produced. This is [`None`] if the call never
+    /// produced an output. The inner value is [`None`] if the output was
+    /// produced but its payload was not recorded.
+    output: Option<Option<JsonMessage>>,
+}
+
+impl DiagramReplay {
+    /// Prepare a replay from the records of a trace, e.g. the records written
+    /// by [`JsonlTracePlugin`](crate::JsonlTracePlugin).
+    pub fn new(records: impl IntoIterator<Item = TraceRecord>) -> Self {
+        // Calls are identified by the session that the input arrived in and
+        // the sequence number that the node assigned to it. The node uses the
+        // same sequence number when it sends out its output. Sessions are only
+        // unique within the run that recorded them.
+        let mut calls: HashMap<(OperationRef, TraceRunId, TraceEntityId, Seq), usize> =
+            HashMap::new();
+        let mut recorded: HashMap<OperationRef, VecDeque<RecordedCall>> = HashMap::new();
+
+        let payload = |message: &Option<MessageRecord>| match message {
+            Some(MessageRecord::Value(value)) => Some(value.clone()),
+            _ => None,
+        };
+
+        for record in records {
+            let run_id = record.run_id;
+            let TraceEventRecord::MessageSent(sent) = record.event else {
+                continue;
+            };
+
+            if let (Some(operation), Some(session)) = (
+                &sent.input.operation.id,
+                sent.input.session_stack.last().copied(),
+            ) {
+                let node_calls = recorded.entry(operation.clone()).or_default();
+                calls.insert(
+                    (operation.clone(), run_id, session, sent.input.seq),
+                    node_calls.len(),
+                );
+                node_calls.push_back(RecordedCall {
+                    input: payload(&sent.message),
+                    output: None,
+                });
+            }
+
+            for source in &sent.output {
+                if source.port.as_slice() != output_port::next().as_slice() {
+                    continue;
+                }
+
+                let (Some(operation), Some(session)) =
+                    (&source.operation.id, source.session_stack.last().copied())
+                else {
+                    continue;
+                };
+
+                let Some(index) = calls.get(&(operation.clone(), run_id, session, source.seq))
+                else {
+                    continue;
+                };
+
+                if let Some(call) = recorded
+                    .get_mut(operation)
+                    .and_then(|node_calls| node_calls.get_mut(*index))
+                {
+                    call.output = Some(payload(&sent.message));
+                }
+            }
+        }
+
+        Self {
+            inner: Arc::new(Mutex::new(ReplayState {
+                recorded,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Prepare a replay from a trace in [JSON Lines](https://jsonlines.org/)
+    /// format, where each line is one [`TraceRecord`].
+    pub fn from_jsonl(reader: impl BufRead) -> std::io::Result<Self> {
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            records.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(Self::new(records))
+    }
+
+    /// Replace the node with this ID by the outputs that were recorded for it.
+    pub fn replace(self, operation: impl Into<OperationRef>) -> Self {
+        self.lock().replaced.insert(operation.into());
+        self
+    }
+
+    /// Check whether the node with this ID will be replaced.
+    pub fn is_replaced(&self, operation: &OperationRef) -> bool {
+        self.lock().replaced.contains(operation)
+    }
+
+    /// Get every divergence from the recorded trace that has been observed so
+    /// far. Recorded calls of replaced nodes that have not happened during the
+    /// replay are reported as [`ReplayDivergenceKind::MissingInput`], so this
+    /// should be checked after the replayed sessions are finished.
+    pub fn divergences(&self) -> Vec<ReplayDivergence> {
+        let state = self.lock();
+        let mut divergences = state.divergences.clone();
+        let mut replaced: Vec<_> = state.replaced.iter().collect();
+        replaced.sort();
+        for operation in replaced {
+            let Some(calls) = state.recorded.get(operation) else {
+                continue;
+            };
+
+            for call in calls {
+                divergences.push(ReplayDivergence {
+                    operation: operation.clone(),
+                    kind: ReplayDivergenceKind::MissingInput {
+                        expected: call.input.clone(),
+                    },
+                });
+            }
+        }
+
+        divergences
+    }
+
+    /// Create the operations that stand in for a replaced node. Returns
+    /// [`None`] if the node is not being replaced.
+    pub(crate) fn create_node(
+        &self,
+        operation: OperationRef,
+        metadata: &NodeMetadata,
+        ctx: &mut BuilderContext,
+    ) -> Result<Option<(DynInputSlot, DynOutput)>, DiagramErrorCode> {
+        if !self.is_replaced(&operation) {
+            return Ok(None);
+        }
+
+        let request = ctx
+            .registry
+            .messages
+            .get_type_info_for(metadata.request())?;
+        let response = ctx
+            .registry
+            .messages
+            .get_type_info_for(metadata.response())?;
+
+        // Serialize the request if possible so it can be compared against the
+        // recorded input. Otherwise we can only count the calls.
+        let (input, replay_output): (DynInputSlot, DynOutput) =
+            if request == TypeInfo::of::<JsonMessage>() {
+                let replay = self.clone();
+                let op = operation.clone();
+                let node = ctx
+                    .builder
+                    .create_map_block(move |input: JsonMessage| replay.call(&op, Some(input)));
+                (node.input.into(), node.output.into())
+            } else if let Some(serialize) =
+                ctx.registry.messages.try_serialize(&request, ctx.builder)?
+            {
+                let replay = self.clone();
+                let op = operation.clone();
+                let node = ctx
+                    .builder
+                    .create_map_block(move |input: JsonMessage| replay.call(&op, Some(input)));
+                serialize.ok.connect_to(&node.input.into(), ctx.builder)?;
+
+                let error_target = ctx.get_implicit_error_target();
+                ctx.add_output_into_target(error_target, serialize.err);
+                (serialize.input, node.output.into())
+            } else {
+                let trigger = (ctx
+                    .registry
+                    .messages
+                    .get_operations(&request)?
+                    .create_trigger_impl)(ctx.builder);
+                let replay = self.clone();
+                let op = operation.clone();
+                let node = ctx
+                    .builder
+                    .create_map_block(move |_: ()| replay.call(&op, None));
+                trigger.output.connect_to(&node.input.into(), ctx.builder)?;
+                (trigger.input, node.output.into())
+            };
+
+        // Calls that did not produce an output in the recording will not
+        // produce one during the replay either.
+        let replay_output = ctx
+            .builder
+            .chain(replay_output.into_output::<Option<JsonMessage>>()?)
+            .dispose_on_none()
+            .output();
+
+        if response == TypeInfo::of::<JsonMessage>() {
+            return Ok(Some((input, replay_output.into())));
+        }
+
+        let deserialize = ctx.registry.messages.deserialize(&response, ctx.builder)?;
+        DynOutput::from(replay_output).connect_to(&deserialize.input, ctx.builder)?;
+
+        let error_target = ctx.get_implicit_error_target();
+        ctx.add_output_into_target(error_target, deserialize.err);
+        Ok(Some((input, deserialize.ok)))
+    }
+
+    /// A replaced node has received an input. Get the output that was recorded
+    /// for it.
+    fn call(&self, operation: &OperationRef, input: Option<JsonMessage>) -> Option<JsonMessage> {
+        let mut state = self.lock();
+        let ReplayState {
+            recorded,
+            divergences,
+            ..
+        } = &mut *state;
+
+        let mut diverge = |kind| {
+            divergences.push(ReplayDivergence {
+                operation: operation.clone(),
+                kind,
+            });
+        };
+
+        let calls = recorded.entry(operation.clone()).or_default();
+
+        // Prefer the earliest call with an identical input so that concurrent
+        // sessions do not need to arrive in exactly the recorded order.
+        let matching = input
+            .as_ref()
+            .and_then(|actual| {
+                calls
+                    .iter()
+                    .position(|call| call.input.as_ref() == Some(actual))
+            })
+            .unwrap_or(0);
+
+        let Some(call) = calls.remove(matching) else {
+            diverge(ReplayDivergenceKind::UnexpectedInput { actual: input });
+            return None;
+        };
+
+        if let (Some(expected), Some(actual)) = (&call.input, &input)
+            && expected != actual
+        {
+            diverge(ReplayDivergenceKind::InputMismatch {
+                expected: expected.clone(),
+                actual: actual.clone(),
+            });
+        }
+
+        match call.output {
+            Some(Some(output)) => Some(output),
+            Some(None) => {
+                diverge(ReplayDivergenceKind::OutputNotRecorded);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A difference between a replayed session and the trace it was recorded in.
+#[derive(ThisError, Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[error("operation {operation} diverged from the recording: {kind}")]
+pub struct ReplayDivergence {
+    /// The replaced node where the divergence was observed.
+    pub operation: OperationRef,
+    pub kind: ReplayDivergenceKind,
+}
+
+#[derive(ThisError, Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayDivergenceKind {
+    /// The node received an input that differs from the recorded input. The
+    /// recorded output was still sent out.
+    #[error("expected input {expected} but received {actual}")]
+    InputMismatch {
+        expected: JsonMessage,
+        actual: JsonMessage,
+    },
+    /// The node received more inputs than were recorded for it. No output was
+    /// sent out. The input is [`None`] if its type cannot be serialized.
+    #[error("received an input that was never recorded: {actual:?}")]
+    UnexpectedInput { actual: Option<JsonMessage> },
+    /// A recorded input never arrived during the replay.
+    #[error("never received the recorded input {expected:?}")]
+    MissingInput { expected: Option<JsonMessage> },
+    /// The recorded call produced an output but its payload was not part of
+    /// the trace, so nothing could be sent out.
+    #[error("the output payload was not recorded")]
+    OutputNotRecorded,
+}
+
+impl Diagram {
+    /// Spawn a workflow from this diagram where the nodes selected by `replay`
+    /// send out recorded messages instead of running.
+    ///
+    /// Use [`DiagramReplay::divergences`] once the session is finished to see
+    /// where the replay differed from the recording.
+    pub fn spawn_replay_workflow<Request, Response, Streams>(
+        &self,
+        cmds: &mut Commands,
+        registry: &DiagramElementRegistry,
+        replay: &DiagramReplay,
+    ) -> Result<Service<Request, Response, Streams>, DiagramError>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+        Streams: StreamPack,
+    {
+        let options = WorkflowBuildOptions {
+            replay: Some(replay.clone()),
+        };
+
+        self.spawn_workflow_with_options(cmds, registry, &options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        CancellationCause, DiagramReplay, OperationName, OperationRef, ReplayDivergenceKind,
+        TraceRecord, TraceRunId, TracedEvent, diagram::testing::*, prelude::*, stream::tests::*,
+    };
+    use bevy_ecs::prelude::{Res, Trigger};
+    use serde_json::json;
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    fn record_trace(fixture: &mut DiagramTestFixture) -> Arc<Mutex<Vec<TraceRecord>>> {
+        let records: Arc<Mutex<Vec<TraceRecord>>> = Default::default();
+        let sink = Arc::clone(&records);
+        fixture.context.app.add_observer(
+            move |trigger: Trigger<TracedEvent>, run_id: Res<TraceRunId>| {
+                sink.lock()
+                    .unwrap()
+                    .push(TraceRecord::new(trigger.event(), *run_id));
+            },
+        );
+        records
+    }
+
+    #[test]
+    fn test_replay_recorded_node() {
+        let mut fixture = DiagramTestFixture::new();
+        let records = record_trace(&mut fixture);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("sensor"),
+            move |builder: &mut Builder, _config: ()| {
+                let counter = Arc::clone(&counter);
+                builder.create_map_block(move |request: i64| {
+                    // Pretend the sensor reading depends on the hardware.
+                    let n = counter.fetch_add(1, Ordering::SeqCst) as i64;
+                    request * 100 + n
+                })
+            },
+        );
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "sensor",
+            "default_trace": "messages",
+            "ops": {
+                "sensor": {
+                    "type": "node",
+                    "builder": "sensor",
+                    "next": "add",
+                },
+                "add": {
+                    "type": "node",
+                    "builder": "add_to",
+                    "config": 1,
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let recorded: i64 = fixture.spawn_and_run(&diagram, 3_i64).unwrap();
+        assert_eq!(recorded, 301);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let records = std::mem::take(&mut *records.lock().unwrap());
+        let replay = DiagramReplay::new(records.clone()).replace(&OperationName::from("sensor"));
+
+        // The real sensor would now give a different reading, but the replay
+        // reproduces the recorded one without calling it.
+        let workflow = fixture
+            .context
+            .command(|cmds| {
+                diagram.spawn_replay_workflow::<i64, i64, ()>(cmds, &fixture.registry, &replay)
+            })
+            .unwrap();
+        let replayed = fixture
+            .context
+            .try_resolve_request(3_i64, workflow, ())
+            .unwrap();
+        assert_eq!(replayed, 301);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(fixture.context.no_unhandled_errors());
+        assert!(replay.divergences().is_empty());
+
+        // A different request diverges from the recording.
+        let replay = DiagramReplay::new(records).replace(&OperationName::from("sensor"));
+        let workflow = fixture
+            .context
+            .command(|cmds| {
+                diagram.spawn_replay_workflow::<i64, i64, ()>(cmds, &fixture.registry, &replay)
+            })
+            .unwrap();
+        let replayed = fixture
+            .context
+            .try_resolve_request(4_i64, workflow, ())
+            .unwrap();
+        assert_eq!(replayed, 301);
+
+        let divergences = replay.divergences();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(
+            divergences[0].kind,
+            ReplayDivergenceKind::InputMismatch {
+                expected: json!(3),
+                actual: json!(4),
+            }
+        );
+    }
+
+    fn register_sensor(fixture: &mut DiagramTestFixture) -> Arc<AtomicUsize> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        fixture.registry.register_node_builder(
+            NodeBuilderOptions::new("streaming_sensor"),
+            move |builder: &mut Builder, _config: ()| {
+                let counter = Arc::clone(&counter);
+                builder.create_map(move |input: Blocking<i64, TestStreamPack>| {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) as i64;
+                    input.streams.stream_string.send(format!("reading {n}"));
+                    input.request * 100 + n
+                })
+            },
+        );
+        calls
+    }
+
+    #[test]
+    fn test_replay_with_streams_and_buffers() {
+        let mut fixture = DiagramTestFixture::new();
+        let records = record_trace(&mut fixture);
+        let calls = register_sensor(&mut fixture);
+
+        let diagram = Diagram::from_json(json!({
+            "version": "0.1.0",
+            "start": "sensor",
+            "default_trace": "messages",
+            "ops": {
+                "sensor": {
+                    "type": "node",
+                    "builder": "streaming_sensor",
+                    "next": "buffer",
+                    "stream_out": {
+                        "stream_string": "log",
+                    },
+                },
+                "log": {
+                    "type": "stream_out",
+                    "name": "stream_string",
+                },
+                "buffer": {
+                    "type": "buffer",
+                },
+                "join": {
+                    "type": "join",
+                    "buffers": ["buffer"],
+                    "next": { "builtin": "terminate" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let (recorded, receivers) = fixture
+            .spawn_and_run_with_streams::<i64, JsonMessage, TestStreamPack>(
+                &diagram,
+                3_i64,
+                Duration::from_secs(2),
+            )
+            .unwrap();
+        assert_eq!(
+            collect_received_values(receivers.stream_string),
+            ["reading 0"]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let records = std::mem::take(&mut *records.lock().unwrap());
+        let replay = DiagramReplay::new(records).replace(&OperationName::from("sensor"));
+        let workflow = fixture
+            .context
+            .command(|cmds| {
+                diagram.spawn_replay_workflow::<i64, JsonMessage, TestStreamPack>(
+                    cmds,
+                    &fixture.registry,
+                    &replay,
+                )
+            })
+            .unwrap();
+
+        let mut capture = fixture
+            .context
+            .command(|cmds| cmds.request(3_i64, workflow).capture());
+        fixture
+            .context
+            .run_with_conditions(&mut capture.outcome, Duration::from_secs(2));
+
+        // The recorded output still passes through the buffer and join, but
+        // the stream of the replaced node is not replayed.
+        let replayed = capture.outcome.try_recv().unwrap().unwrap();
+        assert_eq!(replayed, recorded);
+        assert!(collect_received_values(capture.streams.stream_string).is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(fixture.context.no_unhandled_errors());
+        assert!(replay.divergences().is_empty());
+    }
+
+    #[test]
+    fn test_replay_trace_of_different_diagram() {
+        let mut fixture = DiagramTestFixture::new();
+        let records = record_trace(&mut fixture);
+        let calls = register_sensor(&mut fixture);
+
+        let diagram = |node: &str| {
+            Diagram::from_json(json!({
+                "version": "0.1.0",
+                "start": node,
+                "default_trace": "messages",
+                "ops": {
+                    node: {
+                        "type": "node",
+                        "builder": "streaming_sensor",
+                        "next": { "builtin": "terminate" },
+                    },
+                },
+            }))
+            .unwrap()
+        };
+
+        let recorded: i64 = fixture.spawn_and_run(&diagram("sensor"), 3_i64).unwrap();
+        assert_eq!(recorded, 300);
+
+        // The trace has nothing recorded for "reader", so it cannot produce an
+        // output and the session gets cancelled.
+        let records = std::mem::take(&mut *records.lock().unwrap());
+        let replay = DiagramReplay::new(records)
+            .replace(&OperationName::from("sensor"))
+            .replace(&OperationName::from("reader"));
+        let workflow = fixture
+            .context
+            .command(|cmds| {
+                diagram("reader").spawn_replay_workflow::<i64, i64, ()>(
+                    cmds,
+                    &fixture.registry,
+                    &replay,
+                )
+            })
+            .unwrap();
+
+        let mut outcome = fixture
+            .context
+            .command(|cmds| cmds.request(3_i64, workflow).outcome());
+        fixture
+            .context
+            .run_with_conditions(&mut outcome, Duration::from_millis(100));
+        let cancellation = outcome.try_recv().unwrap().unwrap_err();
+        assert!(matches!(
+            *cancellation.cause,
+            CancellationCause::Unreachable(_)
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let divergences = replay.divergences();
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(
+            divergences[0].operation,
+            OperationRef::from(&OperationName::from("reader"))
+        );
+        assert_eq!(
+            divergences[0].kind,
+            ReplayDivergenceKind::UnexpectedInput {
+                actual: Some(json!(3)),
+            }
+        );
+        assert_eq!(
+            divergences[1].operation,
+            OperationRef::from(&OperationName::from("sensor"))
+        );
+        assert_eq!(
+            divergences[1].kind,
+            ReplayDivergenceKind::MissingInput {
+                expected: Some(json!(3)),
+            }
+        );
+    }
+}
@@ -15,7 +15,7 @@
  *
 */
 
-use crate::InputSlot;
+use crate::{InputSlot, dyn_node::DynInputSlot};
 
 use bevy_ecs::prelude::Entity;
 
@@ -127,6 +127,15 @@ impl TrimPoint {
         Self::new(input, false)
     }
 
+    /// Define where a trim will begin or end using a type-erased input slot.
+    pub fn from_dyn_input(input: &DynInputSlot, inclusive: bool) -> Self {
+        Self {
+            id: input.id(),
+            scope: input.scope(),
+            inclusive,
+        }
+    }
+
     /// Get the ID of this point
     pub fn id(&self) -> Entity {
         self.id